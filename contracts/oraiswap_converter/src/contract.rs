@@ -14,6 +14,7 @@ use oraiswap::converter::{
     QueryMsg, TokenInfo, TokenRatio,
 };
 
+use oraiswap::admin::{accept_admin, propose_admin, query_pending_admin};
 use oraiswap::asset::{Asset, AssetInfo};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -38,6 +39,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::AcceptAdmin {} => accept_owner(deps, info),
         ExecuteMsg::UpdatePair { from, to } => update_pair(deps, info, from, to),
         ExecuteMsg::UnregisterPair { from } => unregister_pair(deps, info, from),
         ExecuteMsg::Convert {} => convert(deps, env, info),
@@ -47,17 +49,25 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
 }
 
 pub fn update_config(deps: DepsMut, info: MessageInfo, owner: Addr) -> StdResult<Response> {
-    let mut config: Config = read_config(deps.storage)?;
+    let config: Config = read_config(deps.storage)?;
 
     if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
         return Err(StdError::generic_err("unauthorized"));
     }
 
-    config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+    // the new owner must accept first
+    propose_admin(deps.storage, deps.api, &owner)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn accept_owner(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = accept_admin(deps.storage, deps.api, &info.sender)?;
 
     store_config(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("action", "update_config"))
+    Ok(Response::new().add_attribute("action", "accept_admin"))
 }
 
 pub fn receive_cw20(
@@ -227,6 +237,7 @@ pub fn convert_reverse(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
         QueryMsg::ConvertInfo { asset_info } => to_binary(&query_convert_info(deps, asset_info)?),
     }
 }
@@ -4,16 +4,17 @@ use std::convert::TryFrom;
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Reply, Response,
-    StdError, StdResult, SubMsg, WasmMsg,
+    to_binary, Addr, Binary, CanonicalAddr, Deps, DepsMut, Env, MessageInfo, Order, Reply,
+    Response, StdError, StdResult, SubMsg, WasmMsg,
 };
+use oraiswap::admin::{accept_admin, propose_admin, query_pending_admin};
 use oraiswap::error::ContractError;
 use oraiswap::querier::query_pair_info_from_pair;
 use oraiswap::response::MsgInstantiateContractResponse;
 
-use crate::state::{read_pairs, Config, CONFIG, PAIRS};
+use crate::state::{read_pairs, read_pairs_by_asset, store_pair_index, Config, CONFIG, PAIRS};
 
-use oraiswap::asset::{pair_key, AssetInfo, PairInfo, PairInfoRaw};
+use oraiswap::asset::{pair_key, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw};
 use oraiswap::factory::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, PairsResponse, QueryMsg,
 };
@@ -56,6 +57,7 @@ pub fn execute(
             token_code_id,
             pair_code_id,
         } => execute_update_config(deps, env, info, owner, token_code_id, pair_code_id),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
         ExecuteMsg::CreatePair { asset_infos } => execute_create_pair(deps, env, info, asset_infos),
     }
 }
@@ -76,8 +78,9 @@ pub fn execute_update_config(
         return Err(ContractError::Unauthorized {});
     }
 
+    // the new owner must accept first
     if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
+        propose_admin(deps.storage, deps.api, &deps.api.addr_validate(&owner)?)?;
     }
 
     if let Some(token_code_id) = token_code_id {
@@ -93,6 +96,14 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    config.owner = accept_admin(deps.storage, deps.api, &info.sender)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "accept_admin"))
+}
+
 // Anyone can execute it to create swap pair
 pub fn execute_create_pair(
     deps: DepsMut,
@@ -113,6 +124,7 @@ pub fn execute_create_pair(
         return Err(ContractError::PairExisted {});
     }
 
+    store_pair_index(deps.storage, &raw_infos)?;
     PAIRS.save(
         deps.storage,
         &pair_key,
@@ -188,10 +200,16 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
         QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
         QueryMsg::Pairs { start_after, limit } => {
             to_binary(&query_pairs(deps, start_after, limit)?)
         }
+        QueryMsg::PairsByAsset {
+            asset_info,
+            start_after,
+            limit,
+        } => to_binary(&query_pairs_by_asset(deps, asset_info, start_after, limit)?),
     }
 }
 
@@ -236,7 +254,38 @@ pub fn query_pairs(
     Ok(resp)
 }
 
+pub fn query_pairs_by_asset(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<PairsResponse> {
+    let start_after = match start_after {
+        Some(start_after) => Some(start_after.to_raw(deps.api)?),
+        None => None,
+    };
+
+    let pairs: Vec<PairInfo> = read_pairs_by_asset(
+        deps.storage,
+        deps.api,
+        &asset_info.to_raw(deps.api)?,
+        start_after,
+        limit,
+    )?;
+
+    Ok(PairsResponse { pairs })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    // backfill the asset index of the existing pairs
+    let pairs = PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pair_info)| pair_info.asset_infos))
+        .collect::<StdResult<Vec<[AssetInfoRaw; 2]>>>()?;
+    for asset_infos in pairs.iter() {
+        store_pair_index(deps.storage, asset_infos)?;
+    }
+
+    Ok(Response::new().add_attribute("indexed_pairs", pairs.len().to_string()))
 }
@@ -2,7 +2,7 @@ use cosmwasm_schema::cw_serde;
 
 use cosmwasm_std::{Api, CanonicalAddr, Order, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
-use oraiswap::asset::{AssetInfoRaw, PairInfo, PairInfoRaw};
+use oraiswap::asset::{pair_key, AssetInfoRaw, PairInfo, PairInfoRaw};
 
 #[cw_serde]
 pub struct Config {
@@ -19,6 +19,9 @@ pub const CONFIG: Item<Config> = Item::new("\u{0}\u{6}config");
 // store temporary pair info while waiting for deployment
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pairs");
 
+// index of PAIRS by each asset then the other asset of the pair, to the pair key
+pub const PAIRS_BY_ASSET: Map<(&[u8], &[u8]), Vec<u8>> = Map::new("pairs_by_asset");
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -41,6 +44,39 @@ pub fn read_pairs(
         .collect::<StdResult<Vec<PairInfo>>>()
 }
 
+pub fn store_pair_index(
+    storage: &mut dyn Storage,
+    asset_infos: &[AssetInfoRaw; 2],
+) -> StdResult<()> {
+    let pair_key = pair_key(asset_infos);
+    let [first, second] = asset_infos;
+    PAIRS_BY_ASSET.save(storage, (first.as_bytes(), second.as_bytes()), &pair_key)?;
+    PAIRS_BY_ASSET.save(storage, (second.as_bytes(), first.as_bytes()), &pair_key)
+}
+
+pub fn read_pairs_by_asset(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    asset_info: &AssetInfoRaw,
+    start_after: Option<AssetInfoRaw>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PairInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .as_ref()
+        .map(|other_info| Bound::exclusive(other_info.as_bytes()));
+
+    PAIRS_BY_ASSET
+        .prefix(asset_info.as_bytes())
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, pair_key) = item?;
+            PAIRS.load(storage, &pair_key)?.to_normal(api)
+        })
+        .collect::<StdResult<Vec<PairInfo>>>()
+}
+
 // this will set the first key after the provided key, by appending a 1 byte
 fn calc_range_start(start_after: Option<[AssetInfoRaw; 2]>) -> Option<Vec<u8>> {
     start_after.map(|asset_infos| {
@@ -65,7 +101,6 @@ mod test {
     use cosmwasm_storage::{
         bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket,
     };
-    use oraiswap::asset::pair_key;
     use oraiswap::pair::DEFAULT_COMMISSION_RATE;
     const KEY_CONFIG: &[u8] = b"config";
 
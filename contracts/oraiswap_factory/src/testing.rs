@@ -1,10 +1,16 @@
-use oraiswap::asset::{AssetInfo, PairInfo};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Api};
+use oraiswap::asset::{pair_key, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw};
 
 use oraiswap::create_entry_points_testing;
+use oraiswap::factory::{MigrateMsg, PairsResponse, QueryMsg};
 use oraiswap::pair::DEFAULT_COMMISSION_RATE;
 use oraiswap::querier::query_pair_info_from_pair;
 use oraiswap::testing::MockApp;
 
+use crate::contract::{migrate, query_pairs_by_asset};
+use crate::state::PAIRS;
+
 #[test]
 fn create_pair() {
     let mut app = MockApp::new(&[]);
@@ -49,3 +55,119 @@ fn create_pair() {
         }
     );
 }
+
+#[test]
+fn pairs_by_asset() {
+    let mut app = MockApp::new(&[]);
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(create_entry_points_testing!(crate).with_reply(crate::contract::reply)),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let token_infos = ["assetA", "assetB", "assetC"].map(|name| AssetInfo::Token {
+        contract_addr: app.create_token(name),
+    });
+    let orai_info = AssetInfo::NativeToken {
+        denom: "orai".to_string(),
+    };
+
+    let mut pair_addrs = vec![];
+    for token_info in token_infos.iter() {
+        pair_addrs.push(
+            app.create_pair([orai_info.clone(), token_info.clone()])
+                .unwrap(),
+        );
+    }
+    app.create_pair([token_infos[1].clone(), token_infos[2].clone()])
+        .unwrap();
+
+    let query_pairs = |asset_info: &AssetInfo, start_after: Option<AssetInfo>, limit: u32| {
+        let res: PairsResponse = app
+            .as_querier()
+            .query_wasm_smart(
+                app.factory_addr.clone(),
+                &QueryMsg::PairsByAsset {
+                    asset_info: asset_info.clone(),
+                    start_after,
+                    limit: Some(limit),
+                },
+            )
+            .unwrap();
+        res.pairs
+            .into_iter()
+            .map(|pair| pair.contract_addr)
+            .collect::<Vec<_>>()
+    };
+
+    // ordered by the other asset, paged after it
+    let mut orai_pairs = query_pairs(&orai_info, None, 2);
+    assert_eq!(orai_pairs.len(), 2);
+    let last_info = token_infos
+        .iter()
+        .zip(pair_addrs.iter())
+        .find(|(_, pair_addr)| **pair_addr == orai_pairs[1])
+        .map(|(token_info, _)| token_info.clone());
+    orai_pairs.extend(query_pairs(&orai_info, last_info, 2));
+    orai_pairs.sort();
+    pair_addrs.sort();
+    assert_eq!(orai_pairs, pair_addrs);
+
+    assert_eq!(query_pairs(&token_infos[0], None, 10).len(), 1);
+    assert_eq!(query_pairs(&token_infos[1], None, 10).len(), 2);
+}
+
+#[test]
+fn migrate_pairs_by_asset() {
+    let mut deps = mock_dependencies();
+
+    let orai_info = AssetInfoRaw::NativeToken {
+        denom: "orai".to_string(),
+    };
+    for name in ["token0000", "token0001"] {
+        let asset_infos = [
+            orai_info.clone(),
+            AssetInfoRaw::Token {
+                contract_addr: deps.api.addr_canonicalize(name).unwrap(),
+            },
+        ];
+        PAIRS
+            .save(
+                &mut deps.storage,
+                &pair_key(&asset_infos),
+                &PairInfoRaw {
+                    oracle_addr: deps.api.addr_canonicalize("oracle0000").unwrap(),
+                    asset_infos,
+                    contract_addr: deps.api.addr_canonicalize("pair0000").unwrap(),
+                    liquidity_token: deps.api.addr_canonicalize("liquidity0000").unwrap(),
+                    commission_rate: DEFAULT_COMMISSION_RATE.to_string(),
+                },
+            )
+            .unwrap();
+    }
+
+    let orai_info = AssetInfo::NativeToken {
+        denom: "orai".to_string(),
+    };
+    let res = query_pairs_by_asset(deps.as_ref(), orai_info.clone(), None, None).unwrap();
+    assert_eq!(res.pairs, vec![]);
+
+    // existing pairs are indexed by the migration
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    let res = query_pairs_by_asset(deps.as_ref(), orai_info, None, None).unwrap();
+    assert_eq!(res.pairs.len(), 2);
+    let res = query_pairs_by_asset(
+        deps.as_ref(),
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("token0001"),
+        },
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(res.pairs.len(), 1);
+}
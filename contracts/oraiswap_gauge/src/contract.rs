@@ -0,0 +1,358 @@
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Uint128,
+};
+
+use crate::state::{
+    delete_gauge, read_config, read_epoch_height, read_epoch_power, read_gauge, read_gauges,
+    read_last_vote_epoch, read_tally, read_vote, store_config, store_epoch_height,
+    store_epoch_power, store_gauge, store_tally, store_vote, Config, VoteInfo,
+};
+
+use oraiswap::admin::{accept_admin, propose_admin, query_pending_admin};
+use oraiswap::asset::AssetInfo;
+use oraiswap::gauge::{
+    ConfigResponse, ExecuteMsg, GaugeVote, GaugeWeight, GaugesResponse, InstantiateMsg, MigrateMsg,
+    PoolWeightResponse, QueryMsg, VoteResponse, WeightsResponse,
+};
+use oraiswap::staking::{BondAtResponse, QueryMsg as StakingQueryMsg};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    if msg.epoch_duration == 0 {
+        return Err(StdError::generic_err("Epoch duration must be positive"));
+    }
+
+    store_config(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_canonicalize(info.sender.as_str())?,
+            staking_contract: deps.api.addr_canonicalize(msg.staking_contract.as_str())?,
+            vote_asset_info: msg.vote_asset_info.to_raw(deps.api)?,
+            epoch_duration: msg.epoch_duration,
+            start_time: msg.start_time.unwrap_or(env.block.time.seconds()),
+        },
+    )?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    match msg {
+        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::AcceptAdmin {} => accept_owner(deps, info),
+        ExecuteMsg::AddGauge { asset_info } => add_gauge(deps, info, asset_info),
+        ExecuteMsg::RemoveGauge { asset_info } => remove_gauge(deps, info, asset_info),
+        ExecuteMsg::Vote { votes } => vote(deps, env, info, votes),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+pub fn update_config(deps: DepsMut, info: MessageInfo, owner: Option<Addr>) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // the new owner must accept first
+    if let Some(owner) = owner {
+        propose_admin(deps.storage, deps.api, &owner)?;
+    }
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+pub fn accept_owner(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = accept_admin(deps.storage, deps.api, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "accept_admin"))
+}
+
+pub fn add_gauge(deps: DepsMut, info: MessageInfo, asset_info: AssetInfo) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    store_gauge(
+        deps.storage,
+        &asset_info.to_vec(deps.api)?,
+        &asset_info.to_raw(deps.api)?,
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "add_gauge"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+/// the votes for a removed gauge are ignored by the weights
+pub fn remove_gauge(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_key = asset_info.to_vec(deps.api)?;
+    if read_gauge(deps.storage, &asset_key)?.is_none() {
+        return Err(StdError::generic_err("Gauge is not registered"));
+    }
+    delete_gauge(deps.storage, &asset_key);
+
+    Ok(Response::new().add_attributes([
+        ("action", "remove_gauge"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+/// Vote
+/// The voting power is the bond in the vote pool at the first vote of the epoch,
+/// voting again in the same epoch replaces the previous vote
+pub fn vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    votes: Vec<GaugeVote>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let epoch = config.epoch_at(env.block.time.seconds());
+    let voter_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    if votes.is_empty() {
+        return Err(StdError::generic_err("No gauge to vote"));
+    }
+    let mut total_ratio = Decimal::zero();
+    let mut vote_keys: Vec<Vec<u8>> = vec![];
+    for gauge_vote in votes.iter() {
+        let asset_key = gauge_vote.asset_info.to_vec(deps.api)?;
+        if read_gauge(deps.storage, &asset_key)?.is_none() {
+            return Err(StdError::generic_err("Gauge is not registered"));
+        }
+        if vote_keys.contains(&asset_key) {
+            return Err(StdError::generic_err("Duplicate gauge vote"));
+        }
+        if gauge_vote.ratio.is_zero() {
+            return Err(StdError::generic_err("Vote ratio must be positive"));
+        }
+        total_ratio += gauge_vote.ratio;
+        vote_keys.push(asset_key);
+    }
+    if total_ratio > Decimal::one() {
+        return Err(StdError::generic_err("Vote ratios exceed 100%"));
+    }
+
+    // bonds after the snapshot of the epoch do not count
+    let height = match read_epoch_height(deps.storage, epoch)? {
+        Some(height) => height,
+        None => {
+            store_epoch_height(deps.storage, epoch, env.block.height)?;
+            env.block.height
+        }
+    };
+    let bond_at: BondAtResponse = deps.querier.query_wasm_smart(
+        deps.api.addr_humanize(&config.staking_contract)?,
+        &StakingQueryMsg::BondAt {
+            staker_addr: info.sender.clone(),
+            asset_info: config.vote_asset_info.to_normal(deps.api)?,
+            height,
+        },
+    )?;
+    let power = bond_at.bond_amount;
+    if power.is_zero() {
+        return Err(StdError::generic_err("No voting power"));
+    }
+
+    let mut epoch_power = read_epoch_power(deps.storage, epoch)?;
+
+    // remove the previous vote of the epoch
+    if let Some(prev_vote) = read_vote(deps.storage, epoch, &voter_raw)? {
+        for (asset_info, ratio) in prev_vote.votes {
+            let asset_key = asset_info.as_bytes();
+            let tally = read_tally(deps.storage, epoch, asset_key)?;
+            let allocated = prev_vote.power * ratio;
+            store_tally(
+                deps.storage,
+                epoch,
+                asset_key,
+                tally.checked_sub(allocated)?,
+            )?;
+            epoch_power = epoch_power.checked_sub(allocated)?;
+        }
+    }
+
+    for (gauge_vote, asset_key) in votes.iter().zip(vote_keys.iter()) {
+        let tally = read_tally(deps.storage, epoch, asset_key)?;
+        let allocated = power * gauge_vote.ratio;
+        store_tally(deps.storage, epoch, asset_key, tally + allocated)?;
+        epoch_power += allocated;
+    }
+    store_epoch_power(deps.storage, epoch, epoch_power)?;
+
+    store_vote(
+        deps.storage,
+        epoch,
+        &voter_raw,
+        &VoteInfo {
+            power,
+            votes: votes
+                .iter()
+                .map(|gauge_vote| Ok((gauge_vote.asset_info.to_raw(deps.api)?, gauge_vote.ratio)))
+                .collect::<StdResult<Vec<_>>>()?,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "vote"),
+        ("voter", info.sender.as_str()),
+        ("epoch", &epoch.to_string()),
+        ("power", &power.to_string()),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
+        QueryMsg::Gauges {} => to_binary(&query_gauges(deps)?),
+        QueryMsg::Weights { epoch } => to_binary(&query_weights(deps, env, epoch)?),
+        QueryMsg::PoolWeight { asset_info, epoch } => {
+            to_binary(&query_pool_weight(deps, env, asset_info, epoch)?)
+        }
+        QueryMsg::Vote { voter, epoch } => to_binary(&query_vote(deps, env, voter, epoch)?),
+    }
+}
+
+pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
+    let state = read_config(deps.storage)?;
+    let resp = ConfigResponse {
+        owner: deps.api.addr_humanize(&state.owner)?,
+        staking_contract: deps.api.addr_humanize(&state.staking_contract)?,
+        vote_asset_info: state.vote_asset_info.to_normal(deps.api)?,
+        epoch_duration: state.epoch_duration,
+        start_time: state.start_time,
+        current_epoch: state.epoch_at(env.block.time.seconds()),
+    };
+
+    Ok(resp)
+}
+
+pub fn query_gauges(deps: Deps) -> StdResult<GaugesResponse> {
+    let gauges = read_gauges(deps.storage)?
+        .iter()
+        .map(|(_, asset_info)| asset_info.to_normal(deps.api))
+        .collect::<StdResult<Vec<AssetInfo>>>()?;
+
+    Ok(GaugesResponse { gauges })
+}
+
+/// weights of an epoch, from the votes of the latest epoch before it with votes
+pub fn query_weights(deps: Deps, env: Env, epoch: Option<u64>) -> StdResult<WeightsResponse> {
+    let config = read_config(deps.storage)?;
+    let epoch = epoch.unwrap_or(config.epoch_at(env.block.time.seconds()));
+    let vote_epoch = read_last_vote_epoch(deps.storage, epoch)?;
+
+    let mut total_power = Uint128::zero();
+    let mut powers = vec![];
+    for (asset_key, asset_info) in read_gauges(deps.storage)? {
+        let power = match vote_epoch {
+            Some(vote_epoch) => read_tally(deps.storage, vote_epoch, &asset_key)?,
+            None => Uint128::zero(),
+        };
+        total_power += power;
+        powers.push((asset_info.to_normal(deps.api)?, power));
+    }
+
+    let weights = powers
+        .into_iter()
+        .map(|(asset_info, power)| GaugeWeight {
+            asset_info,
+            power,
+            weight: if total_power.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::from_ratio(power, total_power)
+            },
+        })
+        .collect();
+
+    Ok(WeightsResponse {
+        epoch,
+        vote_epoch,
+        total_power,
+        weights,
+    })
+}
+
+pub fn query_pool_weight(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+    epoch: Option<u64>,
+) -> StdResult<PoolWeightResponse> {
+    let weights = query_weights(deps, env, epoch)?;
+    let weight = weights
+        .weights
+        .into_iter()
+        .find(|gauge_weight| gauge_weight.asset_info.eq(&asset_info))
+        .map(|gauge_weight| gauge_weight.weight)
+        .unwrap_or_default();
+
+    Ok(PoolWeightResponse {
+        asset_info,
+        epoch: weights.epoch,
+        weight,
+    })
+}
+
+pub fn query_vote(
+    deps: Deps,
+    env: Env,
+    voter: Addr,
+    epoch: Option<u64>,
+) -> StdResult<VoteResponse> {
+    let config = read_config(deps.storage)?;
+    let epoch = epoch.unwrap_or(config.epoch_at(env.block.time.seconds()));
+    let vote_info = read_vote(
+        deps.storage,
+        epoch,
+        &deps.api.addr_canonicalize(voter.as_str())?,
+    )?
+    .unwrap_or(VoteInfo {
+        power: Uint128::zero(),
+        votes: vec![],
+    });
+
+    Ok(VoteResponse {
+        voter,
+        epoch,
+        power: vote_info.power,
+        votes: vote_info
+            .votes
+            .iter()
+            .map(|(asset_info, ratio)| {
+                Ok(GaugeVote {
+                    asset_info: asset_info.to_normal(deps.api)?,
+                    ratio: *ratio,
+                })
+            })
+            .collect::<StdResult<Vec<GaugeVote>>>()?,
+    })
+}
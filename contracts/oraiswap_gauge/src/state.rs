@@ -0,0 +1,132 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdError, StdResult, Storage, Uint128};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use oraiswap::asset::AssetInfoRaw;
+
+static KEY_CONFIG: &[u8] = b"config";
+static KEY_GAUGE: &[u8] = b"gauge";
+static KEY_EPOCH_HEIGHT: &[u8] = b"epoch_height";
+static KEY_EPOCH_POWER: &[u8] = b"epoch_power";
+static PREFIX_TALLY: &[u8] = b"tally";
+static PREFIX_VOTE: &[u8] = b"vote";
+
+#[cw_serde]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub staking_contract: CanonicalAddr,
+    pub vote_asset_info: AssetInfoRaw,
+    pub epoch_duration: u64,
+    pub start_time: u64,
+}
+
+impl Config {
+    pub fn epoch_at(&self, time: u64) -> u64 {
+        time.saturating_sub(self.start_time) / self.epoch_duration
+    }
+}
+
+#[cw_serde]
+pub struct VoteInfo {
+    pub power: Uint128,
+    pub votes: Vec<(AssetInfoRaw, Decimal)>,
+}
+
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    singleton(storage, KEY_CONFIG).save(config)
+}
+
+pub fn read_config(storage: &dyn Storage) -> StdResult<Config> {
+    singleton_read(storage, KEY_CONFIG).load()
+}
+
+pub fn store_gauge(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    asset_info: &AssetInfoRaw,
+) -> StdResult<()> {
+    Bucket::new(storage, KEY_GAUGE).save(asset_key, asset_info)
+}
+
+pub fn delete_gauge(storage: &mut dyn Storage, asset_key: &[u8]) {
+    Bucket::<AssetInfoRaw>::new(storage, KEY_GAUGE).remove(asset_key)
+}
+
+pub fn read_gauge(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<Option<AssetInfoRaw>> {
+    ReadonlyBucket::new(storage, KEY_GAUGE).may_load(asset_key)
+}
+
+pub fn read_gauges(storage: &dyn Storage) -> StdResult<Vec<(Vec<u8>, AssetInfoRaw)>> {
+    ReadonlyBucket::new(storage, KEY_GAUGE)
+        .range(None, None, Order::Ascending)
+        .collect()
+}
+
+/// height of the voting power snapshot, set by the first vote of the epoch
+pub fn store_epoch_height(storage: &mut dyn Storage, epoch: u64, height: u64) -> StdResult<()> {
+    Bucket::new(storage, KEY_EPOCH_HEIGHT).save(&epoch.to_be_bytes(), &height)
+}
+
+pub fn read_epoch_height(storage: &dyn Storage, epoch: u64) -> StdResult<Option<u64>> {
+    ReadonlyBucket::new(storage, KEY_EPOCH_HEIGHT).may_load(&epoch.to_be_bytes())
+}
+
+pub fn store_epoch_power(storage: &mut dyn Storage, epoch: u64, power: Uint128) -> StdResult<()> {
+    Bucket::new(storage, KEY_EPOCH_POWER).save(&epoch.to_be_bytes(), &power)
+}
+
+pub fn read_epoch_power(storage: &dyn Storage, epoch: u64) -> StdResult<Uint128> {
+    Ok(ReadonlyBucket::new(storage, KEY_EPOCH_POWER)
+        .may_load(&epoch.to_be_bytes())?
+        .unwrap_or_default())
+}
+
+/// latest epoch before the given one with votes
+pub fn read_last_vote_epoch(storage: &dyn Storage, before_epoch: u64) -> StdResult<Option<u64>> {
+    let epoch_powers: ReadonlyBucket<Uint128> = ReadonlyBucket::new(storage, KEY_EPOCH_POWER);
+    let last_vote_epoch = epoch_powers
+        .range(None, Some(&before_epoch.to_be_bytes()), Order::Descending)
+        .find(|item| item.as_ref().map_or(true, |(_, power)| !power.is_zero()))
+        .map(|item| {
+            let (k, _) = item?;
+            let epoch: [u8; 8] = k
+                .try_into()
+                .map_err(|_| StdError::generic_err("Invalid epoch key"))?;
+            Ok(u64::from_be_bytes(epoch))
+        })
+        .transpose();
+    last_vote_epoch
+}
+
+pub fn store_tally(
+    storage: &mut dyn Storage,
+    epoch: u64,
+    asset_key: &[u8],
+    power: Uint128,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_TALLY, &epoch.to_be_bytes()]).save(asset_key, &power)
+}
+
+pub fn read_tally(storage: &dyn Storage, epoch: u64, asset_key: &[u8]) -> StdResult<Uint128> {
+    Ok(
+        ReadonlyBucket::multilevel(storage, &[PREFIX_TALLY, &epoch.to_be_bytes()])
+            .may_load(asset_key)?
+            .unwrap_or_default(),
+    )
+}
+
+pub fn store_vote(
+    storage: &mut dyn Storage,
+    epoch: u64,
+    voter: &CanonicalAddr,
+    vote_info: &VoteInfo,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_VOTE, &epoch.to_be_bytes()]).save(voter, vote_info)
+}
+
+pub fn read_vote(
+    storage: &dyn Storage,
+    epoch: u64,
+    voter: &CanonicalAddr,
+) -> StdResult<Option<VoteInfo>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_VOTE, &epoch.to_be_bytes()]).may_load(voter)
+}
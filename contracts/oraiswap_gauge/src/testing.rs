@@ -0,0 +1,239 @@
+use crate::contract::{execute, instantiate, query_config, query_vote, query_weights};
+use cosmwasm_std::{
+    from_binary,
+    testing::{mock_dependencies, mock_env, mock_info},
+    to_binary, Addr, ContractResult, Decimal, StdError, SystemResult, Uint128, WasmQuery,
+};
+use oraiswap::asset::AssetInfo;
+use oraiswap::gauge::{
+    ConfigResponse, ExecuteMsg, GaugeVote, GaugeWeight, InstantiateMsg, WeightsResponse,
+};
+use oraiswap::staking::{BondAtResponse, QueryMsg as StakingQueryMsg};
+
+fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+fn gauge_vote(denom: &str, ratio: u64) -> GaugeVote {
+    GaugeVote {
+        asset_info: native(denom),
+        ratio: Decimal::percent(ratio),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        vote_asset_info: native("orai"),
+        epoch_duration: 1000,
+        start_time: None,
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // it worked, let's query the state
+    let contract_info = query_config(deps.as_ref(), env.clone()).unwrap();
+
+    assert_eq!(
+        contract_info,
+        ConfigResponse {
+            owner: Addr::unchecked("owner"),
+            staking_contract: Addr::unchecked("staking"),
+            vote_asset_info: native("orai"),
+            epoch_duration: 1000,
+            start_time: env.block.time.seconds(),
+            current_epoch: 0,
+        }
+    );
+}
+
+#[test]
+fn vote_gauge_weights() {
+    let mut deps = mock_dependencies();
+    // carol bonds after the snapshot of the second epoch
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+            StakingQueryMsg::BondAt {
+                staker_addr,
+                asset_info,
+                height,
+            } => {
+                let bond_amount = match staker_addr.as_str() {
+                    "alice" => 300u128,
+                    "bob" => 100u128,
+                    "carol" if height > 12346 => 500u128,
+                    _ => 0u128,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&BondAtResponse {
+                        staker_addr,
+                        asset_info,
+                        height,
+                        bond_amount: Uint128::from(bond_amount),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => panic!("unexpected query"),
+        },
+        _ => panic!("unexpected query"),
+    });
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        vote_asset_info: native("orai"),
+        epoch_duration: 1000,
+        start_time: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    for denom in ["atom", "usdt"] {
+        let msg = ExecuteMsg::AddGauge {
+            asset_info: native(denom),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    }
+
+    let vote = |votes: Vec<GaugeVote>| ExecuteMsg::Vote { votes };
+    for (votes, err) in [
+        (vec![gauge_vote("eth", 100)], "Gauge is not registered"),
+        (
+            vec![gauge_vote("atom", 60), gauge_vote("usdt", 50)],
+            "Vote ratios exceed 100%",
+        ),
+        (
+            vec![gauge_vote("atom", 10), gauge_vote("atom", 10)],
+            "Duplicate gauge vote",
+        ),
+    ] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            vote(votes),
+        );
+        assert_eq!(res.unwrap_err(), StdError::generic_err(err));
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("dave", &[]),
+        vote(vec![gauge_vote("atom", 100)]),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("No voting power"));
+
+    // the second vote of alice replaces the first one
+    for (voter, votes) in [
+        ("alice", vec![gauge_vote("atom", 100)]),
+        ("bob", vec![gauge_vote("atom", 50), gauge_vote("usdt", 50)]),
+        (
+            "alice",
+            vec![gauge_vote("atom", 50), gauge_vote("usdt", 25)],
+        ),
+    ] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(voter, &[]),
+            vote(votes),
+        )
+        .unwrap();
+    }
+
+    let vote_res = query_vote(deps.as_ref(), env.clone(), Addr::unchecked("alice"), None).unwrap();
+    assert_eq!(vote_res.power, Uint128::from(300u128));
+    assert_eq!(
+        vote_res.votes,
+        vec![gauge_vote("atom", 50), gauge_vote("usdt", 25)]
+    );
+
+    // no weights before the first vote epoch ends
+    let res = query_weights(deps.as_ref(), env.clone(), None).unwrap();
+    assert_eq!(res.vote_epoch, None);
+    assert!(res.weights.iter().all(|w| w.weight.is_zero()));
+
+    let expected = WeightsResponse {
+        epoch: 1,
+        vote_epoch: Some(0),
+        total_power: Uint128::from(325u128),
+        weights: vec![
+            GaugeWeight {
+                asset_info: native("atom"),
+                power: Uint128::from(200u128),
+                weight: Decimal::from_ratio(200u128, 325u128),
+            },
+            GaugeWeight {
+                asset_info: native("usdt"),
+                power: Uint128::from(125u128),
+                weight: Decimal::from_ratio(125u128, 325u128),
+            },
+        ],
+    };
+    assert_eq!(
+        query_weights(deps.as_ref(), env.clone(), Some(1)).unwrap(),
+        expected
+    );
+
+    // next epoch, the weights stay until new votes
+    env.block.time = env.block.time.plus_seconds(1000);
+    env.block.height += 1;
+    assert_eq!(
+        query_weights(deps.as_ref(), env.clone(), None).unwrap(),
+        expected
+    );
+    let res = query_weights(deps.as_ref(), env.clone(), Some(5)).unwrap();
+    assert_eq!(res.vote_epoch, Some(0));
+
+    // bob votes first and takes the snapshot, carol bonded after it
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("bob", &[]),
+        vote(vec![gauge_vote("atom", 50), gauge_vote("usdt", 50)]),
+    )
+    .unwrap();
+    env.block.height += 5;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("carol", &[]),
+        vote(vec![gauge_vote("usdt", 100)]),
+    );
+    assert_eq!(res.unwrap_err(), StdError::generic_err("No voting power"));
+
+    // votes for a removed gauge are ignored
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::RemoveGauge {
+            asset_info: native("usdt"),
+        },
+    )
+    .unwrap();
+    let res = query_weights(deps.as_ref(), env, Some(2)).unwrap();
+    assert_eq!(res.vote_epoch, Some(1));
+    assert_eq!(
+        res.weights,
+        vec![GaugeWeight {
+            asset_info: native("atom"),
+            power: Uint128::from(50u128),
+            weight: Decimal::one(),
+        }]
+    );
+}
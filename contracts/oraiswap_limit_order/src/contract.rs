@@ -2,24 +2,29 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128,
+    from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Order as OrderBy, Response, StdResult, SubMsg, Uint128, WasmMsg,
 };
 use oraiswap::error::ContractError;
 
+use crate::migration::rebucket_orders;
 use crate::order::{
     cancel_order, execute_order, query_last_order_id, query_order, query_orderbook,
     query_orderbooks, query_orders, submit_order,
 };
 use crate::orderbook::OrderBook;
-use crate::state::{init_last_order_id, read_config, store_config, store_orderbook};
+use crate::state::{
+    init_last_order_id, read_config, read_orderbook, remove_order, remove_orderbook, store_config,
+    store_orderbook,
+};
 use crate::tick::{query_tick, query_ticks};
 
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use oraiswap::admin::{accept_admin, propose_admin, query_pending_admin};
 use oraiswap::asset::{pair_key, Asset, AssetInfo};
 use oraiswap::limit_order::{
     ContractInfo, ContractInfoResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
-    OrderDirection, QueryMsg,
+    OrderBookStatus, OrderDirection, QueryMsg, SelfTradePrevention,
 };
 
 // version info for migration info
@@ -44,6 +49,10 @@ pub fn instantiate(
         } else {
             creator
         },
+        factory_addr: msg
+            .factory_addr
+            .map(|factory_addr| deps.api.addr_canonicalize(factory_addr.as_str()))
+            .transpose()?,
     };
 
     store_config(deps.storage, &config)?;
@@ -56,18 +65,22 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
         ExecuteMsg::UpdateAdmin { admin } => execute_update_admin(deps, info, admin),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
         ExecuteMsg::UpdateOrderBook {
             offer_info,
             ask_info,
             precision,
             min_offer_amount,
+            tick_size,
+            lot_size,
+            self_trade_prevention,
         } => execute_update_orderbook(
             deps,
             info,
@@ -75,11 +88,31 @@ pub fn execute(
             ask_info,
             precision,
             min_offer_amount,
+            tick_size,
+            lot_size,
+            self_trade_prevention,
         ),
+        ExecuteMsg::UpdateOrderBookStatus {
+            offer_info,
+            ask_info,
+            status,
+        } => execute_update_orderbook_status(deps, info, offer_info, ask_info, status),
+        ExecuteMsg::DelistOrderBook {
+            offer_info,
+            ask_info,
+            limit,
+        } => execute_delist_orderbook(deps, info, offer_info, ask_info, limit),
+        ExecuteMsg::RebucketOrderBook {
+            offer_info,
+            ask_info,
+            start_after,
+            limit,
+        } => execute_rebucket_orderbook(deps, info, offer_info, ask_info, start_after, limit),
         ExecuteMsg::SubmitOrder {
             direction,
             offer_asset,
             ask_asset,
+            amm_fallback,
         } => {
             // if sell then paid asset must be ask asset, this way we've just assumed that we offer usdt and ask for orai
             // for execute order, it is direct match(user has known it is buy or sell) so no order is needed
@@ -90,14 +123,18 @@ pub fn execute(
                 OrderDirection::Sell => &ask_asset,
             };
 
-            // if paid asset is cw20, we check it in Cw20HookMessage
-            if !paid_asset.is_native_token() {
-                return Err(ContractError::MustProvideNativeToken {});
-            }
-
-            paid_asset.assert_sent_native_token_balance(&info)?;
+            // native paid asset is sent along, cw20 paid asset is pulled from the allowance
+            let transfer_msg = receive_paid_asset(&env, &info, paid_asset)?;
             // then submit order
-            submit_order(deps, info.sender, direction, offer_asset, ask_asset)
+            submit_order(
+                deps,
+                info.sender,
+                direction,
+                offer_asset,
+                ask_asset,
+                amm_fallback,
+            )
+            .map(|res| prepend_message(res, transfer_msg))
         }
         ExecuteMsg::CancelOrder {
             order_id,
@@ -109,22 +146,50 @@ pub fn execute(
             order_id,
             offer_info,
         } => {
-            if !ask_asset.is_native_token() {
-                return Err(ContractError::MustProvideNativeToken {});
-            }
-
-            ask_asset.assert_sent_native_token_balance(&info)?;
+            let transfer_msg = receive_paid_asset(&env, &info, &ask_asset)?;
             execute_order(deps, offer_info, info.sender, ask_asset, order_id)
+                .map(|res| prepend_message(res, transfer_msg))
         }
     }
 }
 
+/// check the native paid asset is sent, or build the message pulling the cw20 paid asset from the sender allowance
+fn receive_paid_asset(
+    env: &Env,
+    info: &MessageInfo,
+    paid_asset: &Asset,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    match &paid_asset.info {
+        AssetInfo::NativeToken { .. } => {
+            paid_asset.assert_sent_native_token_balance(info)?;
+            Ok(None)
+        }
+        AssetInfo::Token { contract_addr } => Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: info.sender.to_string(),
+                recipient: env.contract.address.to_string(),
+                amount: paid_asset.amount,
+            })?,
+            funds: vec![],
+        }))),
+    }
+}
+
+/// the paid asset must be received before any transfer out
+fn prepend_message(mut res: Response, msg: Option<CosmosMsg>) -> Response {
+    if let Some(msg) = msg {
+        res.messages.insert(0, SubMsg::new(msg));
+    }
+    res
+}
+
 pub fn execute_update_admin(
     deps: DepsMut,
     info: MessageInfo,
     admin: Addr,
 ) -> Result<Response, ContractError> {
-    let mut contract_info = read_config(deps.storage)?;
+    let contract_info = read_config(deps.storage)?;
     let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
 
     // check authorized
@@ -132,13 +197,27 @@ pub fn execute_update_admin(
         return Err(ContractError::Unauthorized {});
     }
 
-    // update new admin
-    contract_info.admin = deps.api.addr_canonicalize(admin.as_str())?;
+    // the new admin must accept first
+    propose_admin(deps.storage, deps.api, &admin)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "execute_update_admin"),
+        ("pending_admin", admin.as_str()),
+    ]))
+}
+
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut contract_info = read_config(deps.storage)?;
+    contract_info.admin = accept_admin(deps.storage, deps.api, &info.sender)?;
     store_config(deps.storage, &contract_info)?;
 
-    Ok(Response::new().add_attributes(vec![("action", "execute_update_admin")]))
+    Ok(Response::new().add_attributes(vec![
+        ("action", "execute_accept_admin"),
+        ("admin", info.sender.as_str()),
+    ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_orderbook(
     deps: DepsMut,
     info: MessageInfo,
@@ -146,6 +225,9 @@ pub fn execute_update_orderbook(
     offer_info: AssetInfo,
     precision: Option<Decimal>,
     min_offer_amount: Uint128,
+    tick_size: Option<Decimal>,
+    lot_size: Option<Uint128>,
+    self_trade_prevention: Option<SelfTradePrevention>,
 ) -> Result<Response, ContractError> {
     let contract_info = read_config(deps.storage)?;
     let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
@@ -156,17 +238,148 @@ pub fn execute_update_orderbook(
     }
 
     let pair_key = pair_key(&[offer_info.to_raw(deps.api)?, ask_info.to_raw(deps.api)?]);
+    // keep the current status when updating an existing order book
+    let status = read_orderbook(deps.storage, &pair_key)
+        .map(|ob| ob.status)
+        .unwrap_or_default();
     let order_book = OrderBook {
         ask_info: ask_info.to_raw(deps.api)?,
         offer_info: offer_info.to_raw(deps.api)?,
         min_offer_amount,
         precision,
+        tick_size,
+        lot_size,
+        status,
+        self_trade_prevention,
     };
+    order_book.validate_tick_size()?;
     store_orderbook(deps.storage, &pair_key, &order_book)?;
 
     Ok(Response::new().add_attributes(vec![("action", "execute_update_orderbook")]))
 }
 
+pub fn execute_update_orderbook_status(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_info: AssetInfo,
+    ask_info: AssetInfo,
+    status: OrderBookStatus,
+) -> Result<Response, ContractError> {
+    let contract_info = read_config(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_key = pair_key(&[offer_info.to_raw(deps.api)?, ask_info.to_raw(deps.api)?]);
+    let mut order_book = read_orderbook(deps.storage, &pair_key)?;
+
+    // delisting can not be reverted, remaining orders must be refunded
+    if order_book.status == OrderBookStatus::Delisting {
+        return Err(ContractError::OrderBookHalted {});
+    }
+
+    order_book.status = status;
+    store_orderbook(deps.storage, &pair_key, &order_book)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_orderbook_status"),
+        ("pair", &format!("{}-{}", offer_info, ask_info)),
+        ("status", &format!("{:?}", status)),
+    ]))
+}
+
+pub fn execute_delist_orderbook(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_info: AssetInfo,
+    ask_info: AssetInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let contract_info = read_config(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_key = pair_key(&[offer_info.to_raw(deps.api)?, ask_info.to_raw(deps.api)?]);
+    let mut order_book = read_orderbook(deps.storage, &pair_key)?;
+
+    // refund the first orders, removed orders are not read again in the next call
+    let orders = order_book.get_orders(deps.storage, None, limit, Some(OrderBy::Ascending))?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for order in orders.iter() {
+        let refund_asset = order.get_refund_asset(&offer_info, &ask_info)?;
+        if !refund_asset.amount.is_zero() {
+            messages.push(refund_asset.into_msg(
+                None,
+                &deps.querier,
+                deps.api.addr_humanize(&order.bidder_addr)?,
+            )?);
+        }
+        remove_order(deps.storage, &pair_key, order)?;
+    }
+
+    // remove the order book once there is no open order
+    let delisted = order_book
+        .get_orders(deps.storage, None, Some(1), Some(OrderBy::Ascending))?
+        .is_empty();
+    if delisted {
+        remove_orderbook(deps.storage, &pair_key);
+    } else {
+        order_book.status = OrderBookStatus::Delisting;
+        store_orderbook(deps.storage, &pair_key, &order_book)?;
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "delist_orderbook"),
+        ("pair", &format!("{}-{}", offer_info, ask_info)),
+        ("total_refunds", &orders.len().to_string()),
+        ("delisted", &delisted.to_string()),
+    ]))
+}
+
+pub fn execute_rebucket_orderbook(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_info: AssetInfo,
+    ask_info: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let contract_info = read_config(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_key = pair_key(&[offer_info.to_raw(deps.api)?, ask_info.to_raw(deps.api)?]);
+    let order_book = read_orderbook(deps.storage, &pair_key)?;
+
+    let (messages, last_order_id) = rebucket_orders(
+        deps,
+        &order_book,
+        &offer_info,
+        &ask_info,
+        start_after,
+        limit,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "rebucket_orderbook"),
+        (
+            "last_order_id",
+            &last_order_id.map_or(String::new(), |id| id.to_string()),
+        ),
+    ]))
+}
+
 pub fn receive_cw20(
     deps: DepsMut,
     info: MessageInfo,
@@ -183,9 +396,27 @@ pub fn receive_cw20(
 
     match from_binary(&cw20_msg.msg) {
         Ok(Cw20HookMsg::SubmitOrder {
-            ask_asset,
             direction,
-        }) => submit_order(deps, sender, direction, provided_asset, ask_asset),
+            offer_asset,
+            ask_asset,
+            amm_fallback,
+        }) => {
+            let paid_asset = match direction {
+                OrderDirection::Buy => &offer_asset,
+                OrderDirection::Sell => &ask_asset,
+            };
+            if paid_asset.ne(&provided_asset) {
+                return Err(ContractError::AssetMismatch {});
+            }
+            submit_order(
+                deps,
+                sender,
+                direction,
+                offer_asset,
+                ask_asset,
+                amm_fallback,
+            )
+        }
         // this is opposite to SubmitOrder, so offer asset is ask asset
         Ok(Cw20HookMsg::ExecuteOrder {
             order_id,
@@ -199,6 +430,7 @@ pub fn receive_cw20(
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::ContractInfo {} => to_binary(&query_contract_info(deps)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
         QueryMsg::Order {
             order_id,
             offer_info,
@@ -267,10 +499,20 @@ pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
         version: info.version,
         name: info.name,
         admin: deps.api.addr_humanize(&info.admin)?,
+        factory_addr: info
+            .factory_addr
+            .map(|factory_addr| deps.api.addr_humanize(&factory_addr))
+            .transpose()?,
     })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    if let Some(factory_addr) = msg.factory_addr {
+        let mut config = read_config(deps.storage)?;
+        config.factory_addr = Some(deps.api.addr_canonicalize(factory_addr.as_str())?);
+        store_config(deps.storage, &config)?;
+    }
+
     Ok(Response::default())
 }
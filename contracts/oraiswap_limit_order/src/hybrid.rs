@@ -0,0 +1,382 @@
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, Decimal, DepsMut, Fraction, QuerierWrapper, StdError,
+    StdResult, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use oraiswap::asset::{Asset, AssetInfo};
+use oraiswap::error::ContractError;
+use oraiswap::limit_order::OrderDirection;
+use oraiswap::oracle::OracleContract;
+use oraiswap::pair::{ExecuteMsg as PairExecuteMsg, PairExecuteMsgCw20};
+use oraiswap::querier::{query_pair_info, simulate};
+
+use crate::orderbook::{OrderBook, SelfTradeAction};
+use crate::state::{read_config, remove_order, MAX_LIMIT};
+
+// maximum halving of the amm amount to stay within the limit price
+const MAX_AMM_SPLITS: u32 = 10;
+
+// the swap return is checked against the order price, only rounding is tolerated (0.1%)
+const AMM_MAX_SPREAD: Decimal = Decimal::raw(1_000_000_000_000_000);
+
+pub struct HybridFill {
+    pub messages: Vec<CosmosMsg>,
+    /// paid amount filled by the order book
+    pub book_paid_amount: Uint128,
+    /// paid amount swapped through the amm pair
+    pub amm_paid_amount: Uint128,
+    /// paid amount left, rest on the order book
+    pub left_paid_amount: Uint128,
+    /// paid amount decremented by self trade prevention, refunded to the bidder
+    pub cancelled_paid_amount: Uint128,
+    /// the order is cancelled by self trade prevention, nothing left can rest
+    pub taker_cancelled: bool,
+    pub attributes: Vec<(&'static str, String)>,
+}
+
+/// fill a taker order hop by hop from the next crossing book tick or the amm pair, whichever returns more,
+/// never at a worse price than the order price. Paid asset is offer asset for buy and ask asset for sell
+#[allow(clippy::too_many_arguments)]
+pub fn fill_with_amm_fallback(
+    deps: DepsMut,
+    order_book: &OrderBook,
+    sender: &Addr,
+    direction: OrderDirection,
+    offer_info: &AssetInfo,
+    ask_info: &AssetInfo,
+    price: Decimal,
+    paid_amount: Uint128,
+) -> Result<HybridFill, ContractError> {
+    // the factory pair of the order book assets
+    let factory_addr = read_config(deps.storage)?
+        .factory_addr
+        .ok_or_else(|| StdError::generic_err("No factory to route the order"))?;
+    let pair_info = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&factory_addr)?,
+        &[offer_info.clone(), ask_info.clone()],
+    )?;
+
+    let pair_key = &order_book.get_pair_key();
+    let (paid_info, receive_info, counter_direction) = match direction {
+        OrderDirection::Buy => (offer_info, ask_info, OrderDirection::Sell),
+        OrderDirection::Sell => (ask_info, offer_info, OrderDirection::Buy),
+    };
+    let amm_pair = AmmPair {
+        pair_addr: pair_info.contract_addr,
+        oracle_contract: OracleContract(pair_info.oracle_addr),
+        paid_info,
+        receive_info,
+        receiver: sender,
+    };
+
+    // paid amount at the price of the return amount, buy pays offer asset and sell pays ask asset,
+    // rounded down so that the price check is never looser than the price
+    let paid_at = |return_amount: Uint128, price: Decimal| match direction {
+        OrderDirection::Buy => return_amount * price,
+        OrderDirection::Sell => {
+            return_amount.multiply_ratio(Decimal::one().atomics(), price.atomics())
+        }
+    };
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut left_paid_amount = paid_amount;
+    let mut book_paid_amount = Uint128::zero();
+    let mut book_return_amount = Uint128::zero();
+    let mut amm_paid_amount = Uint128::zero();
+    let mut amm_return_amount = Uint128::zero();
+    let mut cancelled_paid_amount = Uint128::zero();
+    let mut taker_cancelled = false;
+    let mut attributes = vec![];
+    let taker_addr = deps.api.addr_canonicalize(sender.as_str())?;
+
+    for _ in 0..MAX_LIMIT {
+        if left_paid_amount.is_zero() || taker_cancelled {
+            break;
+        }
+
+        // next tick on the other side that crosses the order price
+        let (tick_price, found, _) = match direction {
+            OrderDirection::Buy => order_book.lowest_price(deps.storage, counter_direction),
+            OrderDirection::Sell => order_book.highest_price(deps.storage, counter_direction),
+        };
+        let crossed = found
+            && match direction {
+                OrderDirection::Buy => tick_price <= price,
+                OrderDirection::Sell => tick_price >= price,
+            };
+
+        if !crossed {
+            // no book liquidity left, swap the largest part within the order price
+            let mut amount = left_paid_amount;
+            let mut amm_return =
+                amm_pair.simulate_return(&deps.querier, amm_paid_amount, amm_return_amount, amount);
+            for _ in 0..MAX_AMM_SPLITS {
+                if !amm_return.is_zero() && paid_at(amm_return, price) >= amount {
+                    break;
+                }
+                amount = amount.multiply_ratio(1u128, 2u128);
+                amm_return = amm_pair.simulate_return(
+                    &deps.querier,
+                    amm_paid_amount,
+                    amm_return_amount,
+                    amount,
+                );
+            }
+            if amm_return.is_zero() || paid_at(amm_return, price) < amount {
+                break;
+            }
+            amm_paid_amount += amount;
+            amm_return_amount += amm_return;
+            left_paid_amount -= amount;
+            continue;
+        }
+
+        let orders =
+            order_book.orders_at(deps.storage, tick_price, counter_direction, None, None)?;
+        let tick_paid_amount = orders
+            .iter()
+            .map(|order| order.taker_fill_amount(left_paid_amount))
+            .collect::<StdResult<Vec<(Uint128, Uint128)>>>()?
+            .into_iter()
+            .map(|(ask_amount, offer_amount)| match direction {
+                OrderDirection::Buy => offer_amount,
+                OrderDirection::Sell => ask_amount,
+            })
+            .sum::<Uint128>();
+        let amount = Uint128::min(left_paid_amount, tick_paid_amount);
+
+        // compare the marginal amm return with the book return for this hop
+        let amm_return =
+            amm_pair.simulate_return(&deps.querier, amm_paid_amount, amm_return_amount, amount);
+        if paid_at(amm_return, tick_price) > amount {
+            amm_paid_amount += amount;
+            amm_return_amount += amm_return;
+            left_paid_amount -= amount;
+            continue;
+        }
+
+        // fill the tick first in first out
+        let mut tick_left_amount = amount;
+        let mut matched = false;
+        for mut order in orders {
+            if tick_left_amount.is_zero() {
+                break;
+            }
+            let (ask_amount, offer_amount) = order.taker_fill_amount(tick_left_amount)?;
+
+            // same bidder on both sides, prevent self trade
+            if order.bidder_addr == taker_addr {
+                match order_book.self_trade_action(None, &order) {
+                    Some(SelfTradeAction::CancelTaker) => {
+                        taker_cancelled = true;
+                        attributes.push(("self_trade_cancelled", "taker".to_string()));
+                        break;
+                    }
+                    Some(SelfTradeAction::CancelMaker) => {
+                        let refund_asset = order.get_refund_asset(offer_info, ask_info)?;
+                        remove_order(deps.storage, pair_key, &order)?;
+                        if !refund_asset.amount.is_zero() {
+                            messages.push(refund_asset.into_msg(
+                                None,
+                                &deps.querier,
+                                sender.clone(),
+                            )?);
+                        }
+                        attributes.push(("self_trade_cancelled", order.order_id.to_string()));
+                        matched = true;
+                        continue;
+                    }
+                    Some(SelfTradeAction::DecrementBoth) => {
+                        let paid = match direction {
+                            OrderDirection::Buy => offer_amount,
+                            OrderDirection::Sell => ask_amount,
+                        };
+                        if paid.is_zero() {
+                            break;
+                        }
+                        let refund_asset = Asset {
+                            info: match direction {
+                                OrderDirection::Buy => ask_info.clone(),
+                                OrderDirection::Sell => offer_info.clone(),
+                            },
+                            amount: order.decrement(deps.storage, pair_key, ask_amount)?,
+                        };
+                        if !refund_asset.amount.is_zero() {
+                            messages.push(refund_asset.into_msg(
+                                None,
+                                &deps.querier,
+                                sender.clone(),
+                            )?);
+                        }
+                        attributes.push((
+                            "self_trade_decremented",
+                            format!("{}:{}", order.order_id, ask_amount),
+                        ));
+                        tick_left_amount -= paid;
+                        cancelled_paid_amount += paid;
+                        left_paid_amount -= paid;
+                        matched = true;
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+
+            let (paid, received, bidder_asset) = match direction {
+                OrderDirection::Buy => (
+                    offer_amount,
+                    ask_amount,
+                    Asset {
+                        info: offer_info.clone(),
+                        amount: offer_amount,
+                    },
+                ),
+                OrderDirection::Sell => (
+                    ask_amount,
+                    offer_amount,
+                    Asset {
+                        info: ask_info.clone(),
+                        amount: ask_amount,
+                    },
+                ),
+            };
+            if paid.is_zero() {
+                break;
+            }
+            order.fill_order(deps.storage, pair_key, ask_amount, offer_amount)?;
+            if !bidder_asset.amount.is_zero() {
+                let bidder_addr = deps.api.addr_humanize(&order.bidder_addr)?;
+                messages.push(bidder_asset.into_msg(None, &deps.querier, bidder_addr)?);
+            }
+            tick_left_amount -= paid;
+            book_paid_amount += paid;
+            book_return_amount += received;
+            left_paid_amount -= paid;
+            matched = true;
+        }
+
+        // dust that can not fill any order at this tick
+        if !matched {
+            break;
+        }
+    }
+
+    if !book_return_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: receive_info.clone(),
+                amount: book_return_amount,
+            }
+            .into_msg(None, &deps.querier, sender.clone())?,
+        );
+    }
+
+    if !amm_paid_amount.is_zero() {
+        // the swap is simulated in the same block, the pair sends the return to the bidder,
+        // never below the order price: paid per received is the price for buy and its inverse for sell
+        let belief_price = match direction {
+            OrderDirection::Buy => Some(price),
+            OrderDirection::Sell => price.inv(),
+        };
+        messages.push(into_swap_msg(
+            amm_pair.pair_addr,
+            Asset {
+                info: paid_info.clone(),
+                amount: amm_paid_amount,
+            },
+            belief_price,
+            sender.clone(),
+        )?);
+    }
+
+    Ok(HybridFill {
+        messages,
+        book_paid_amount,
+        amm_paid_amount,
+        left_paid_amount,
+        cancelled_paid_amount,
+        taker_cancelled,
+        attributes,
+    })
+}
+
+/// the amm pair of the order book, return is net of the tax paid to the bidder
+struct AmmPair<'a> {
+    pair_addr: Addr,
+    oracle_contract: OracleContract,
+    paid_info: &'a AssetInfo,
+    receive_info: &'a AssetInfo,
+    receiver: &'a Addr,
+}
+
+impl AmmPair<'_> {
+    /// marginal return of swapping amount more on top of the amount already routed to the pair
+    fn simulate_return(
+        &self,
+        querier: &QuerierWrapper,
+        routed_amount: Uint128,
+        routed_return_amount: Uint128,
+        amount: Uint128,
+    ) -> Uint128 {
+        if amount.is_zero() {
+            return Uint128::zero();
+        }
+        // a pair without liquidity just returns nothing
+        let return_amount = simulate(
+            querier,
+            self.pair_addr.clone(),
+            &Asset {
+                info: self.paid_info.clone(),
+                amount: routed_amount + amount,
+            },
+        )
+        .map_or(Uint128::zero(), |res| res.return_amount);
+        let return_asset = Asset {
+            info: self.receive_info.clone(),
+            amount: return_amount,
+        };
+        let tax_amount = return_asset
+            .compute_tax(&self.oracle_contract, querier, Some(self.receiver))
+            .unwrap_or_default();
+        return_amount
+            .saturating_sub(tax_amount)
+            .saturating_sub(routed_return_amount)
+    }
+}
+
+fn into_swap_msg(
+    pair_addr: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    to: Addr,
+) -> StdResult<CosmosMsg> {
+    match offer_asset.info.clone() {
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_addr.to_string(),
+            funds: vec![Coin {
+                denom,
+                amount: offer_asset.amount,
+            }],
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset,
+                belief_price,
+                max_spread: Some(AMM_MAX_SPREAD),
+                to: Some(to),
+            })?,
+        })),
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_addr.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairExecuteMsgCw20::Swap {
+                    belief_price,
+                    max_spread: Some(AMM_MAX_SPREAD),
+                    to: Some(to),
+                })?,
+            })?,
+        })),
+    }
+}
@@ -2,6 +2,8 @@ pub mod contract;
 pub mod orderbook;
 pub mod state;
 
+mod hybrid;
+mod migration;
 mod order;
 mod tick;
 
@@ -0,0 +1,96 @@
+use cosmwasm_std::{Addr, CosmosMsg, Decimal, DepsMut, Order as OrderBy, Uint128};
+use oraiswap::asset::{Asset, AssetInfo};
+use oraiswap::error::ContractError;
+use oraiswap::limit_order::OrderDirection;
+
+use crate::orderbook::{Order, OrderBook};
+use crate::state::{read_orders, remove_order, store_order};
+
+/// re-bucket existing orders of an orderbook into the current tick size and lot size
+/// the remaining part of each misaligned order is re-submitted with the same order id at the rounded price,
+/// the paid asset left over is refunded to the bidder. Return refund messages and the last processed order id
+pub fn rebucket_orders(
+    deps: DepsMut,
+    order_book: &OrderBook,
+    offer_info: &AssetInfo,
+    ask_info: &AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<(Vec<CosmosMsg>, Option<u64>), ContractError> {
+    let pair_key = &order_book.get_pair_key();
+    let orders = read_orders(
+        deps.storage,
+        pair_key,
+        start_after,
+        limit,
+        Some(OrderBy::Ascending),
+    )?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let last_order_id = orders.last().map(|order| order.order_id);
+
+    for order in orders {
+        let remaining_offer_amount = order.offer_amount.checked_sub(order.filled_offer_amount)?;
+        let remaining_ask_amount = order.ask_amount.checked_sub(order.filled_ask_amount)?;
+
+        // order that can not be aligned any more is closed and fully refunded
+        let (offer_amount, ask_amount, refund_amount) = order_book
+            .align_order(
+                order.direction,
+                remaining_offer_amount,
+                remaining_ask_amount,
+            )
+            .unwrap_or((
+                Uint128::zero(),
+                Uint128::zero(),
+                match order.direction {
+                    OrderDirection::Buy => remaining_offer_amount,
+                    OrderDirection::Sell => remaining_ask_amount,
+                },
+            ));
+
+        // already in the right bucket
+        if refund_amount.is_zero()
+            && !ask_amount.is_zero()
+            && Decimal::from_ratio(offer_amount, ask_amount) == order.get_price()
+        {
+            continue;
+        }
+
+        remove_order(deps.storage, pair_key, &order)?;
+
+        if !ask_amount.is_zero() {
+            store_order(
+                deps.storage,
+                pair_key,
+                &Order {
+                    order_id: order.order_id,
+                    direction: order.direction,
+                    bidder_addr: order.bidder_addr.clone(),
+                    offer_amount,
+                    ask_amount,
+                    filled_offer_amount: Uint128::zero(),
+                    filled_ask_amount: Uint128::zero(),
+                },
+                true,
+            )?;
+        }
+
+        if !refund_amount.is_zero() {
+            let paid_info = match order.direction {
+                OrderDirection::Buy => offer_info.clone(),
+                OrderDirection::Sell => ask_info.clone(),
+            };
+            let bidder_addr: Addr = deps.api.addr_humanize(&order.bidder_addr)?;
+            messages.push(
+                Asset {
+                    info: paid_info,
+                    amount: refund_amount,
+                }
+                .into_msg(None, &deps.querier, bidder_addr)?,
+            );
+        }
+    }
+
+    Ok((messages, last_order_id))
+}
@@ -1,13 +1,15 @@
 use std::convert::TryFrom;
 
-use crate::orderbook::Order;
+use crate::hybrid::fill_with_amm_fallback;
+use crate::orderbook::{Order, SelfTradeAction};
 use crate::state::{
     increase_last_order_id, read_last_order_id, read_order, read_orderbook, read_orderbooks,
     read_orders, read_orders_with_indexer, remove_order, store_order, PREFIX_ORDER_BY_BIDDER,
     PREFIX_ORDER_BY_PRICE, PREFIX_TICK,
 };
 use cosmwasm_std::{
-    Addr, CosmosMsg, Deps, DepsMut, MessageInfo, Order as OrderBy, Response, StdResult, Uint128,
+    Addr, CosmosMsg, Decimal, Deps, DepsMut, MessageInfo, Order as OrderBy, Response, StdError,
+    StdResult, Uint128,
 };
 
 use oraiswap::asset::{pair_key, Asset, AssetInfo};
@@ -18,21 +20,126 @@ use oraiswap::limit_order::{
 };
 
 pub fn submit_order(
-    deps: DepsMut,
+    mut deps: DepsMut,
     sender: Addr,
     direction: OrderDirection,
     offer_asset: Asset,
     ask_asset: Asset,
+    amm_fallback: bool,
 ) -> Result<Response, ContractError> {
+    // the order price divides by both amounts
+    if offer_asset.amount.is_zero() || ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
     // check min offer amount and min ask amount
     // need to setup min offer_amount and ask_amount for a specific pair so that no one can spam
-    let offer_asset_raw = offer_asset.to_raw(deps.api)?;
-    let ask_asset_raw = ask_asset.to_raw(deps.api)?;
-    let pair_key = pair_key(&[offer_asset_raw.info, ask_asset_raw.info]);
+    let pair_key = pair_key(&[
+        offer_asset.info.to_raw(deps.api)?,
+        ask_asset.info.to_raw(deps.api)?,
+    ]);
     let order_book = read_orderbook(deps.storage, &pair_key)?;
+    order_book.assert_can_submit()?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes: Vec<(&str, String)> = vec![];
+
+    // fill from the book or the amm first, only the remaining part rests on the book
+    let (offer_asset, ask_asset) = if amm_fallback {
+        let price = Decimal::from_ratio(offer_asset.amount, ask_asset.amount);
+        let paid_amount = match direction {
+            OrderDirection::Buy => offer_asset.amount,
+            OrderDirection::Sell => ask_asset.amount,
+        };
+        let fill = fill_with_amm_fallback(
+            deps.branch(),
+            &order_book,
+            &sender,
+            direction,
+            &offer_asset.info,
+            &ask_asset.info,
+            price,
+            paid_amount,
+        )?;
+        messages.extend(fill.messages);
+        attributes.push(("book_paid_amount", fill.book_paid_amount.to_string()));
+        attributes.push(("amm_paid_amount", fill.amm_paid_amount.to_string()));
+        attributes.extend(fill.attributes);
+
+        let left_paid_amount = fill.left_paid_amount;
+        let (offer_amount, ask_amount) = match direction {
+            OrderDirection::Buy => (
+                left_paid_amount,
+                ask_asset
+                    .amount
+                    .multiply_ratio(left_paid_amount, offer_asset.amount),
+            ),
+            OrderDirection::Sell => (
+                offer_asset
+                    .amount
+                    .multiply_ratio(left_paid_amount, ask_asset.amount),
+                left_paid_amount,
+            ),
+        };
+
+        // remaining part that can not rest on the book is refunded, as well as the part
+        // cancelled by self trade prevention
+        let rest = if fill.taker_cancelled || left_paid_amount.is_zero() {
+            None
+        } else {
+            order_book
+                .align_order(direction, offer_amount, ask_amount)
+                .ok()
+                .filter(|(offer_amount, _, _)| offer_amount.ge(&order_book.min_offer_amount))
+        };
+        let refund_amount = match rest {
+            Some(_) => fill.cancelled_paid_amount,
+            None => fill.cancelled_paid_amount + left_paid_amount,
+        };
+        if !refund_amount.is_zero() {
+            let paid_info = match direction {
+                OrderDirection::Buy => offer_asset.info.clone(),
+                OrderDirection::Sell => ask_asset.info.clone(),
+            };
+            messages.push(
+                Asset {
+                    info: paid_info,
+                    amount: refund_amount,
+                }
+                .into_msg(None, &deps.querier, sender.clone())?,
+            );
+        }
+        if rest.is_none() {
+            return Ok(Response::new()
+                .add_messages(messages)
+                .add_attributes(vec![
+                    ("action", "submit_order"),
+                    ("bidder_addr", sender.as_str()),
+                    ("refund_amount", &refund_amount.to_string()),
+                ])
+                .add_attributes(attributes));
+        }
+
+        (
+            Asset {
+                info: offer_asset.info,
+                amount: offer_amount,
+            },
+            Asset {
+                info: ask_asset.info,
+                amount: ask_amount,
+            },
+        )
+    } else {
+        (offer_asset, ask_asset)
+    };
+
+    // round price to tick size and ask amount to lot size, the paid asset left over is refunded
+    let (offer_amount, ask_amount, refund_amount) =
+        order_book.align_order(direction, offer_asset.amount, ask_asset.amount)?;
 
     // require minimum amount for the orderbook
-    if offer_asset.amount.lt(&order_book.min_offer_amount) {
+    if offer_amount.lt(&order_book.min_offer_amount) {
         return Err(ContractError::TooSmallOfferAmount {});
     }
 
@@ -45,22 +152,48 @@ pub fn submit_order(
             order_id,
             direction,
             bidder_addr: deps.api.addr_canonicalize(sender.as_str())?,
-            offer_amount: offer_asset_raw.amount,
-            ask_amount: ask_asset_raw.amount,
+            offer_amount,
+            ask_amount,
             filled_offer_amount: Uint128::zero(),
             filled_ask_amount: Uint128::zero(),
         },
         true,
     )?;
 
-    Ok(Response::new().add_attributes(vec![
-        ("action", "submit_order"),
-        ("order_id", &order_id.to_string()),
-        ("bidder_addr", sender.as_str()),
-        ("offer_asset", &offer_asset.to_string()),
-        ("ask_asset", &ask_asset.to_string()),
-        ("total_orders", &total_orders.to_string()),
-    ]))
+    let offer_asset = Asset {
+        info: offer_asset.info,
+        amount: offer_amount,
+    };
+    let ask_asset = Asset {
+        info: ask_asset.info,
+        amount: ask_amount,
+    };
+
+    if !refund_amount.is_zero() {
+        let paid_info = match direction {
+            OrderDirection::Buy => offer_asset.info.clone(),
+            OrderDirection::Sell => ask_asset.info.clone(),
+        };
+        messages.push(
+            Asset {
+                info: paid_info,
+                amount: refund_amount,
+            }
+            .into_msg(None, &deps.querier, sender.clone())?,
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "submit_order"),
+            ("order_id", &order_id.to_string()),
+            ("bidder_addr", sender.as_str()),
+            ("offer_asset", &offer_asset.to_string()),
+            ("ask_asset", &ask_asset.to_string()),
+            ("total_orders", &total_orders.to_string()),
+        ])
+        .add_attributes(attributes))
 }
 
 pub fn cancel_order(
@@ -77,15 +210,11 @@ pub fn cancel_order(
         return Err(ContractError::Unauthorized {});
     }
 
-    // Compute refund asset
-    let left_offer_amount = order.offer_amount.checked_sub(order.filled_offer_amount)?;
-    let bidder_refund = Asset {
-        info: offer_info,
-        amount: left_offer_amount,
-    };
+    // Compute refund asset, the paid asset left in the order
+    let bidder_refund = order.get_refund_asset(&offer_info, &ask_info)?;
 
     // Build refund msg
-    let messages = if left_offer_amount > Uint128::zero() {
+    let messages = if !bidder_refund.amount.is_zero() {
         vec![bidder_refund
             .clone()
             .into_msg(None, &deps.querier, info.sender)?]
@@ -114,26 +243,87 @@ pub fn execute_order(
         offer_info.to_raw(deps.api)?,
         ask_asset.info.to_raw(deps.api)?,
     ]);
+    let order_book = read_orderbook(deps.storage, &pair_key)?;
+    order_book.assert_can_match()?;
     let mut order = read_order(deps.storage, &pair_key, order_id)?;
 
-    // Compute offer amount & match ask amount
-    let (offer_amount, match_ask_amount) = order.matchable_amount(ask_asset.amount)?;
+    // the executor is the bidder of the order, prevent self trade
+    if order.bidder_addr == deps.api.addr_canonicalize(sender.as_str())? {
+        if let Some(action) = order_book.self_trade_action(None, &order) {
+            // the executor always gets back the provided asset
+            let (decremented_amount, _) = order.taker_fill_amount(ask_asset.amount)?;
+            let mut messages: Vec<CosmosMsg> = vec![];
+            if !ask_asset.amount.is_zero() {
+                messages.push(
+                    ask_asset
+                        .clone()
+                        .into_msg(None, &deps.querier, sender.clone())?,
+                );
+            }
+
+            // the maker paid the asset the executor receives, offer info in both directions
+            let refund_asset = match action {
+                SelfTradeAction::CancelTaker => None,
+                SelfTradeAction::CancelMaker => {
+                    let refund_amount = order.left_paid_amount()?;
+                    remove_order(deps.storage, &pair_key, &order)?;
+                    Some(Asset {
+                        info: offer_info.clone(),
+                        amount: refund_amount,
+                    })
+                }
+                SelfTradeAction::DecrementBoth => Some(Asset {
+                    info: offer_info.clone(),
+                    amount: order.decrement(deps.storage, &pair_key, decremented_amount)?,
+                }),
+            };
+            if let Some(refund_asset) = refund_asset.filter(|asset| !asset.amount.is_zero()) {
+                messages.push(refund_asset.into_msg(None, &deps.querier, sender)?);
+            }
+
+            let self_trade_attribute = match action {
+                SelfTradeAction::CancelTaker => ("self_trade_cancelled", "executor".to_string()),
+                SelfTradeAction::CancelMaker => ("self_trade_cancelled", order_id.to_string()),
+                SelfTradeAction::DecrementBoth => (
+                    "self_trade_decremented",
+                    format!("{}:{}", order_id, decremented_amount),
+                ),
+            };
+            return Ok(Response::new().add_messages(messages).add_attributes(vec![
+                ("action", "execute_order".to_string()),
+                ("order_id", order_id.to_string()),
+                self_trade_attribute,
+            ]));
+        }
+    }
+
+    // the executor pays what the order asks for: ask asset of buy order, offer asset of sell order
+    let (fill_ask_amount, fill_offer_amount, executor_receive_amount) = match order.direction {
+        OrderDirection::Buy => {
+            // Compute offer amount & match ask amount
+            let (offer_amount, _) = order.matchable_amount(ask_asset.amount)?;
+            (ask_asset.amount, offer_amount, offer_amount)
+        }
+        OrderDirection::Sell => {
+            let match_offer_amount = order.offer_amount.checked_sub(order.filled_offer_amount)?;
+            if match_offer_amount < ask_asset.amount || order.ask_amount == order.filled_ask_amount
+            {
+                return Err(StdError::generic_err("insufficient order amount left").into());
+            }
+            let (ask_amount, _) = order.taker_fill_amount(ask_asset.amount)?;
+            (ask_amount, ask_asset.amount, ask_amount)
+        }
+    };
     let executor_receive = Asset {
         info: offer_info,
-        amount: offer_amount,
+        amount: executor_receive_amount,
     };
 
     let bidder_addr = deps.api.addr_humanize(&order.bidder_addr)?;
 
     // When natch amount equals ask amount, close order
-    let total_orders = if match_ask_amount == ask_asset.amount {
-        remove_order(deps.storage, &pair_key, &order)?
-    } else {
-        order.filled_ask_amount += ask_asset.amount;
-        order.filled_offer_amount += executor_receive.amount;
-        // update order
-        store_order(deps.storage, &pair_key, &order, false)?
-    };
+    let total_orders =
+        order.fill_order(deps.storage, &pair_key, fill_ask_amount, fill_offer_amount)?;
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if !executor_receive.amount.is_zero() {
@@ -5,12 +5,14 @@ use cosmwasm_storage::ReadonlyBucket;
 use oraiswap::{
     asset::{pair_key_from_asset_keys, Asset, AssetInfo, AssetInfoRaw},
     error::ContractError,
-    limit_order::{OrderBookResponse, OrderDirection, OrderResponse},
+    limit_order::{
+        OrderBookResponse, OrderBookStatus, OrderDirection, OrderResponse, SelfTradePrevention,
+    },
 };
 
 use cosmwasm_std::{
-    Api, CanonicalAddr, CosmosMsg, Decimal, DepsMut, Order as OrderBy, StdError, StdResult,
-    Storage, Uint128,
+    Api, Attribute, CanonicalAddr, Decimal, DepsMut, Order as OrderBy, Response, StdError,
+    StdResult, Storage, Uint128,
 };
 
 use crate::state::{
@@ -89,6 +91,89 @@ impl Order {
         ))
     }
 
+    /// decrease the remaining part of the order by ask amount at the order price without any transfer,
+    /// the order is removed when nothing is left. Return the paid amount to refund to the bidder
+    pub fn decrement(
+        &mut self,
+        storage: &mut dyn Storage,
+        pair_key: &[u8],
+        ask_amount: Uint128,
+    ) -> StdResult<Uint128> {
+        let left_offer_amount = self.offer_amount.checked_sub(self.filled_offer_amount)?;
+        let left_ask_amount = self.ask_amount.checked_sub(self.filled_ask_amount)?;
+        remove_order(storage, pair_key, self)?;
+
+        if ask_amount >= left_ask_amount {
+            return Ok(match self.direction {
+                OrderDirection::Buy => left_offer_amount,
+                OrderDirection::Sell => left_ask_amount,
+            });
+        }
+
+        let offer_amount = ask_amount.multiply_ratio(self.offer_amount, self.ask_amount);
+        self.offer_amount = self.offer_amount.checked_sub(offer_amount)?;
+        self.ask_amount = self.ask_amount.checked_sub(ask_amount)?;
+        store_order(storage, pair_key, self, true)?;
+
+        Ok(match self.direction {
+            OrderDirection::Buy => offer_amount,
+            OrderDirection::Sell => ask_amount,
+        })
+    }
+
+    /// return (ask_amount, offer_amount) filled by a taker paying at most paid_amount at the order price,
+    /// taker pays offer asset to a sell order and ask asset to a buy order
+    pub fn taker_fill_amount(&self, paid_amount: Uint128) -> StdResult<(Uint128, Uint128)> {
+        let left_offer_amount = self.offer_amount.checked_sub(self.filled_offer_amount)?;
+        let left_ask_amount = self.ask_amount.checked_sub(self.filled_ask_amount)?;
+        Ok(match self.direction {
+            OrderDirection::Buy => {
+                if paid_amount >= left_ask_amount {
+                    (left_ask_amount, left_offer_amount)
+                } else {
+                    (
+                        paid_amount,
+                        paid_amount.multiply_ratio(self.offer_amount, self.ask_amount),
+                    )
+                }
+            }
+            OrderDirection::Sell => {
+                if paid_amount >= left_offer_amount {
+                    (left_ask_amount, left_offer_amount)
+                } else {
+                    let ask_amount = paid_amount.multiply_ratio(self.ask_amount, self.offer_amount);
+                    (
+                        ask_amount,
+                        ask_amount.multiply_ratio(self.offer_amount, self.ask_amount),
+                    )
+                }
+            }
+        })
+    }
+
+    /// the paid asset left in the order, offer asset for buy order and ask asset for sell order
+    pub fn get_refund_asset(
+        &self,
+        offer_info: &AssetInfo,
+        ask_info: &AssetInfo,
+    ) -> StdResult<Asset> {
+        Ok(Asset {
+            info: match self.direction {
+                OrderDirection::Buy => offer_info.clone(),
+                OrderDirection::Sell => ask_info.clone(),
+            },
+            amount: self.left_paid_amount()?,
+        })
+    }
+
+    /// the paid amount left in the order, offer amount for buy order and ask amount for sell order
+    pub fn left_paid_amount(&self) -> StdResult<Uint128> {
+        Ok(match self.direction {
+            OrderDirection::Buy => self.offer_amount.checked_sub(self.filled_offer_amount)?,
+            OrderDirection::Sell => self.ask_amount.checked_sub(self.filled_ask_amount)?,
+        })
+    }
+
     pub fn get_price(&self) -> Decimal {
         Decimal::from_ratio(self.offer_amount, self.ask_amount)
     }
@@ -117,6 +202,14 @@ impl Order {
     }
 }
 
+/// what to do when a taker order meets an order of the same bidder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfTradeAction {
+    CancelTaker,
+    CancelMaker,
+    DecrementBoth,
+}
+
 /// Ticks are stored in Ordered database, so we just need to process at 50 recent ticks is ok
 #[cw_serde]
 pub struct OrderBook {
@@ -124,6 +217,11 @@ pub struct OrderBook {
     pub offer_info: AssetInfoRaw,
     pub precision: Option<Decimal>,
     pub min_offer_amount: Uint128,
+    pub tick_size: Option<Decimal>,
+    pub lot_size: Option<Uint128>,
+    #[serde(default)]
+    pub status: OrderBookStatus,
+    pub self_trade_prevention: Option<SelfTradePrevention>,
 }
 
 impl OrderBook {
@@ -137,6 +235,24 @@ impl OrderBook {
             min_offer_amount: Uint128::zero(),
             ask_info,
             precision,
+            tick_size: None,
+            lot_size: None,
+            status: OrderBookStatus::Active,
+            self_trade_prevention: None,
+        }
+    }
+
+    pub fn assert_can_submit(&self) -> Result<(), ContractError> {
+        match self.status {
+            OrderBookStatus::Active => Ok(()),
+            _ => Err(ContractError::OrderBookPaused {}),
+        }
+    }
+
+    pub fn assert_can_match(&self) -> Result<(), ContractError> {
+        match self.status {
+            OrderBookStatus::Active | OrderBookStatus::Paused => Ok(()),
+            _ => Err(ContractError::OrderBookHalted {}),
         }
     }
 
@@ -146,9 +262,118 @@ impl OrderBook {
             ask_info: self.ask_info.to_normal(api)?,
             min_offer_amount: self.min_offer_amount,
             precision: self.precision,
+            tick_size: self.tick_size,
+            lot_size: self.lot_size,
+            status: self.status,
+            self_trade_prevention: self.self_trade_prevention,
+        })
+    }
+
+    /// action to take when the taker meets a maker order of the same bidder, none if self trade is allowed.
+    /// Taker order id is none for an incoming order, which is always the newest
+    pub fn self_trade_action(
+        &self,
+        taker_order_id: Option<u64>,
+        maker_order: &Order,
+    ) -> Option<SelfTradeAction> {
+        let taker_is_newest = match taker_order_id {
+            Some(order_id) => order_id > maker_order.order_id,
+            None => true,
+        };
+        self.self_trade_prevention.map(|mode| match mode {
+            SelfTradePrevention::CancelNewest if taker_is_newest => SelfTradeAction::CancelTaker,
+            SelfTradePrevention::CancelNewest => SelfTradeAction::CancelMaker,
+            SelfTradePrevention::CancelOldest if taker_is_newest => SelfTradeAction::CancelMaker,
+            SelfTradePrevention::CancelOldest => SelfTradeAction::CancelTaker,
+            SelfTradePrevention::DecrementBoth => SelfTradeAction::DecrementBoth,
         })
     }
 
+    /// ask amount (multiple of lot size) * price (multiple of tick size) must be an integer,
+    /// so that the price of an aligned order is exactly a tick and no new tick is created
+    pub fn validate_tick_size(&self) -> Result<(), ContractError> {
+        let lot_size = self.lot_size.unwrap_or(Uint128::one());
+        if lot_size.is_zero() {
+            return Err(ContractError::TooSmallAskAmount {});
+        }
+
+        if let Some(tick_size) = self.tick_size {
+            if tick_size.is_zero()
+                || !(tick_size.atomics().checked_mul(lot_size)? % Decimal::one().atomics())
+                    .is_zero()
+            {
+                return Err(ContractError::InvalidTickSize {});
+            }
+        }
+
+        Ok(())
+    }
+
+    /// round price to the tick size, buy price is rounded down and sell price is rounded up
+    /// so the bidder never gets a worse price than the submitted one
+    pub fn round_price(&self, price: Decimal, direction: OrderDirection) -> Decimal {
+        match self.tick_size {
+            Some(tick_size) if !tick_size.is_zero() => {
+                let tick = tick_size.atomics().u128();
+                let ticks = price.atomics().u128() / tick;
+                match direction {
+                    OrderDirection::Buy => Decimal::raw(ticks * tick),
+                    OrderDirection::Sell => {
+                        if ticks * tick == price.atomics().u128() {
+                            price
+                        } else {
+                            Decimal::raw((ticks + 1) * tick)
+                        }
+                    }
+                }
+            }
+            _ => price,
+        }
+    }
+
+    /// align the offer amount and ask amount of an order to the tick size and lot size
+    /// return (offer_amount, ask_amount, refund_amount) where refund amount is the paid asset left over
+    /// (offer asset for buy order, ask asset for sell order)
+    pub fn align_order(
+        &self,
+        direction: OrderDirection,
+        offer_amount: Uint128,
+        ask_amount: Uint128,
+    ) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+        if self.tick_size.is_none() && self.lot_size.is_none() {
+            return Ok((offer_amount, ask_amount, Uint128::zero()));
+        }
+
+        // ask amount is always the base asset, round down to lot size
+        let aligned_ask_amount = match self.lot_size {
+            Some(lot_size) if !lot_size.is_zero() => ask_amount / lot_size * lot_size,
+            _ => ask_amount,
+        };
+        if aligned_ask_amount.is_zero() {
+            return Err(ContractError::TooSmallAskAmount {});
+        }
+
+        let aligned_offer_amount = match self.tick_size {
+            Some(_) => {
+                let price =
+                    self.round_price(Decimal::from_ratio(offer_amount, ask_amount), direction);
+                aligned_ask_amount * price
+            }
+            // no tick size, keep the submitted price
+            None => offer_amount.multiply_ratio(aligned_ask_amount, ask_amount),
+        };
+        if aligned_offer_amount.is_zero() {
+            return Err(ContractError::TooSmallOfferAmount {});
+        }
+
+        let refund_amount = match direction {
+            OrderDirection::Buy => offer_amount.checked_sub(aligned_offer_amount)?,
+            OrderDirection::Sell => ask_amount.checked_sub(aligned_ask_amount)?,
+        };
+
+        Ok((aligned_offer_amount, aligned_ask_amount, refund_amount))
+    }
+
     pub fn get_pair_key(&self) -> Vec<u8> {
         pair_key_from_asset_keys(self.offer_info.as_bytes(), self.ask_info.as_bytes())
     }
@@ -331,7 +556,8 @@ impl OrderBook {
         deps: DepsMut,
         ask_order: &mut Order,
         offer_orders: &mut Vec<Order>,
-    ) -> Result<Vec<CosmosMsg>, ContractError> {
+    ) -> Result<Response, ContractError> {
+        self.assert_can_match()?;
         let pair_key = &self.get_pair_key();
         // this will try to fill all orders
         // for loop orders, to create a vector of (offer_amount and match_ask_amount), then execute the order list
@@ -340,9 +566,69 @@ impl OrderBook {
         let ask_info = self.ask_info.to_normal(deps.api)?;
         let offer_info = self.offer_info.to_normal(deps.api)?;
         let mut messages = vec![];
+        let mut attributes: Vec<Attribute> = vec![];
+        let mut ask_order_cancelled = false;
+        let mut decremented_ask_amount = Uint128::zero();
         let mut executor_receive_amount = Uint128::zero();
         let mut lef_ask_order_amount = ask_order.ask_amount;
         for order in offer_orders {
+            // same bidder on both sides, prevent self trade
+            if order.bidder_addr == ask_order.bidder_addr {
+                match self.self_trade_action(Some(ask_order.order_id), order) {
+                    Some(SelfTradeAction::CancelMaker) => {
+                        let refund_asset = order.get_refund_asset(&offer_info, &ask_info)?;
+                        remove_order(deps.storage, pair_key, order)?;
+                        if !refund_asset.amount.is_zero() {
+                            messages.push(refund_asset.into_msg(
+                                None,
+                                &deps.querier,
+                                sender.clone(),
+                            )?);
+                        }
+                        attributes.push(Attribute::new(
+                            "self_trade_cancelled",
+                            order.order_id.to_string(),
+                        ));
+                        continue;
+                    }
+                    Some(SelfTradeAction::CancelTaker) => {
+                        ask_order_cancelled = true;
+                        break;
+                    }
+                    Some(SelfTradeAction::DecrementBoth) => {
+                        let ask_amount = Uint128::min(
+                            lef_ask_order_amount,
+                            order.ask_amount - order.filled_ask_amount,
+                        );
+                        lef_ask_order_amount -= ask_amount;
+                        decremented_ask_amount += ask_amount;
+                        let refund_asset = Asset {
+                            info: match order.direction {
+                                OrderDirection::Buy => offer_info.clone(),
+                                OrderDirection::Sell => ask_info.clone(),
+                            },
+                            amount: order.decrement(deps.storage, pair_key, ask_amount)?,
+                        };
+                        if !refund_asset.amount.is_zero() {
+                            messages.push(refund_asset.into_msg(
+                                None,
+                                &deps.querier,
+                                sender.clone(),
+                            )?);
+                        }
+                        attributes.push(Attribute::new(
+                            "self_trade_decremented",
+                            format!("{}:{}", order.order_id, ask_amount),
+                        ));
+                        if lef_ask_order_amount.is_zero() {
+                            break;
+                        }
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+
             // offer amount is already paid, we need ask amount to be received
             // remember that ask of buy and ask of sell are opposite sides
             // ask_amount is equal match ask amount, to make sure always matched
@@ -381,12 +667,12 @@ impl OrderBook {
             ask_order.fill_order(
                 deps.storage,
                 pair_key,
-                ask_order.ask_amount - lef_ask_order_amount,
+                ask_order.ask_amount - lef_ask_order_amount - decremented_ask_amount,
                 executor_receive_amount,
             )?;
 
             let executor_receive = Asset {
-                info: offer_info,
+                info: offer_info.clone(),
                 amount: executor_receive_amount,
             };
             // dont use oracle for limit order
@@ -396,6 +682,35 @@ impl OrderBook {
                 deps.api.addr_validate(sender.as_str())?,
             )?);
         }
-        Ok(messages)
+
+        // the ask order is cancelled or decremented by self trade prevention, its paid asset is refunded
+        let refund_asset = if ask_order_cancelled {
+            let refund_asset = ask_order.get_refund_asset(&offer_info, &ask_info)?;
+            remove_order(deps.storage, pair_key, ask_order)?;
+            attributes.push(Attribute::new(
+                "self_trade_cancelled",
+                ask_order.order_id.to_string(),
+            ));
+            refund_asset
+        } else {
+            Asset {
+                info: match ask_order.direction {
+                    OrderDirection::Buy => offer_info,
+                    OrderDirection::Sell => ask_info,
+                },
+                amount: if decremented_ask_amount.is_zero() {
+                    Uint128::zero()
+                } else {
+                    ask_order.decrement(deps.storage, pair_key, decremented_ask_amount)?
+                },
+            }
+        };
+        if !refund_asset.amount.is_zero() {
+            messages.push(refund_asset.into_msg(None, &deps.querier, sender)?);
+        }
+
+        Ok(Response::new()
+            .add_messages(messages)
+            .add_attributes(attributes))
     }
 }
@@ -37,6 +37,10 @@ pub fn store_orderbook(
     Bucket::new(storage, PREFIX_ORDER_BOOK).save(pair_key, order_book)
 }
 
+pub fn remove_orderbook(storage: &mut dyn Storage, pair_key: &[u8]) {
+    Bucket::<OrderBook>::new(storage, PREFIX_ORDER_BOOK).remove(pair_key)
+}
+
 // do not return error, by default it return no precision and zero min offer amount
 pub fn read_orderbook(storage: &dyn Storage, pair_key: &[u8]) -> StdResult<OrderBook> {
     ReadonlyBucket::new(storage, PREFIX_ORDER_BOOK).load(pair_key)
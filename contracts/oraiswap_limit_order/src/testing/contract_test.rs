@@ -1,11 +1,14 @@
-use cosmwasm_std::{to_binary, Addr, Coin, Uint128};
+use std::str::FromStr;
+
+use cosmwasm_std::{from_slice, to_binary, Addr, Coin, Decimal, Uint128};
 use oraiswap::create_entry_points_testing;
 use oraiswap::testing::{AttributeUtil, MockApp, ATOM_DENOM};
 
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::limit_order::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LastOrderIdResponse, OrderBooksResponse,
-    OrderDirection, OrderFilter, OrderResponse, OrdersResponse, QueryMsg, TicksResponse,
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, LastOrderIdResponse, OrderBookResponse,
+    OrderBookStatus, OrderBooksResponse, OrderDirection, OrderFilter, OrderResponse,
+    OrdersResponse, QueryMsg, SelfTradePrevention, TicksResponse,
 };
 
 use crate::jsonstr;
@@ -26,6 +29,7 @@ fn submit_order() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -55,6 +59,9 @@ fn submit_order() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -77,6 +84,7 @@ fn submit_order() {
                 contract_addr: token_addr.clone(),
             },
         },
+        amm_fallback: false,
     };
 
     let res = app.execute(
@@ -102,6 +110,7 @@ fn submit_order() {
                 contract_addr: token_addr.clone(),
             },
         },
+        amm_fallback: false,
     };
 
     let res = app.execute(
@@ -126,6 +135,7 @@ fn submit_order() {
                 contract_addr: token_addr.clone(),
             },
         },
+        amm_fallback: false,
     };
 
     // paid 1000000orai to get 1000000 token_addr
@@ -164,12 +174,19 @@ fn submit_order() {
         amount: Uint128::new(1000000u128),
         msg: to_binary(&Cw20HookMsg::SubmitOrder {
             direction: OrderDirection::Buy,
+            offer_asset: Asset {
+                amount: Uint128::from(1000000u128),
+                info: AssetInfo::Token {
+                    contract_addr: token_addr.clone(),
+                },
+            },
             ask_asset: Asset {
                 amount: Uint128::from(1000000u128),
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
             },
+            amm_fallback: false,
         })
         .unwrap(),
     };
@@ -219,6 +236,7 @@ fn cancel_order_native_token() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -241,6 +259,9 @@ fn cancel_order_native_token() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -263,6 +284,7 @@ fn cancel_order_native_token() {
                 contract_addr: token_addr.clone(),
             },
         },
+        amm_fallback: false,
     };
 
     let _res = app
@@ -346,6 +368,7 @@ fn cancel_order_token() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -368,6 +391,9 @@ fn cancel_order_token() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -381,12 +407,19 @@ fn cancel_order_token() {
         amount: Uint128::new(1000000u128),
         msg: to_binary(&Cw20HookMsg::SubmitOrder {
             direction: OrderDirection::Buy,
+            offer_asset: Asset {
+                amount: Uint128::from(1000000u128),
+                info: AssetInfo::Token {
+                    contract_addr: token_addr.clone(),
+                },
+            },
             ask_asset: Asset {
                 amount: Uint128::from(1000000u128),
                 info: AssetInfo::NativeToken {
                     denom: ORAI_DENOM.to_string(),
                 },
             },
+            amm_fallback: false,
         })
         .unwrap(),
     };
@@ -478,6 +511,7 @@ fn execute_order_native_token() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -500,6 +534,9 @@ fn execute_order_native_token() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -522,6 +559,7 @@ fn execute_order_native_token() {
                 denom: ATOM_DENOM.to_string(),
             },
         },
+        amm_fallback: false,
     };
 
     // offer atom, ask for orai
@@ -708,6 +746,7 @@ fn execute_order_token() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -730,6 +769,9 @@ fn execute_order_token() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -743,12 +785,19 @@ fn execute_order_token() {
         amount: Uint128::new(1000000u128),
         msg: to_binary(&Cw20HookMsg::SubmitOrder {
             direction: OrderDirection::Buy,
+            offer_asset: Asset {
+                amount: Uint128::from(1000000u128),
+                info: AssetInfo::Token {
+                    contract_addr: token_addrs[0].clone(),
+                },
+            },
             ask_asset: Asset {
                 amount: Uint128::from(1000000u128),
                 info: AssetInfo::Token {
                     contract_addr: token_addrs[1].clone(),
                 },
             },
+            amm_fallback: false,
         })
         .unwrap(),
     };
@@ -910,6 +959,7 @@ fn orders_querier() {
         name: None,
         version: None,
         admin: None,
+        factory_addr: None,
     };
     let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
     let limit_order_addr = app
@@ -932,6 +982,9 @@ fn orders_querier() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -950,6 +1003,9 @@ fn orders_querier() {
         },
         precision: None,
         min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
     };
     let _res = app.execute(
         Addr::unchecked("addr0000"),
@@ -986,6 +1042,7 @@ fn orders_querier() {
                 denom: ATOM_DENOM.to_string(),
             },
         },
+        amm_fallback: false,
     };
 
     let _res = app
@@ -1006,12 +1063,19 @@ fn orders_querier() {
         amount: Uint128::from(1000000u128),
         msg: to_binary(&Cw20HookMsg::SubmitOrder {
             direction: OrderDirection::Buy,
+            offer_asset: Asset {
+                amount: Uint128::from(1000000u128),
+                info: AssetInfo::Token {
+                    contract_addr: token_addrs[0].clone(),
+                },
+            },
             ask_asset: Asset {
                 amount: Uint128::from(1000000u128),
                 info: AssetInfo::Token {
                     contract_addr: token_addrs[1].clone(),
                 },
             },
+            amm_fallback: false,
         })
         .unwrap(),
     };
@@ -1244,3 +1308,1514 @@ fn orders_querier() {
         println!("{:?}", res);
     }
 }
+
+#[test]
+fn submit_order_with_tick_size_and_lot_size() {
+    let mut app = MockApp::new(&[(
+        &"addr0000".to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )]);
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        factory_addr: None,
+    };
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let limit_order_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr0000"),
+            &msg,
+            &[],
+            "limit order",
+        )
+        .unwrap();
+
+    let offer_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let ask_info = AssetInfo::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+
+    // create order book without tick size
+    let msg = ExecuteMsg::UpdateOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        precision: None,
+        min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    // legacy order, paid 1000 orai to get 333 atom at price 3.003003
+    let msg = ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Buy,
+        offer_asset: Asset {
+            amount: Uint128::from(1000u128),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(333u128),
+            info: ask_info.clone(),
+        },
+        amm_fallback: false,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    )
+    .unwrap();
+
+    // tick size 0.01 * lot size 10 is not an integer amount
+    let msg = ExecuteMsg::UpdateOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        precision: None,
+        min_offer_amount: Uint128::zero(),
+        tick_size: Some(Decimal::percent(1)),
+        lot_size: Some(Uint128::from(10u128)),
+        self_trade_prevention: None,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    let msg = ExecuteMsg::UpdateOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        precision: None,
+        min_offer_amount: Uint128::zero(),
+        tick_size: Some(Decimal::percent(10)),
+        lot_size: Some(Uint128::from(10u128)),
+        self_trade_prevention: None,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    // only admin can rebucket
+    let msg = ExecuteMsg::RebucketOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        start_after: None,
+        limit: None,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0001"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    // price 3.003003 is rounded down to 3.0, ask 333 down to 330, so 10 orai is refunded
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![("action", "rebucket_orderbook"), ("last_order_id", "1")]
+    );
+
+    let order = app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 1,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(990u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(330u128));
+    assert_eq!(
+        app.query_balance(limit_order_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(990u128)
+    );
+
+    // sell 105 atom to get 321 orai at price 3.057, rounded up to 3.1 for 100 atom, 5 atom is refunded
+    let msg = ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Sell,
+        offer_asset: Asset {
+            amount: Uint128::from(321u128),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(105u128),
+            info: ask_info.clone(),
+        },
+        amm_fallback: false,
+    };
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &msg,
+            &[Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(105u128),
+            }],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "submit_order"),
+            ("order_id", "2"),
+            ("bidder_addr", "addr0000"),
+            ("offer_asset", &format!("310{}", ORAI_DENOM)),
+            ("ask_asset", &format!("100{}", ATOM_DENOM)),
+            ("total_orders", "1")
+        ]
+    );
+    assert_eq!(
+        app.query_balance(limit_order_addr.clone(), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(100u128)
+    );
+
+    // ask amount below lot size is rejected
+    let msg = ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Sell,
+        offer_asset: Asset {
+            amount: Uint128::from(30u128),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(9u128),
+            info: ask_info.clone(),
+        },
+        amm_fallback: false,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[Coin {
+            denom: ATOM_DENOM.to_string(),
+            amount: Uint128::from(9u128),
+        }],
+    );
+    app.assert_fail(res);
+
+    // all orders sit on tick prices
+    let res = app
+        .query::<TicksResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Ticks {
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+                direction: OrderDirection::Sell,
+                start_after: None,
+                limit: None,
+                order_by: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.ticks[0].price, Decimal::from_str("3.1").unwrap());
+}
+
+#[test]
+fn orderbook_lifecycle() {
+    let mut app = MockApp::new(&[
+        (
+            &"addr0000".to_string(),
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        ),
+        (
+            &"addr0001".to_string(),
+            &[Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            }],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        factory_addr: None,
+    };
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let limit_order_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr0000"),
+            &msg,
+            &[],
+            "limit order",
+        )
+        .unwrap();
+
+    let offer_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let ask_info = AssetInfo::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+
+    let msg = ExecuteMsg::UpdateOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        precision: None,
+        min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: None,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    // buy 1000 atom with 1000 orai
+    let buy_msg = ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Buy,
+        offer_asset: Asset {
+            amount: Uint128::from(1000u128),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(1000u128),
+            info: ask_info.clone(),
+        },
+        amm_fallback: false,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &buy_msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    )
+    .unwrap();
+
+    // sell 500 atom for 600 orai
+    let sell_msg = ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Sell,
+        offer_asset: Asset {
+            amount: Uint128::from(600u128),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(500u128),
+            info: ask_info.clone(),
+        },
+        amm_fallback: false,
+    };
+    app.execute(
+        Addr::unchecked("addr0001"),
+        limit_order_addr.clone(),
+        &sell_msg,
+        &[Coin {
+            denom: ATOM_DENOM.to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    )
+    .unwrap();
+
+    // only admin can update status
+    let msg = ExecuteMsg::UpdateOrderBookStatus {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        status: OrderBookStatus::Paused,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0001"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    );
+    app.assert_fail(res);
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    // paused order book rejects new orders
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &buy_msg,
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    app.assert_fail(res);
+
+    // halted order book rejects matching
+    let msg = ExecuteMsg::UpdateOrderBookStatus {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        status: OrderBookStatus::Halted,
+    };
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+    let res = app.execute(
+        Addr::unchecked("addr0001"),
+        limit_order_addr.clone(),
+        &ExecuteMsg::ExecuteOrder {
+            ask_asset: Asset {
+                amount: Uint128::from(100u128),
+                info: ask_info.clone(),
+            },
+            order_id: 1,
+            offer_info: offer_info.clone(),
+        },
+        &[Coin {
+            denom: ATOM_DENOM.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    app.assert_fail(res);
+
+    // delist one order at a time
+    let msg = ExecuteMsg::DelistOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        limit: Some(1),
+    };
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "delist_orderbook"),
+            ("pair", &format!("{}-{}", ORAI_DENOM, ATOM_DENOM)),
+            ("total_refunds", "1"),
+            ("delisted", "false"),
+        ]
+    );
+
+    let order_book = app
+        .query::<OrderBookResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::OrderBook {
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order_book.status, OrderBookStatus::Delisting);
+
+    // delisting can not be reverted
+    let msg = ExecuteMsg::UpdateOrderBookStatus {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        status: OrderBookStatus::Active,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    let msg = ExecuteMsg::DelistOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        limit: None,
+    };
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "delist_orderbook"),
+            ("pair", &format!("{}-{}", ORAI_DENOM, ATOM_DENOM)),
+            ("total_refunds", "1"),
+            ("delisted", "true"),
+        ]
+    );
+
+    // every bidder got back the paid asset
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0000"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128)
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0001"), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(1000000u128)
+    );
+    assert!(app
+        .query::<OrderBookResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::OrderBook {
+                offer_info,
+                ask_info,
+            },
+        )
+        .is_err());
+}
+
+#[test]
+fn submit_order_with_amm_fallback() {
+    let mut app = MockApp::new(&[
+        (
+            &"addr0000".to_string(),
+            &[
+                Coin {
+                    denom: ORAI_DENOM.to_string(),
+                    amount: Uint128::from(10000000u128),
+                },
+                Coin {
+                    denom: ATOM_DENOM.to_string(),
+                    amount: Uint128::from(10000000u128),
+                },
+            ],
+        ),
+        (
+            &"addr0001".to_string(),
+            &[Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(10000u128),
+            }],
+        ),
+    ]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+    // no tax on the pool return
+    app.set_tax(
+        Decimal::zero(),
+        &[
+            (&ORAI_DENOM.to_string(), &Uint128::zero()),
+            (&ATOM_DENOM.to_string(), &Uint128::zero()),
+        ],
+    );
+
+    let offer_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let ask_info = AssetInfo::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+
+    // pool price is 1 orai per atom
+    let pair_addr = app
+        .create_pair([offer_info.clone(), ask_info.clone()])
+        .unwrap();
+    app.execute(
+        Addr::unchecked("addr0000"),
+        pair_addr.clone(),
+        &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+            assets: [
+                Asset {
+                    info: offer_info.clone(),
+                    amount: Uint128::from(1000000u128),
+                },
+                Asset {
+                    info: ask_info.clone(),
+                    amount: Uint128::from(1000000u128),
+                },
+            ],
+            slippage_tolerance: None,
+            receiver: None,
+        },
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )
+    .unwrap();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        factory_addr: Some(app.factory_addr.clone()),
+    };
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let limit_order_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr0000"),
+            &msg,
+            &[],
+            "limit order",
+        )
+        .unwrap();
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &ExecuteMsg::UpdateOrderBook {
+            offer_info: offer_info.clone(),
+            ask_info: ask_info.clone(),
+            precision: None,
+            min_offer_amount: Uint128::zero(),
+            tick_size: None,
+            lot_size: None,
+            self_trade_prevention: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    // sell orders at 0.9 (better than pool) and 1.2 (worse than pool)
+    for (offer_amount, ask_amount) in [(900u128, 1000u128), (1200u128, 1000u128)] {
+        app.execute(
+            Addr::unchecked("addr0001"),
+            limit_order_addr.clone(),
+            &ExecuteMsg::SubmitOrder {
+                direction: OrderDirection::Sell,
+                offer_asset: Asset {
+                    amount: Uint128::from(offer_amount),
+                    info: offer_info.clone(),
+                },
+                ask_asset: Asset {
+                    amount: Uint128::from(ask_amount),
+                    info: ask_info.clone(),
+                },
+                amm_fallback: false,
+            },
+            &[Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(ask_amount),
+            }],
+        )
+        .unwrap();
+    }
+
+    // the order price needs both amounts
+    let buy_msg = |offer_amount: u128, ask_amount: u128| ExecuteMsg::SubmitOrder {
+        direction: OrderDirection::Buy,
+        offer_asset: Asset {
+            amount: Uint128::from(offer_amount),
+            info: offer_info.clone(),
+        },
+        ask_asset: Asset {
+            amount: Uint128::from(ask_amount),
+            info: ask_info.clone(),
+        },
+        amm_fallback: true,
+    };
+    let res = app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &buy_msg(3000, 0),
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(3000u128),
+        }],
+    );
+    app.assert_fail(res);
+
+    // the book fills the 0.9 tick, the rest goes to the pool which is better than the 1.2 tick
+    let simulation: oraiswap::pair::SimulationResponse = app
+        .query(
+            pair_addr.clone(),
+            &oraiswap::pair::QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: offer_info.clone(),
+                    amount: Uint128::from(2100u128),
+                },
+            },
+        )
+        .unwrap();
+    let atom_balance = app
+        .query_balance(Addr::unchecked("addr0000"), ATOM_DENOM.to_string())
+        .unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &buy_msg(3000, 2500),
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(3000u128),
+            }],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "submit_order"),
+            ("bidder_addr", "addr0000"),
+            ("refund_amount", "0"),
+            ("book_paid_amount", "900"),
+            ("amm_paid_amount", "2100"),
+        ]
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0000"), ATOM_DENOM.to_string())
+            .unwrap(),
+        atom_balance + Uint128::from(1000u128) + simulation.return_amount
+    );
+    assert_eq!(
+        app.query_balance(Addr::unchecked("addr0001"), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(900u128)
+    );
+
+    // the 1.2 tick is untouched
+    let order = app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 2,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order.filled_ask_amount, Uint128::zero());
+
+    // the pool can not fill at price 0.9, the order rests
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &buy_msg(900, 1000),
+            &[Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(900u128),
+            }],
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "submit_order"),
+            ("order_id", "3"),
+            ("bidder_addr", "addr0000"),
+            ("offer_asset", &format!("900{}", ORAI_DENOM)),
+            ("ask_asset", &format!("1000{}", ATOM_DENOM)),
+            ("total_orders", "1"),
+            ("book_paid_amount", "0"),
+            ("amm_paid_amount", "0"),
+        ]
+    );
+}
+
+#[test]
+fn submit_order_without_amm_fallback() {
+    // payloads from clients before the amm fallback still parse, without the fallback
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    let ask_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "atom".to_string(),
+        },
+        amount: Uint128::from(200u128),
+    };
+    let payload = br#"{"submit_order":{"direction":"buy","offer_asset":{"info":{"native_token":{"denom":"orai"}},"amount":"100"},"ask_asset":{"info":{"native_token":{"denom":"atom"}},"amount":"200"}}}"#;
+
+    assert_eq!(
+        from_slice::<ExecuteMsg>(payload).unwrap(),
+        ExecuteMsg::SubmitOrder {
+            direction: OrderDirection::Buy,
+            offer_asset: offer_asset.clone(),
+            ask_asset: ask_asset.clone(),
+            amm_fallback: false,
+        }
+    );
+    assert_eq!(
+        from_slice::<Cw20HookMsg>(payload).unwrap(),
+        Cw20HookMsg::SubmitOrder {
+            direction: OrderDirection::Buy,
+            offer_asset,
+            ask_asset,
+            amm_fallback: false,
+        }
+    );
+}
+
+#[test]
+fn execute_order_self_trade_prevention() {
+    let mut app = MockApp::new(&[(
+        &"addr0000".to_string(),
+        &[
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ],
+    )]);
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        factory_addr: None,
+    };
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let limit_order_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr0000"),
+            &msg,
+            &[],
+            "limit order",
+        )
+        .unwrap();
+
+    let offer_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let ask_info = AssetInfo::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+    let update_msg = |self_trade_prevention| ExecuteMsg::UpdateOrderBook {
+        offer_info: offer_info.clone(),
+        ask_info: ask_info.clone(),
+        precision: None,
+        min_offer_amount: Uint128::zero(),
+        tick_size: None,
+        lot_size: None,
+        self_trade_prevention: Some(self_trade_prevention),
+    };
+    let execute_msg = ExecuteMsg::ExecuteOrder {
+        ask_asset: Asset {
+            amount: Uint128::from(400u128),
+            info: ask_info.clone(),
+        },
+        order_id: 1,
+        offer_info: offer_info.clone(),
+    };
+    let atom = [Coin {
+        denom: ATOM_DENOM.to_string(),
+        amount: Uint128::from(400u128),
+    }];
+
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &update_msg(SelfTradePrevention::CancelNewest),
+        &[],
+    )
+    .unwrap();
+
+    // buy 1000 atom with 1000 orai
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &ExecuteMsg::SubmitOrder {
+            direction: OrderDirection::Buy,
+            offer_asset: Asset {
+                amount: Uint128::from(1000u128),
+                info: offer_info.clone(),
+            },
+            ask_asset: Asset {
+                amount: Uint128::from(1000u128),
+                info: ask_info.clone(),
+            },
+            amm_fallback: false,
+        },
+        &[Coin {
+            denom: ORAI_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    )
+    .unwrap();
+
+    // the execution is the newest, nothing is matched
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &execute_msg,
+            &atom,
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "execute_order"),
+            ("order_id", "1"),
+            ("self_trade_cancelled", "executor"),
+        ]
+    );
+    let order = app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 1,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order.filled_ask_amount, Uint128::zero());
+
+    // both sides are decremented by 400 atom
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &update_msg(SelfTradePrevention::DecrementBoth),
+        &[],
+    )
+    .unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &execute_msg,
+            &atom,
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "execute_order"),
+            ("order_id", "1"),
+            ("self_trade_decremented", "1:400"),
+        ]
+    );
+    let order = app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 1,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(600u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(600u128));
+
+    // the resting order is the oldest and gets cancelled
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &update_msg(SelfTradePrevention::CancelOldest),
+        &[],
+    )
+    .unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &execute_msg,
+            &atom,
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "execute_order"),
+            ("order_id", "1"),
+            ("self_trade_cancelled", "1"),
+        ]
+    );
+    assert!(app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 1,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .is_err());
+
+    // sell 1000 atom for 1000 orai, the executor pays orai and the maker paid atom
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &ExecuteMsg::SubmitOrder {
+            direction: OrderDirection::Sell,
+            offer_asset: Asset {
+                amount: Uint128::from(1000u128),
+                info: offer_info.clone(),
+            },
+            ask_asset: Asset {
+                amount: Uint128::from(1000u128),
+                info: ask_info.clone(),
+            },
+            amm_fallback: false,
+        },
+        &[Coin {
+            denom: ATOM_DENOM.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    )
+    .unwrap();
+    let execute_msg = ExecuteMsg::ExecuteOrder {
+        ask_asset: Asset {
+            amount: Uint128::from(400u128),
+            info: offer_info.clone(),
+        },
+        order_id: 2,
+        offer_info: ask_info.clone(),
+    };
+    let orai = [Coin {
+        denom: ORAI_DENOM.to_string(),
+        amount: Uint128::from(400u128),
+    }];
+
+    // the sell order is decremented by 400 atom, refunded in atom
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &update_msg(SelfTradePrevention::DecrementBoth),
+        &[],
+    )
+    .unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &execute_msg,
+            &orai,
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "execute_order"),
+            ("order_id", "2"),
+            ("self_trade_decremented", "2:400"),
+        ]
+    );
+    let order = app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 2,
+                offer_info: offer_info.clone(),
+                ask_info: ask_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(order.offer_asset.amount, Uint128::from(600u128));
+    assert_eq!(order.ask_asset.amount, Uint128::from(600u128));
+    assert_eq!(
+        app.query_balance(limit_order_addr.clone(), ATOM_DENOM.to_string())
+            .unwrap(),
+        Uint128::from(600u128)
+    );
+    assert_eq!(
+        app.query_balance(limit_order_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap(),
+        Uint128::zero()
+    );
+
+    // the resting sell order is cancelled, the 600 atom left are refunded
+    app.execute(
+        Addr::unchecked("addr0000"),
+        limit_order_addr.clone(),
+        &update_msg(SelfTradePrevention::CancelOldest),
+        &[],
+    )
+    .unwrap();
+    let res = app
+        .execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &execute_msg,
+            &orai,
+        )
+        .unwrap();
+    assert_eq!(
+        res.get_attributes(1),
+        vec![
+            ("action", "execute_order"),
+            ("order_id", "2"),
+            ("self_trade_cancelled", "2"),
+        ]
+    );
+    assert!(app
+        .query::<OrderResponse, _>(
+            limit_order_addr.clone(),
+            &QueryMsg::Order {
+                order_id: 2,
+                offer_info,
+                ask_info,
+            },
+        )
+        .is_err());
+    assert_eq!(
+        app.query_all_balances(limit_order_addr.clone()).unwrap(),
+        vec![]
+    );
+
+    // every refund went back to the bidder
+    assert_eq!(
+        app.query_all_balances(Addr::unchecked("addr0000")).unwrap(),
+        vec![
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ]
+    );
+}
+
+fn query_asset_balance(app: &MockApp, addr: &str, info: &AssetInfo) -> Uint128 {
+    match info {
+        AssetInfo::NativeToken { denom } => app
+            .query_balance(Addr::unchecked(addr), denom.to_string())
+            .unwrap(),
+        AssetInfo::Token { contract_addr } => {
+            app.query::<cw20::BalanceResponse, _>(
+                contract_addr.clone(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: addr.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+        }
+    }
+}
+
+/// native paid asset is sent along, cw20 paid asset is approved then pulled by the contract
+fn execute_with_paid_asset(
+    app: &mut MockApp,
+    sender: &str,
+    limit_order_addr: &Addr,
+    msg: &ExecuteMsg,
+    paid_asset: &Asset,
+) {
+    let funds = match &paid_asset.info {
+        AssetInfo::NativeToken { denom } => vec![Coin {
+            denom: denom.to_string(),
+            amount: paid_asset.amount,
+        }],
+        AssetInfo::Token { contract_addr } => {
+            app.execute(
+                Addr::unchecked(sender),
+                contract_addr.clone(),
+                &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: limit_order_addr.to_string(),
+                    amount: paid_asset.amount,
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+            vec![]
+        }
+    };
+    app.execute(
+        Addr::unchecked(sender),
+        limit_order_addr.clone(),
+        msg,
+        &funds,
+    )
+    .unwrap();
+}
+
+/// submit an order paying the paid asset, cw20 goes through the hook
+fn submit_with_paid_asset(
+    app: &mut MockApp,
+    sender: &str,
+    limit_order_addr: &Addr,
+    direction: OrderDirection,
+    offer_asset: Asset,
+    ask_asset: Asset,
+) {
+    let paid_asset = match direction {
+        OrderDirection::Buy => offer_asset.clone(),
+        OrderDirection::Sell => ask_asset.clone(),
+    };
+    match &paid_asset.info {
+        AssetInfo::NativeToken { .. } => execute_with_paid_asset(
+            app,
+            sender,
+            limit_order_addr,
+            &ExecuteMsg::SubmitOrder {
+                direction,
+                offer_asset,
+                ask_asset,
+                amm_fallback: false,
+            },
+            &paid_asset,
+        ),
+        AssetInfo::Token { contract_addr } => {
+            app.execute(
+                Addr::unchecked(sender),
+                contract_addr.clone(),
+                &cw20::Cw20ExecuteMsg::Send {
+                    contract: limit_order_addr.to_string(),
+                    amount: paid_asset.amount,
+                    msg: to_binary(&Cw20HookMsg::SubmitOrder {
+                        direction,
+                        offer_asset,
+                        ask_asset,
+                        amm_fallback: false,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+    }
+}
+
+#[test]
+fn submit_execute_cancel_any_asset_combination() {
+    for (quote_is_token, base_is_token) in
+        [(false, false), (false, true), (true, false), (true, true)]
+    {
+        let init_coins = [
+            Coin {
+                denom: ORAI_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+            Coin {
+                denom: ATOM_DENOM.to_string(),
+                amount: Uint128::from(1000000u128),
+            },
+        ];
+        let mut app = MockApp::new(&[
+            (&"addr0000".to_string(), &init_coins),
+            (&"addr0001".to_string(), &init_coins),
+        ]);
+        app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+        app.set_token_balances(&[
+            (
+                &"quote".to_string(),
+                &[
+                    (&"addr0000".to_string(), &Uint128::from(1000000u128)),
+                    (&"addr0001".to_string(), &Uint128::from(1000000u128)),
+                ],
+            ),
+            (
+                &"base".to_string(),
+                &[
+                    (&"addr0000".to_string(), &Uint128::from(1000000u128)),
+                    (&"addr0001".to_string(), &Uint128::from(1000000u128)),
+                ],
+            ),
+        ]);
+
+        let quote_info = if quote_is_token {
+            AssetInfo::Token {
+                contract_addr: app.get_token_addr("quote").unwrap(),
+            }
+        } else {
+            AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            }
+        };
+        let base_info = if base_is_token {
+            AssetInfo::Token {
+                contract_addr: app.get_token_addr("base").unwrap(),
+            }
+        } else {
+            AssetInfo::NativeToken {
+                denom: ATOM_DENOM.to_string(),
+            }
+        };
+        let quote = |amount: u128| Asset {
+            info: quote_info.clone(),
+            amount: Uint128::from(amount),
+        };
+        let base = |amount: u128| Asset {
+            info: base_info.clone(),
+            amount: Uint128::from(amount),
+        };
+
+        let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+        let limit_order_addr = app
+            .instantiate(
+                code_id,
+                Addr::unchecked("addr0000"),
+                &InstantiateMsg {
+                    name: None,
+                    version: None,
+                    admin: None,
+                    factory_addr: None,
+                },
+                &[],
+                "limit order",
+            )
+            .unwrap();
+
+        app.execute(
+            Addr::unchecked("addr0000"),
+            limit_order_addr.clone(),
+            &ExecuteMsg::UpdateOrderBook {
+                offer_info: quote_info.clone(),
+                ask_info: base_info.clone(),
+                precision: None,
+                min_offer_amount: Uint128::zero(),
+                tick_size: None,
+                lot_size: None,
+                self_trade_prevention: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let balances_before = [
+            query_asset_balance(&app, "addr0000", &quote_info),
+            query_asset_balance(&app, "addr0000", &base_info),
+            query_asset_balance(&app, "addr0001", &quote_info),
+            query_asset_balance(&app, "addr0001", &base_info),
+        ];
+
+        // buy order pays the quote asset, sell order pays the base asset
+        for (sender, direction, offer_asset, ask_asset) in [
+            ("addr0000", OrderDirection::Buy, quote(1000), base(1000)),
+            ("addr0001", OrderDirection::Sell, quote(1200), base(1000)),
+        ] {
+            submit_with_paid_asset(
+                &mut app,
+                sender,
+                &limit_order_addr,
+                direction,
+                offer_asset,
+                ask_asset,
+            );
+        }
+
+        // the hook must send the paid asset
+        if let AssetInfo::Token { contract_addr } = &base_info {
+            let res = app.execute(
+                Addr::unchecked("addr0001"),
+                contract_addr.clone(),
+                &cw20::Cw20ExecuteMsg::Send {
+                    contract: limit_order_addr.to_string(),
+                    amount: Uint128::from(1000u128),
+                    msg: to_binary(&Cw20HookMsg::SubmitOrder {
+                        direction: OrderDirection::Buy,
+                        offer_asset: quote(1000),
+                        ask_asset: base(1000),
+                        amm_fallback: false,
+                    })
+                    .unwrap(),
+                },
+                &[],
+            );
+            app.assert_fail(res);
+        }
+
+        // fill the buy order partially, the executor pays base and receives quote
+        execute_with_paid_asset(
+            &mut app,
+            "addr0001",
+            &limit_order_addr,
+            &ExecuteMsg::ExecuteOrder {
+                ask_asset: base(400),
+                order_id: 1,
+                offer_info: quote_info.clone(),
+            },
+            &base(400),
+        );
+
+        // fill the sell order partially, the executor pays quote and receives base
+        execute_with_paid_asset(
+            &mut app,
+            "addr0000",
+            &limit_order_addr,
+            &ExecuteMsg::ExecuteOrder {
+                ask_asset: quote(600),
+                order_id: 2,
+                offer_info: base_info.clone(),
+            },
+            &quote(600),
+        );
+
+        // cancel both orders, the buy order refunds quote and the sell order refunds base
+        for (sender, order_id) in [("addr0000", 1), ("addr0001", 2)] {
+            app.execute(
+                Addr::unchecked(sender),
+                limit_order_addr.clone(),
+                &ExecuteMsg::CancelOrder {
+                    order_id,
+                    offer_info: quote_info.clone(),
+                    ask_info: base_info.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+        }
+
+        assert_eq!(
+            [
+                query_asset_balance(&app, "addr0000", &quote_info),
+                query_asset_balance(&app, "addr0000", &base_info),
+                query_asset_balance(&app, "addr0001", &quote_info),
+                query_asset_balance(&app, "addr0001", &base_info),
+            ],
+            [
+                balances_before[0] - Uint128::from(1000u128),
+                balances_before[1] + Uint128::from(900u128),
+                balances_before[2] + Uint128::from(1000u128),
+                balances_before[3] - Uint128::from(900u128),
+            ]
+        );
+        assert_eq!(
+            query_asset_balance(&app, limit_order_addr.as_str(), &quote_info),
+            Uint128::zero()
+        );
+        assert_eq!(
+            query_asset_balance(&app, limit_order_addr.as_str(), &base_info),
+            Uint128::zero()
+        );
+
+        // self trade on a sell order, the maker is refunded the base asset it paid
+        let balances_before = [
+            query_asset_balance(&app, "addr0001", &quote_info),
+            query_asset_balance(&app, "addr0001", &base_info),
+        ];
+        submit_with_paid_asset(
+            &mut app,
+            "addr0001",
+            &limit_order_addr,
+            OrderDirection::Sell,
+            quote(1200),
+            base(1000),
+        );
+        for (self_trade_prevention, order_left) in [
+            (SelfTradePrevention::DecrementBoth, true),
+            (SelfTradePrevention::CancelOldest, false),
+        ] {
+            app.execute(
+                Addr::unchecked("addr0000"),
+                limit_order_addr.clone(),
+                &ExecuteMsg::UpdateOrderBook {
+                    offer_info: quote_info.clone(),
+                    ask_info: base_info.clone(),
+                    precision: None,
+                    min_offer_amount: Uint128::zero(),
+                    tick_size: None,
+                    lot_size: None,
+                    self_trade_prevention: Some(self_trade_prevention),
+                },
+                &[],
+            )
+            .unwrap();
+            execute_with_paid_asset(
+                &mut app,
+                "addr0001",
+                &limit_order_addr,
+                &ExecuteMsg::ExecuteOrder {
+                    ask_asset: quote(600),
+                    order_id: 3,
+                    offer_info: base_info.clone(),
+                },
+                &quote(600),
+            );
+            let order = app.query::<OrderResponse, _>(
+                limit_order_addr.clone(),
+                &QueryMsg::Order {
+                    order_id: 3,
+                    offer_info: quote_info.clone(),
+                    ask_info: base_info.clone(),
+                },
+            );
+            assert_eq!(order.is_ok(), order_left);
+        }
+
+        assert_eq!(
+            [
+                query_asset_balance(&app, "addr0001", &quote_info),
+                query_asset_balance(&app, "addr0001", &base_info),
+                query_asset_balance(&app, limit_order_addr.as_str(), &quote_info),
+                query_asset_balance(&app, limit_order_addr.as_str(), &base_info),
+            ],
+            [
+                balances_before[0],
+                balances_before[1],
+                Uint128::zero(),
+                Uint128::zero()
+            ]
+        );
+    }
+}
@@ -1,9 +1,11 @@
 use std::str::FromStr;
 
-use cosmwasm_std::{testing::mock_dependencies, Api, Decimal};
+use cosmwasm_std::{
+    testing::mock_dependencies, Api, Attribute, Decimal, Order as OrderBy, Uint128,
+};
 use oraiswap::{
     asset::{AssetInfoRaw, ORAI_DENOM},
-    limit_order::OrderDirection,
+    limit_order::{OrderDirection, SelfTradePrevention},
     testing::ATOM_DENOM,
 };
 
@@ -580,7 +582,7 @@ fn matchable_orders() {
 
     let ask_order = &mut match_buy_orders[0];
 
-    let messages = ob
+    let res = ob
         .distribute_order_to_orders(deps.as_mut(), ask_order, &mut offer_orders)
         .unwrap();
 
@@ -590,10 +592,88 @@ fn matchable_orders() {
         assert_eq!(offer_order.ask_amount, offer_order.filled_ask_amount);
     }
 
-    println!("messages {:?}", messages);
+    println!("messages {:?}", res.messages);
     println!(
         "ask order {}\noffer order {}",
         jsonstr!(ask_order),
         jsonstr!(offer_orders)
     );
 }
+
+#[test]
+fn matchable_orders_self_trade_prevention() {
+    let mut deps = mock_dependencies();
+    let offer_info = AssetInfoRaw::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let ask_info = AssetInfoRaw::NativeToken {
+        denom: ATOM_DENOM.to_string(),
+    };
+    let bidder_addr = deps.api.addr_canonicalize("addr0000").unwrap();
+    let other_bidder_addr = deps.api.addr_canonicalize("addr0001").unwrap();
+
+    init_last_order_id(deps.as_mut().storage).unwrap();
+
+    let orders = [
+        Order::new(
+            increase_last_order_id(deps.as_mut().storage).unwrap(),
+            bidder_addr.clone(),
+            OrderDirection::Sell,
+            Decimal::from_str("1.099").unwrap(),
+            10000u128.into(),
+        ),
+        Order::new(
+            increase_last_order_id(deps.as_mut().storage).unwrap(),
+            other_bidder_addr,
+            OrderDirection::Sell,
+            Decimal::from_str("1.099").unwrap(),
+            5000u128.into(),
+        ),
+        Order::new(
+            increase_last_order_id(deps.as_mut().storage).unwrap(),
+            bidder_addr,
+            OrderDirection::Buy,
+            Decimal::from_str("1.099").unwrap(),
+            15000u128.into(),
+        ),
+    ];
+
+    let mut ob = OrderBook::new(ask_info, offer_info, None);
+    ob.self_trade_prevention = Some(SelfTradePrevention::CancelOldest);
+    for order in orders.iter() {
+        ob.add_order(deps.as_mut().storage, order).unwrap();
+    }
+
+    let mut offer_orders = ob
+        .orders_at(
+            deps.as_ref().storage,
+            Decimal::from_str("1.099").unwrap(),
+            OrderDirection::Sell,
+            None,
+            None,
+        )
+        .unwrap();
+    let mut ask_order = orders[2].clone();
+
+    // the older sell order of the same bidder is cancelled, the other one is matched
+    let res = ob
+        .distribute_order_to_orders(deps.as_mut(), &mut ask_order, &mut offer_orders)
+        .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![Attribute::new("self_trade_cancelled", "1")]
+    );
+    assert_eq!(ask_order.filled_ask_amount, Uint128::from(5000u128));
+    assert_eq!(offer_orders[1].filled_ask_amount, Uint128::from(5000u128));
+
+    let orders = ob
+        .get_orders(deps.as_ref().storage, None, None, Some(OrderBy::Ascending))
+        .unwrap();
+    assert_eq!(
+        orders
+            .iter()
+            .map(|order| order.order_id)
+            .collect::<Vec<u64>>(),
+        vec![3]
+    );
+}
@@ -1,22 +1,42 @@
 use cosmwasm_std::{entry_point, Coin};
 
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, Uint128,
+    to_binary, Addr, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Response, StdError, StdResult, Storage, Uint128,
 };
 
-use oraiswap::asset::ORAI_DENOM;
+use oraiswap::admin::{
+    accept_admin, cancel_change, propose_admin, query_pending_admin, query_timelock, queue_change,
+    store_timelock_delay, take_change,
+};
+use oraiswap::asset::{AssetInfo, ORAI_DENOM};
 use oraiswap::oracle::{
-    ContractInfo, ContractInfoResponse, ExchangeRateItem, ExchangeRateResponse,
-    ExchangeRatesResponse, ExecuteMsg, MigrateMsg, OracleContractQuery, OracleExchangeQuery,
-    OracleTreasuryQuery, QueryMsg, TaxCapResponse, TaxRateResponse,
+    ContractInfo, ContractInfoResponse, CrossRateResponse, ExchangeRateAtResponse,
+    ExchangeRateFeed, ExchangeRateHistoryItem, ExchangeRateHistoryResponse,
+    ExchangeRateInfoResponse, ExchangeRateItem, ExchangeRateResponse, ExchangeRatesResponse,
+    ExecuteMsg, FeedersResponse, HistoryConfig, MigrateMsg, OracleContractQuery,
+    OracleExchangeQuery, OracleTreasuryQuery, QueryMsg, RoundResponse, TaxCapResponse,
+    TaxRateForResponse, TaxRateResponse,
 };
 
 use oraiswap::error::ContractError;
 use oraiswap::oracle::InstantiateMsg;
 
 // use crate::msg::{ExecuteMsg, InstantiateMsg};
-use crate::state::{CONTRACT_INFO, EXCHANGE_RATES, TAX_CAP, TAX_RATE};
+use crate::pool_price::{
+    check_pool_deviation, compute_pool_rate, execute_observe_prices, execute_update_price_source,
+    query_pool_price,
+};
+use crate::state::{
+    Round, CONTRACT_INFO, DENOM_TAX_RATE, EXCHANGE_RATES, EXCHANGE_RATE_HISTORY,
+    EXCHANGE_RATE_HISTORY_SIZE, EXCHANGE_RATE_UPDATED, FEEDERS, FEEDER_CONFIG, HISTORY_CONFIG,
+    PRICE_SOURCES, ROUNDS, TAX_CAP, TAX_EXEMPT, TAX_RATE,
+};
+use cw_storage_plus::Bound;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:oraiswap_oracle";
@@ -59,19 +79,64 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // tax changes wait for the timelock
+    if is_timelocked(&msg) {
+        assert_admin(deps.as_ref(), &info.sender)?;
+        if let Some(change) = queue_change(deps.storage, &env, &msg)? {
+            return Ok(Response::new().add_attributes(change.attributes()));
+        }
+    }
+
     match msg {
         ExecuteMsg::UpdateExchangeRate {
             denom,
             exchange_rate,
-        } => execute_update_exchange_rate(deps, info, denom, exchange_rate),
+        } => execute_update_exchange_rate(deps, env, info, denom, exchange_rate),
         ExecuteMsg::DeleteExchangeRate { denom } => execute_delete_exchange_rate(deps, info, denom),
         ExecuteMsg::UpdateTaxCap { cap, denom } => execute_update_tax_cap(deps, info, denom, cap),
         ExecuteMsg::UpdateTaxRate { rate } => execute_update_tax_rate(deps, info, rate),
+        ExecuteMsg::UpdateDenomTaxRate { denom, rate } => {
+            execute_update_denom_tax_rate(deps, info, denom, rate)
+        }
+        ExecuteMsg::AddTaxExempt { address } => execute_add_tax_exempt(deps, info, address),
+        ExecuteMsg::RemoveTaxExempt { address } => execute_remove_tax_exempt(deps, info, address),
         ExecuteMsg::UpdateAdmin { admin } => execute_update_admin(deps, info, admin),
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, info),
+        ExecuteMsg::UpdateTimelock { delay } => execute_update_timelock(deps, info, delay),
+        ExecuteMsg::ExecuteTimelocked { id } => execute_timelocked(deps, env, info, id),
+        ExecuteMsg::CancelTimelocked { id } => execute_cancel_timelocked(deps, info, id),
+        ExecuteMsg::AddFeeder { feeder } => execute_add_feeder(deps, info, feeder),
+        ExecuteMsg::RemoveFeeder { feeder } => execute_remove_feeder(deps, info, feeder),
+        ExecuteMsg::UpdateFeederConfig {
+            min_feeders,
+            round_duration,
+            max_deviation,
+            max_age,
+        } => execute_update_feeder_config(
+            deps,
+            info,
+            min_feeders,
+            round_duration,
+            max_deviation,
+            max_age,
+        ),
+        ExecuteMsg::FeedExchangeRates { exchange_rates } => {
+            execute_feed_exchange_rates(deps, env, info, exchange_rates)
+        }
+        ExecuteMsg::UpdateHistoryConfig {
+            max_entries,
+            retention,
+        } => execute_update_history_config(deps, info, max_entries, retention),
+        ExecuteMsg::UpdatePriceSource { asset_info, source } => {
+            execute_update_price_source(deps, info, asset_info, source)
+        }
+        ExecuteMsg::ObservePrices { asset_infos } => {
+            execute_observe_prices(deps, env, info, asset_infos)
+        }
     }
 }
 
@@ -117,12 +182,75 @@ pub fn execute_update_tax_rate(
     Ok(Response::default())
 }
 
+pub fn execute_update_denom_tax_rate(
+    deps: DepsMut,
+    info: MessageInfo,
+    denom: String,
+    rate: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match rate {
+        Some(rate) => {
+            let rate = rate.clamp(contract_info.min_rate, contract_info.max_rate);
+            DENOM_TAX_RATE.save(deps.storage, denom.as_bytes(), &rate)?;
+        }
+        None => DENOM_TAX_RATE.remove(deps.storage, denom.as_bytes()),
+    }
+
+    Ok(Response::default())
+}
+
+pub fn execute_add_tax_exempt(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    TAX_EXEMPT.save(deps.storage, address_raw.as_slice(), &true)?;
+
+    Ok(Response::default())
+}
+
+pub fn execute_remove_tax_exempt(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: Addr,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+    TAX_EXEMPT.remove(deps.storage, address_raw.as_slice());
+
+    Ok(Response::default())
+}
+
 pub fn execute_update_admin(
     deps: DepsMut,
     info: MessageInfo,
     admin: Addr,
 ) -> Result<Response, ContractError> {
-    let mut contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
     let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
 
     // check authorized
@@ -130,16 +258,93 @@ pub fn execute_update_admin(
         return Err(ContractError::Unauthorized {});
     }
 
-    // update new admin
-    contract_info.admin = deps.api.addr_canonicalize(admin.as_str())?;
+    // the new admin must accept first
+    propose_admin(deps.storage, deps.api, &admin)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "propose_admin"),
+        ("pending_admin", admin.as_str()),
+    ]))
+}
+
+pub fn execute_accept_admin(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut contract_info = CONTRACT_INFO.load(deps.storage)?;
+    contract_info.admin = accept_admin(deps.storage, deps.api, &info.sender)?;
     CONTRACT_INFO.save(deps.storage, &contract_info)?;
 
-    // return nothing new
+    Ok(Response::new()
+        .add_attributes([("action", "accept_admin"), ("admin", info.sender.as_str())]))
+}
+
+pub fn execute_update_timelock(
+    deps: DepsMut,
+    info: MessageInfo,
+    delay: u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    store_timelock_delay(deps.storage, delay)?;
+
     Ok(Response::default())
 }
 
+pub fn execute_timelocked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+
+    match take_change(deps.storage, &env, id)? {
+        ExecuteMsg::UpdateTaxCap { cap, denom } => execute_update_tax_cap(deps, info, denom, cap),
+        ExecuteMsg::UpdateTaxRate { rate } => execute_update_tax_rate(deps, info, rate),
+        ExecuteMsg::UpdateDenomTaxRate { denom, rate } => {
+            execute_update_denom_tax_rate(deps, info, denom, rate)
+        }
+        ExecuteMsg::UpdateTimelock { delay } => execute_update_timelock(deps, info, delay),
+        _ => Err(ContractError::Std(StdError::generic_err(
+            "Invalid timelocked change",
+        ))),
+    }
+}
+
+pub fn execute_cancel_timelocked(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    assert_admin(deps.as_ref(), &info.sender)?;
+    cancel_change(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attributes([("action", "cancel_change"), ("change_id", &id.to_string())]))
+}
+
+fn is_timelocked(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::UpdateTaxCap { .. }
+            | ExecuteMsg::UpdateTaxRate { .. }
+            | ExecuteMsg::UpdateDenomTaxRate { .. }
+            | ExecuteMsg::UpdateTimelock { .. }
+    )
+}
+
+fn assert_admin(deps: Deps, sender: &Addr) -> Result<(), ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    if contract_info
+        .admin
+        .ne(&deps.api.addr_canonicalize(sender.as_str())?)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
 pub fn execute_update_exchange_rate(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     denom: String,
     exchange_rate: Decimal,
@@ -152,7 +357,13 @@ pub fn execute_update_exchange_rate(
         return Err(ContractError::Unauthorized {});
     }
 
-    EXCHANGE_RATES.save(deps.storage, denom.as_bytes(), &exchange_rate)?;
+    check_pool_deviation(deps.as_ref(), &env, &denom, exchange_rate)?;
+    save_exchange_rate(
+        deps.storage,
+        denom.as_bytes(),
+        exchange_rate,
+        env.block.time.seconds(),
+    )?;
 
     Ok(Response::default())
 }
@@ -171,16 +382,272 @@ pub fn execute_delete_exchange_rate(
     }
 
     EXCHANGE_RATES.remove(deps.storage, denom.as_bytes());
+    EXCHANGE_RATE_UPDATED.remove(deps.storage, denom.as_bytes());
+
+    Ok(Response::default())
+}
+
+pub fn execute_add_feeder(
+    deps: DepsMut,
+    info: MessageInfo,
+    feeder: Addr,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let feeder_raw = deps.api.addr_canonicalize(feeder.as_str())?;
+    FEEDERS.save(deps.storage, feeder_raw.as_slice(), &true)?;
+
+    Ok(Response::default())
+}
+
+pub fn execute_remove_feeder(
+    deps: DepsMut,
+    info: MessageInfo,
+    feeder: Addr,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let feeder_raw = deps.api.addr_canonicalize(feeder.as_str())?;
+    FEEDERS.remove(deps.storage, feeder_raw.as_slice());
+
+    Ok(Response::default())
+}
+
+pub fn execute_update_feeder_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    min_feeders: Option<u32>,
+    round_duration: Option<u64>,
+    max_deviation: Option<Decimal>,
+    max_age: Option<u64>,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config = FEEDER_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(min_feeders) = min_feeders {
+        if min_feeders == 0 {
+            return Err(ContractError::Std(StdError::generic_err(
+                "Min feeders must be positive",
+            )));
+        }
+        config.min_feeders = min_feeders;
+    }
+    if let Some(round_duration) = round_duration {
+        config.round_duration = round_duration;
+    }
+    if let Some(max_deviation) = max_deviation {
+        config.max_deviation = max_deviation;
+    }
+    if let Some(max_age) = max_age {
+        config.max_age = max_age;
+    }
+    FEEDER_CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::default())
+}
+
+pub fn execute_update_history_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_entries: Option<u32>,
+    retention: Option<u64>,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut config = HISTORY_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(max_entries) = max_entries {
+        config.max_entries = max_entries;
+    }
+    if let Some(retention) = retention {
+        config.retention = retention;
+    }
+    HISTORY_CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::default())
 }
 
+/// each feeder submits once per round, a new submission replaces the previous one.
+/// The rate is the median of the round once it has enough submissions
+pub fn execute_feed_exchange_rates(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    exchange_rates: Vec<ExchangeRateFeed>,
+) -> Result<Response, ContractError> {
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if FEEDERS
+        .may_load(deps.storage, sender_addr.as_slice())?
+        .is_none()
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let config = FEEDER_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    let now = env.block.time.seconds();
+    let mut updated_denoms = vec![];
+    for feed in exchange_rates {
+        if feed.exchange_rate.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let denom_key = feed.denom.as_bytes();
+        let mut round = match ROUNDS.may_load(deps.storage, denom_key)? {
+            Some(round) if now < round.start_time + config.round_duration => round,
+            prev_round => Round {
+                id: prev_round.map_or(1, |round| round.id + 1),
+                start_time: now,
+                submissions: vec![],
+            },
+        };
+
+        match round
+            .submissions
+            .iter_mut()
+            .find(|(feeder, _)| feeder.eq(&sender_addr))
+        {
+            Some(submission) => submission.1 = feed.exchange_rate,
+            None => round
+                .submissions
+                .push((sender_addr.clone(), feed.exchange_rate)),
+        }
+
+        if round.submissions.len() >= config.min_feeders as usize {
+            let exchange_rate = aggregate_submissions(&round.submissions, config.max_deviation);
+            check_pool_deviation(deps.as_ref(), &env, &feed.denom, exchange_rate)?;
+            save_exchange_rate(deps.storage, denom_key, exchange_rate, now)?;
+            updated_denoms.push(feed.denom.clone());
+        }
+        ROUNDS.save(deps.storage, denom_key, &round)?;
+    }
+
+    let mut response = Response::new().add_attributes([
+        ("action", "feed_exchange_rates"),
+        ("feeder", info.sender.as_str()),
+    ]);
+    if !updated_denoms.is_empty() {
+        response = response.add_attribute("updated_denoms", updated_denoms.join(","));
+    }
+
+    Ok(response)
+}
+
+/// median of the submissions within max_deviation of the median of all of them
+fn aggregate_submissions(
+    submissions: &[(CanonicalAddr, Decimal)],
+    max_deviation: Decimal,
+) -> Decimal {
+    let prices = submissions
+        .iter()
+        .map(|(_, price)| *price)
+        .collect::<Vec<Decimal>>();
+    let median_price = median(prices.clone());
+    let max_distance = median_price * max_deviation;
+    let accepted_prices = prices
+        .into_iter()
+        .filter(|price| {
+            let distance = if *price > median_price {
+                *price - median_price
+            } else {
+                median_price - *price
+            };
+            distance <= max_distance
+        })
+        .collect::<Vec<Decimal>>();
+
+    if accepted_prices.is_empty() {
+        median_price
+    } else {
+        median(accepted_prices)
+    }
+}
+
+/// saves the rate with its history, the oldest entries beyond the retention are pruned
+fn save_exchange_rate(
+    storage: &mut dyn Storage,
+    denom_key: &[u8],
+    exchange_rate: Decimal,
+    now: u64,
+) -> StdResult<()> {
+    EXCHANGE_RATES.save(storage, denom_key, &exchange_rate)?;
+    EXCHANGE_RATE_UPDATED.save(storage, denom_key, &now)?;
+
+    let mut size = EXCHANGE_RATE_HISTORY_SIZE
+        .may_load(storage, denom_key)?
+        .unwrap_or_default();
+    if !EXCHANGE_RATE_HISTORY.has(storage, (denom_key, now)) {
+        size += 1;
+    }
+    EXCHANGE_RATE_HISTORY.save(storage, (denom_key, now), &exchange_rate)?;
+
+    let config = HISTORY_CONFIG.may_load(storage)?.unwrap_or_default();
+    let cutoff = if config.retention > 0 {
+        now.saturating_sub(config.retention)
+    } else {
+        0
+    };
+    let mut expired = vec![];
+    for time in EXCHANGE_RATE_HISTORY
+        .prefix(denom_key)
+        .keys(storage, None, None, Order::Ascending)
+    {
+        let time = time?;
+        let over_size = config.max_entries > 0 && size > config.max_entries;
+        if !over_size && time >= cutoff {
+            break;
+        }
+        expired.push(time);
+        size -= 1;
+    }
+    for time in expired {
+        EXCHANGE_RATE_HISTORY.remove(storage, (denom_key, time));
+    }
+
+    EXCHANGE_RATE_HISTORY_SIZE.save(storage, denom_key, &size)
+}
+
+fn median(mut prices: Vec<Decimal>) -> Decimal {
+    prices.sort();
+    let middle = prices.len() / 2;
+    if prices.len().is_multiple_of(2) {
+        (prices[middle - 1] + prices[middle]) * Decimal::percent(50)
+    } else {
+        prices[middle]
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Treasury(query_data) => match query_data {
             OracleTreasuryQuery::TaxRate {} => to_binary(&query_tax_rate(deps)?),
             OracleTreasuryQuery::TaxCap { denom } => to_binary(&query_tax_cap(deps, denom)?),
+            OracleTreasuryQuery::TaxRateFor { denom, address } => {
+                to_binary(&query_tax_rate_for(deps, denom, address)?)
+            }
         },
         QueryMsg::Exchange(query_data) => match query_data {
             OracleExchangeQuery::ExchangeRate {
@@ -188,6 +655,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 quote_denom,
             } => to_binary(&query_exchange_rate(
                 deps,
+                env,
                 base_denom.unwrap_or(ORAI_DENOM.to_string()),
                 quote_denom,
             )?),
@@ -196,15 +664,47 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 quote_denoms,
             } => to_binary(&query_exchange_rates(
                 deps,
+                env,
                 base_denom.unwrap_or(ORAI_DENOM.to_string()),
                 quote_denoms,
             )?),
+            OracleExchangeQuery::ExchangeRateInfo { denom } => {
+                to_binary(&query_exchange_rate_info(deps, env, denom)?)
+            }
+            OracleExchangeQuery::Round { denom } => to_binary(&query_round(deps, denom)?),
+            OracleExchangeQuery::ExchangeRateAt { quote_denom, time } => {
+                to_binary(&query_exchange_rate_at(deps, quote_denom, time)?)
+            }
+            OracleExchangeQuery::ExchangeRateHistory {
+                quote_denom,
+                start_after,
+                limit,
+            } => to_binary(&query_exchange_rate_history(
+                deps,
+                quote_denom,
+                start_after,
+                limit,
+            )?),
+            OracleExchangeQuery::CrossRate { base, quote } => {
+                to_binary(&query_cross_rate(deps, env, base, quote)?)
+            }
+            OracleExchangeQuery::PoolPrice { asset_info } => {
+                to_binary(&query_pool_price(deps, env, asset_info)?)
+            }
         },
         QueryMsg::Contract(query_data) => match query_data {
             OracleContractQuery::ContractInfo {} => to_binary(&query_contract_info(deps)?),
             OracleContractQuery::RewardPool { denom } => {
                 to_binary(&query_contract_balance(deps, env, denom)?)
             }
+            OracleContractQuery::Feeders {} => to_binary(&query_feeders(deps)?),
+            OracleContractQuery::HistoryConfig {} => to_binary(&query_history_config(deps)?),
+            OracleContractQuery::PendingAdmin {} => {
+                to_binary(&query_pending_admin(deps.storage, deps.api)?)
+            }
+            OracleContractQuery::Timelock { start_after, limit } => {
+                to_binary(&query_timelock(deps.storage, start_after, limit)?)
+            }
         },
     }
 }
@@ -229,14 +729,50 @@ pub fn query_tax_cap(deps: Deps, denom: String) -> StdResult<TaxCapResponse> {
     })
 }
 
+pub fn query_tax_rate_for(
+    deps: Deps,
+    denom: String,
+    address: Option<Addr>,
+) -> StdResult<TaxRateForResponse> {
+    let exempt = match address {
+        Some(address) => {
+            let address_raw = deps.api.addr_canonicalize(address.as_str())?;
+            TAX_EXEMPT.has(deps.storage, address_raw.as_slice())
+        }
+        None => false,
+    };
+    if exempt {
+        return Ok(TaxRateForResponse {
+            denom,
+            rate: Decimal::zero(),
+            cap: Uint128::zero(),
+            exempt,
+        });
+    }
+
+    let rate = match DENOM_TAX_RATE.may_load(deps.storage, denom.as_bytes())? {
+        Some(rate) => rate,
+        None => query_tax_rate(deps)?.rate,
+    };
+    let cap = query_tax_cap(deps, denom.clone())?.cap;
+
+    Ok(TaxRateForResponse {
+        denom,
+        rate,
+        cap,
+        exempt,
+    })
+}
+
 pub fn query_exchange_rate(
     deps: Deps,
+    env: Env,
     base_denom: String,
     quote_denom: String,
 ) -> StdResult<ExchangeRateResponse> {
     // quote = ask, offer = base
-    let base_rate = get_orai_exchange_rate(deps, &base_denom)?;
-    let quote_rate = get_orai_exchange_rate(deps, &quote_denom)?;
+    let base_rate = get_orai_exchange_rate(deps, &env, &base_denom)?;
+    let quote_rate = get_orai_exchange_rate(deps, &env, &quote_denom)?;
 
     let res = ExchangeRateResponse {
         base_denom: base_denom.clone(),
@@ -249,8 +785,39 @@ pub fn query_exchange_rate(
     Ok(res)
 }
 
+pub fn query_cross_rate(
+    deps: Deps,
+    env: Env,
+    base: AssetInfo,
+    quote: AssetInfo,
+) -> StdResult<CrossRateResponse> {
+    let (base_rate, base_path) = load_orai_exchange_rate(deps, &env, &base.to_string(), 0)?;
+    let (quote_rate, quote_path) = load_orai_exchange_rate(deps, &env, &quote.to_string(), 0)?;
+
+    // the paths of both assets meet at Orai at the latest, drop their common tail
+    let mut base_path = [vec![base.clone()], base_path].concat();
+    let mut quote_path = [vec![quote.clone()], quote_path].concat();
+    while base_path.len() > 1
+        && quote_path.len() > 1
+        && base_path[base_path.len() - 2] == quote_path[quote_path.len() - 2]
+    {
+        base_path.pop();
+        quote_path.pop();
+    }
+    quote_path.pop();
+    quote_path.reverse();
+
+    Ok(CrossRateResponse {
+        base,
+        quote,
+        exchange_rate: quote_rate / base_rate,
+        path: [base_path, quote_path].concat(),
+    })
+}
+
 pub fn query_exchange_rates(
     deps: Deps,
+    env: Env,
     base_denom: String,
     quote_denoms: Vec<String>,
 ) -> StdResult<ExchangeRatesResponse> {
@@ -259,10 +826,10 @@ pub fn query_exchange_rates(
         items: vec![],
     };
 
-    let base_rate = get_orai_exchange_rate(deps, &base_denom)?;
+    let base_rate = get_orai_exchange_rate(deps, &env, &base_denom)?;
 
     for quote_denom in quote_denoms {
-        let quote_rate = get_orai_exchange_rate(deps, &quote_denom)?;
+        let quote_rate = get_orai_exchange_rate(deps, &env, &quote_denom)?;
 
         res.items.push(ExchangeRateItem {
             quote_denom,
@@ -273,6 +840,136 @@ pub fn query_exchange_rates(
     Ok(res)
 }
 
+pub fn query_exchange_rate_info(
+    deps: Deps,
+    env: Env,
+    denom: String,
+) -> StdResult<ExchangeRateInfoResponse> {
+    if denom == ORAI_DENOM {
+        return Ok(ExchangeRateInfoResponse {
+            denom,
+            exchange_rate: Decimal::one(),
+            last_updated: None,
+            stale: false,
+        });
+    }
+
+    let exchange_rate = EXCHANGE_RATES.load(deps.storage, denom.as_bytes())?;
+    let last_updated = EXCHANGE_RATE_UPDATED.may_load(deps.storage, denom.as_bytes())?;
+    let stale = is_stale(deps, &env, last_updated)?;
+
+    Ok(ExchangeRateInfoResponse {
+        denom,
+        exchange_rate,
+        last_updated,
+        stale,
+    })
+}
+
+pub fn query_exchange_rate_at(
+    deps: Deps,
+    quote_denom: String,
+    time: u64,
+) -> StdResult<ExchangeRateAtResponse> {
+    if quote_denom == ORAI_DENOM {
+        return Ok(ExchangeRateAtResponse {
+            denom: quote_denom,
+            time,
+            exchange_rate: Decimal::one(),
+            updated_at: 0,
+        });
+    }
+
+    let (updated_at, exchange_rate) = EXCHANGE_RATE_HISTORY
+        .prefix(quote_denom.as_bytes())
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(time)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .ok_or_else(|| {
+            StdError::generic_err(format!("No exchange rate of {} at {}", quote_denom, time))
+        })?;
+
+    Ok(ExchangeRateAtResponse {
+        denom: quote_denom,
+        time,
+        exchange_rate,
+        updated_at,
+    })
+}
+
+pub fn query_exchange_rate_history(
+    deps: Deps,
+    quote_denom: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ExchangeRateHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let history = EXCHANGE_RATE_HISTORY
+        .prefix(quote_denom.as_bytes())
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (time, exchange_rate) = item?;
+            Ok(ExchangeRateHistoryItem {
+                time,
+                exchange_rate,
+            })
+        })
+        .collect::<StdResult<Vec<ExchangeRateHistoryItem>>>()?;
+
+    Ok(ExchangeRateHistoryResponse {
+        denom: quote_denom,
+        history,
+    })
+}
+
+pub fn query_history_config(deps: Deps) -> StdResult<HistoryConfig> {
+    Ok(HISTORY_CONFIG.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_round(deps: Deps, denom: String) -> StdResult<RoundResponse> {
+    let round = ROUNDS
+        .may_load(deps.storage, denom.as_bytes())?
+        .unwrap_or(Round {
+            id: 0,
+            start_time: 0,
+            submissions: vec![],
+        });
+
+    Ok(RoundResponse {
+        denom,
+        round_id: round.id,
+        start_time: round.start_time,
+        submissions: round
+            .submissions
+            .iter()
+            .map(|(feeder, price)| Ok((deps.api.addr_humanize(feeder)?, *price)))
+            .collect::<StdResult<Vec<(Addr, Decimal)>>>()?,
+    })
+}
+
+pub fn query_feeders(deps: Deps) -> StdResult<FeedersResponse> {
+    let feeders = FEEDERS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|key| deps.api.addr_humanize(&key?.into()))
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    Ok(FeedersResponse {
+        feeders,
+        config: FEEDER_CONFIG.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
 pub fn query_contract_info(deps: Deps) -> StdResult<ContractInfoResponse> {
     let info = CONTRACT_INFO.load(deps.storage)?;
     Ok(ContractInfoResponse {
@@ -290,15 +987,74 @@ pub fn query_contract_balance(deps: Deps, env: Env, denom: String) -> StdResult<
     deps.querier.query_balance(env.contract.address, &denom)
 }
 
-fn get_orai_exchange_rate(deps: Deps, denom: &str) -> StdResult<Decimal> {
+fn get_orai_exchange_rate(deps: Deps, env: &Env, denom: &str) -> StdResult<Decimal> {
+    Ok(load_orai_exchange_rate(deps, env, denom, 0)?.0)
+}
+
+/// fed rate of the denom, assets with a price source are priced from their pair otherwise.
+/// Also returns the assets crossed from the denom to Orai
+pub fn load_orai_exchange_rate(
+    deps: Deps,
+    env: &Env,
+    denom: &str,
+    hops: u8,
+) -> StdResult<(Decimal, Vec<AssetInfo>)> {
+    let orai_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
     if denom == ORAI_DENOM {
-        return Ok(Decimal::one());
+        return Ok((Decimal::one(), vec![]));
+    }
+
+    let exchange_rate = match EXCHANGE_RATES.may_load(deps.storage, denom.as_bytes())? {
+        Some(exchange_rate) => exchange_rate,
+        None if PRICE_SOURCES.has(deps.storage, denom.as_bytes()) => {
+            return compute_pool_rate(deps, env, denom, hops);
+        }
+        None => EXCHANGE_RATES.load(deps.storage, denom.as_bytes())?,
+    };
+    let last_updated = EXCHANGE_RATE_UPDATED.may_load(deps.storage, denom.as_bytes())?;
+    if is_stale(deps, env, last_updated)? {
+        return Err(StdError::generic_err(format!(
+            "Exchange rate of {} is stale",
+            denom
+        )));
+    }
+
+    Ok((exchange_rate, vec![orai_info]))
+}
+
+/// rates without update time are stale once a max age is set
+fn is_stale(deps: Deps, env: &Env, last_updated: Option<u64>) -> StdResult<bool> {
+    let config = FEEDER_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+    if config.max_age == 0 {
+        return Ok(false);
     }
 
-    EXCHANGE_RATES.load(deps.storage, denom.as_bytes())
+    Ok(match last_updated {
+        Some(last_updated) => env.block.time.seconds() > last_updated + config.max_age,
+        None => true,
+    })
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // seed the history with the current rates
+    let denom_keys = EXCHANGE_RATES
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Vec<u8>>>>()?;
+    for denom_key in denom_keys {
+        if denom_key == ORAI_DENOM.as_bytes()
+            || EXCHANGE_RATE_HISTORY_SIZE.has(deps.storage, &denom_key)
+        {
+            continue;
+        }
+        let exchange_rate = EXCHANGE_RATES.load(deps.storage, &denom_key)?;
+        let updated = EXCHANGE_RATE_UPDATED
+            .may_load(deps.storage, &denom_key)?
+            .unwrap_or(env.block.time.seconds());
+        save_exchange_rate(deps.storage, &denom_key, exchange_rate, updated)?;
+    }
+
     Ok(Response::default())
 }
@@ -1,4 +1,5 @@
 pub mod contract;
+pub mod pool_price;
 pub mod state;
 
 #[cfg(test)]
@@ -0,0 +1,286 @@
+use cosmwasm_std::{
+    Addr, Decimal, Deps, DepsMut, Env, MessageInfo, Order, QuerierWrapper, Response, StdError,
+    StdResult, Uint128,
+};
+use cw_storage_plus::Bound;
+
+use oraiswap::asset::AssetInfo;
+use oraiswap::error::ContractError;
+use oraiswap::oracle::{PoolPriceResponse, PriceSource};
+use oraiswap::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+
+use crate::contract::load_orai_exchange_rate;
+use crate::state::{
+    PriceObservation, CONTRACT_INFO, EXCHANGE_RATES, FEEDERS, PRICE_OBSERVATIONS, PRICE_SOURCES,
+};
+
+// pairs chained to price an asset
+const MAX_PRICE_HOPS: u8 = 3;
+// shorter windows are cheap to move with a few manipulated observations
+const MIN_TWAP_WINDOW: u64 = 300;
+
+pub fn execute_update_price_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    source: Option<PriceSource>,
+) -> Result<Response, ContractError> {
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+
+    // check authorized
+    if contract_info.admin.ne(&sender_addr) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let denom = asset_info.to_string();
+    match source {
+        Some(source) => {
+            if source.twap_window < MIN_TWAP_WINDOW {
+                return Err(StdError::generic_err(format!(
+                    "Twap window must be at least {} seconds",
+                    MIN_TWAP_WINDOW
+                ))
+                .into());
+            }
+            // the pair must trade the asset
+            query_pair_reserves(&deps.querier, &source.pair_addr, &denom)?;
+            PRICE_SOURCES.save(deps.storage, denom.as_bytes(), &source)?;
+        }
+        None => PRICE_SOURCES.remove(deps.storage, denom.as_bytes()),
+    }
+
+    // observations of the previous source do not apply
+    let times = PRICE_OBSERVATIONS
+        .prefix(denom.as_bytes())
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+    for time in times {
+        PRICE_OBSERVATIONS.remove(deps.storage, (denom.as_bytes(), time));
+    }
+
+    Ok(Response::new().add_attributes([("action", "update_price_source"), ("asset_info", &denom)]))
+}
+
+pub fn execute_observe_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_infos: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let sender_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if FEEDERS
+        .may_load(deps.storage, sender_addr.as_slice())?
+        .is_none()
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let now = env.block.time.seconds();
+    for asset_info in asset_infos {
+        let denom = asset_info.to_string();
+        let denom_key = denom.as_bytes();
+        let source = read_price_source(deps.as_ref(), &denom)?;
+        // once per block, the first observation stands
+        if PRICE_OBSERVATIONS.has(deps.storage, (denom_key, now)) {
+            continue;
+        }
+        let (reserve, other_reserve, _) =
+            query_pair_reserves(&deps.querier, &source.pair_addr, &denom)?;
+        let price = Decimal::from_ratio(reserve, other_reserve);
+
+        let last_observation = PRICE_OBSERVATIONS
+            .prefix(denom_key)
+            .range(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?;
+        // the new price only counts from now on, the elapsed time is accumulated at the previous one
+        let cumulative = match last_observation {
+            Some((time, observation)) => cumulative_at(&observation, time, now)?,
+            None => Decimal::zero(),
+        };
+        PRICE_OBSERVATIONS.save(
+            deps.storage,
+            (denom_key, now),
+            &PriceObservation { price, cumulative },
+        )?;
+
+        // keep the latest observation before the window to start the average from
+        let expired = PRICE_OBSERVATIONS
+            .prefix(denom_key)
+            .keys(
+                deps.storage,
+                None,
+                Some(Bound::exclusive(now.saturating_sub(source.twap_window))),
+                Order::Descending,
+            )
+            .skip(1)
+            .collect::<StdResult<Vec<u64>>>()?;
+        for time in expired {
+            PRICE_OBSERVATIONS.remove(deps.storage, (denom_key, time));
+        }
+    }
+
+    Ok(Response::new().add_attribute("action", "observe_prices"))
+}
+
+pub fn query_pool_price(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+) -> StdResult<PoolPriceResponse> {
+    let denom = asset_info.to_string();
+    let source = read_price_source(deps, &denom)?;
+    let (pool_rate, _) = compute_pool_rate(deps, &env, &denom, 0)?;
+    let exchange_rate = EXCHANGE_RATES.may_load(deps.storage, denom.as_bytes())?;
+
+    Ok(PoolPriceResponse {
+        asset_info,
+        source,
+        pool_rate,
+        exchange_rate,
+        deviation: exchange_rate.map(|rate| rate_deviation(rate, pool_rate)),
+    })
+}
+
+/// rejects a fed rate too far from the pool price of the asset
+pub fn check_pool_deviation(
+    deps: Deps,
+    env: &Env,
+    denom: &str,
+    exchange_rate: Decimal,
+) -> StdResult<()> {
+    let max_deviation = match PRICE_SOURCES.may_load(deps.storage, denom.as_bytes())? {
+        Some(PriceSource {
+            max_deviation: Some(max_deviation),
+            ..
+        }) => max_deviation,
+        _ => return Ok(()),
+    };
+
+    let (pool_rate, _) = compute_pool_rate(deps, env, denom, 0)?;
+    if rate_deviation(exchange_rate, pool_rate) > max_deviation {
+        return Err(StdError::generic_err(format!(
+            "Exchange rate of {} deviates from the pool price",
+            denom
+        )));
+    }
+
+    Ok(())
+}
+
+/// rate of the asset to Orai, its price in the other asset of the pair times the rate of that one
+pub fn compute_pool_rate(
+    deps: Deps,
+    env: &Env,
+    denom: &str,
+    hops: u8,
+) -> StdResult<(Decimal, Vec<AssetInfo>)> {
+    if hops >= MAX_PRICE_HOPS {
+        return Err(StdError::generic_err(format!(
+            "Too many pairs to price {}",
+            denom
+        )));
+    }
+
+    let source = read_price_source(deps, denom)?;
+    let (_, _, other_info) = query_pair_reserves(&deps.querier, &source.pair_addr, denom)?;
+    let pair_rate = compute_twap(deps, env, denom, source.twap_window)?;
+    let (other_rate, other_path) =
+        load_orai_exchange_rate(deps, env, &other_info.to_string(), hops + 1)?;
+    let exchange_rate = pair_rate
+        .checked_mul(other_rate)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+
+    Ok((exchange_rate, [vec![other_info], other_path].concat()))
+}
+
+/// average price over the window ending at the last observation, whose own price is not counted yet
+fn compute_twap(deps: Deps, env: &Env, denom: &str, window: u64) -> StdResult<Decimal> {
+    let now = env.block.time.seconds();
+    let observations = PRICE_OBSERVATIONS.prefix(denom.as_bytes());
+    let not_enough_observations =
+        || StdError::generic_err(format!("Not enough price observations of {}", denom));
+
+    let (last_time, last_observation) = observations
+        .range(deps.storage, None, None, Order::Descending)
+        .next()
+        .transpose()?
+        .ok_or_else(not_enough_observations)?;
+    if now - last_time > window {
+        return Err(StdError::generic_err(format!(
+            "Price observations of {} are stale",
+            denom
+        )));
+    }
+    let start = last_time
+        .checked_sub(window)
+        .ok_or_else(not_enough_observations)?;
+    let (start_time, start_observation) = observations
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(start)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .ok_or_else(not_enough_observations)?;
+
+    let cumulative =
+        last_observation.cumulative - cumulative_at(&start_observation, start_time, start)?;
+
+    Ok(cumulative / Decimal::from_ratio(window, 1u64))
+}
+
+fn cumulative_at(observation: &PriceObservation, time: u64, at: u64) -> StdResult<Decimal> {
+    observation
+        .price
+        .checked_mul(Decimal::from_ratio(at - time, 1u64))
+        .and_then(|sum| sum.checked_add(observation.cumulative))
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+fn read_price_source(deps: Deps, denom: &str) -> StdResult<PriceSource> {
+    PRICE_SOURCES
+        .may_load(deps.storage, denom.as_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("No price source for {}", denom)))
+}
+
+/// reserves of the asset and the other asset of the pair
+fn query_pair_reserves(
+    querier: &QuerierWrapper,
+    pair_addr: &Addr,
+    denom: &str,
+) -> StdResult<(Uint128, Uint128, AssetInfo)> {
+    let pool: PoolResponse = querier.query_wasm_smart(pair_addr, &PairQueryMsg::Pool {})?;
+    let [first, second] = pool.assets;
+    let (asset, other) = if first.info.to_string() == denom {
+        (first, second)
+    } else if second.info.to_string() == denom {
+        (second, first)
+    } else {
+        return Err(StdError::generic_err(format!(
+            "Pair {} does not trade {}",
+            pair_addr, denom
+        )));
+    };
+
+    if asset.amount.is_zero() || other.amount.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Pair {} has no liquidity",
+            pair_addr
+        )));
+    }
+
+    Ok((asset.amount, other.amount, other.info))
+}
+
+fn rate_deviation(exchange_rate: Decimal, pool_rate: Decimal) -> Decimal {
+    let distance = if exchange_rate > pool_rate {
+        exchange_rate - pool_rate
+    } else {
+        pool_rate - exchange_rate
+    };
+    distance / pool_rate
+}
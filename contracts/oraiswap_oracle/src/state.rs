@@ -1,12 +1,45 @@
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{CanonicalAddr, Decimal, Uint128};
 use cw_storage_plus::{Item, Map};
-use oraiswap::oracle::ContractInfo;
+use oraiswap::oracle::{ContractInfo, FeederConfig, HistoryConfig, PriceSource};
 
 // put the length bytes at the first for compatibility with legacy singleton store
 pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("\u{0}\u{13}contract_info");
 pub const TAX_RATE: Item<Decimal> = Item::new("\u{0}\u{8}tax_rate");
 
 pub const TAX_CAP: Map<&[u8], Uint128> = Map::new("tax_cap");
+/// Tax rate of denom, the global tax rate applies to the others
+pub const DENOM_TAX_RATE: Map<&[u8], Decimal> = Map::new("denom_tax_rate");
+pub const TAX_EXEMPT: Map<&[u8], bool> = Map::new("tax_exempt");
 /// Exchange rate of denom to Orai
 /// (QUOTE_DENOM / ORAI)  / (BASE_DENOM / ORAI) = QUOTE_DENOM / BASE_DENOM
 pub const EXCHANGE_RATES: Map<&[u8], Decimal> = Map::new("exchange_rates");
+/// Last time the exchange rate of denom was updated
+pub const EXCHANGE_RATE_UPDATED: Map<&[u8], u64> = Map::new("exchange_rate_updated");
+
+/// Exchange rate of denom to Orai by update time
+pub const EXCHANGE_RATE_HISTORY: Map<(&[u8], u64), Decimal> = Map::new("exchange_rate_history");
+pub const EXCHANGE_RATE_HISTORY_SIZE: Map<&[u8], u32> = Map::new("exchange_rate_history_size");
+pub const HISTORY_CONFIG: Item<HistoryConfig> = Item::new("history_config");
+
+/// Pair pricing the asset, keyed by denom or token address
+pub const PRICE_SOURCES: Map<&[u8], PriceSource> = Map::new("price_sources");
+pub const PRICE_OBSERVATIONS: Map<(&[u8], u64), PriceObservation> = Map::new("price_observations");
+
+pub const FEEDER_CONFIG: Item<FeederConfig> = Item::new("feeder_config");
+pub const FEEDERS: Map<&[u8], bool> = Map::new("feeders");
+pub const ROUNDS: Map<&[u8], Round> = Map::new("rounds");
+
+#[cw_serde]
+pub struct Round {
+    pub id: u64,
+    pub start_time: u64,
+    pub submissions: Vec<(CanonicalAddr, Decimal)>,
+}
+
+/// pool price at the observation time, with the time weighted sum of the prices until then
+#[cw_serde]
+pub struct PriceObservation {
+    pub price: Decimal,
+    pub cumulative: Decimal,
+}
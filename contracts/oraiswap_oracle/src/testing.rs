@@ -1,9 +1,26 @@
-use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, Uint128, WasmMsg};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR,
+};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, BankMsg, Coin, ContractResult, CosmosMsg, Decimal, Env,
+    OwnedDeps, StdError, SystemResult, Uint128, WasmMsg, WasmQuery,
+};
 
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
-use oraiswap::oracle::{ExecuteMsg, OracleContract};
+use oraiswap::error::ContractError;
+use oraiswap::oracle::{
+    ExchangeRateFeed, ExchangeRateHistoryItem, ExecuteMsg, InstantiateMsg, OracleContract,
+    OracleTreasuryQuery, PriceSource, QueryMsg, TaxCapResponse, TaxRateResponse,
+};
+use oraiswap::pair::PoolResponse;
+
+use crate::contract::{
+    execute, instantiate, query_contract_info, query_cross_rate, query_exchange_rate,
+    query_exchange_rate_at, query_exchange_rate_history, query_exchange_rate_info, query_feeders,
+    query_round, query_tax_rate,
+};
+use crate::pool_price::query_pool_price;
 use oraiswap::testing::{MockApp, APP_OWNER};
 
 fn setup_contract() -> MockApp {
@@ -134,20 +151,20 @@ fn test_asset() {
 
     assert_eq!(
         token_asset
-            .compute_tax(&orai_oracle, &app.as_querier())
+            .compute_tax(&orai_oracle, &app.as_querier(), None)
             .unwrap(),
         Uint128::zero()
     );
     assert_eq!(
         native_token_asset
-            .compute_tax(&orai_oracle, &app.as_querier())
+            .compute_tax(&orai_oracle, &app.as_querier(), None)
             .unwrap(),
         Uint128::from(1220u128)
     );
 
     assert_eq!(
         native_token_asset
-            .deduct_tax(&orai_oracle, &app.as_querier())
+            .deduct_tax(&orai_oracle, &app.as_querier(), None)
             .unwrap(),
         Coin {
             denom: "uusd".to_string(),
@@ -191,3 +208,752 @@ fn test_asset() {
         })
     );
 }
+
+#[test]
+fn tax_rate_for_recipient() {
+    let mut app = setup_contract();
+    app.set_tax(
+        Decimal::percent(1),
+        &[
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+            (&"uatom".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+
+    for msg in [
+        ExecuteMsg::UpdateDenomTaxRate {
+            denom: "uatom".to_string(),
+            rate: Some(Decimal::permille(5)),
+        },
+        ExecuteMsg::AddTaxExempt {
+            address: Addr::unchecked("pair"),
+        },
+    ] {
+        app.execute(
+            Addr::unchecked("addr0000"),
+            app.oracle_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+        app.execute(
+            Addr::unchecked(APP_OWNER),
+            app.oracle_addr.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    let orai_oracle = OracleContract(app.oracle_addr.clone());
+    let asset = |denom: &str| Asset {
+        amount: Uint128::from(123123u128),
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+    };
+
+    // the global rate applies to denoms without their own rate
+    assert_eq!(
+        asset("uusd")
+            .compute_tax(&orai_oracle, &app.as_querier(), None)
+            .unwrap(),
+        Uint128::from(1220u128)
+    );
+    assert_eq!(
+        asset("uatom")
+            .compute_tax(&orai_oracle, &app.as_querier(), None)
+            .unwrap(),
+        Uint128::from(613u128)
+    );
+
+    // exempt recipients are not taxed
+    let res = orai_oracle
+        .query_tax_rate_for(&app.as_querier(), "uatom", Some(Addr::unchecked("pair")))
+        .unwrap();
+    assert!(res.exempt);
+    assert_eq!(res.rate, Decimal::zero());
+    assert_eq!(
+        asset("uatom")
+            .deduct_tax(
+                &orai_oracle,
+                &app.as_querier(),
+                Some(&Addr::unchecked("pair"))
+            )
+            .unwrap(),
+        Coin {
+            denom: "uatom".to_string(),
+            amount: Uint128::from(123123u128),
+        }
+    );
+
+    // without its own rate the denom falls back to the global rate
+    app.execute(
+        Addr::unchecked(APP_OWNER),
+        app.oracle_addr.clone(),
+        &ExecuteMsg::UpdateDenomTaxRate {
+            denom: "uatom".to_string(),
+            rate: None,
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        asset("uatom")
+            .compute_tax(
+                &orai_oracle,
+                &app.as_querier(),
+                Some(&Addr::unchecked("addr0000"))
+            )
+            .unwrap(),
+        Uint128::from(1220u128)
+    );
+}
+
+#[test]
+fn tax_without_tax_rate_for() {
+    // an oracle not migrated yet only answers the global rate and cap
+    let mut deps = mock_dependencies();
+    deps.querier.update_wasm(|query| match query {
+        WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+            QueryMsg::Treasury(OracleTreasuryQuery::TaxRate {}) => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&TaxRateResponse {
+                        rate: Decimal::percent(1),
+                    })
+                    .unwrap(),
+                ))
+            }
+            QueryMsg::Treasury(OracleTreasuryQuery::TaxCap { .. }) => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&TaxCapResponse {
+                        cap: Uint128::from(1000000u128),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Ok(ContractResult::Err("unknown query".to_string())),
+        },
+        _ => panic!("unexpected query"),
+    });
+
+    let asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uatom".to_string(),
+        },
+        amount: Uint128::from(10100u128),
+    };
+    assert_eq!(
+        asset
+            .compute_tax(
+                &OracleContract(Addr::unchecked("oracle")),
+                &deps.as_ref().querier,
+                Some(&Addr::unchecked("addr0000"))
+            )
+            .unwrap(),
+        Uint128::from(101u128)
+    );
+}
+
+#[test]
+fn feed_exchange_rates() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        min_rate: None,
+        max_rate: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    for feeder in ["feeder1", "feeder2", "feeder3"] {
+        let msg = ExecuteMsg::AddFeeder {
+            feeder: Addr::unchecked(feeder),
+        };
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr", &[]),
+            msg.clone(),
+        );
+        assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    }
+    let msg = ExecuteMsg::UpdateFeederConfig {
+        min_feeders: Some(3),
+        round_duration: None,
+        max_deviation: None,
+        max_age: Some(100),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    let feeders = query_feeders(deps.as_ref()).unwrap();
+    assert_eq!(feeders.feeders.len(), 3);
+    assert_eq!(feeders.config.min_feeders, 3);
+    assert_eq!(feeders.config.max_age, 100);
+
+    let feed = |exchange_rate: Decimal| ExecuteMsg::FeedExchangeRates {
+        exchange_rates: vec![ExchangeRateFeed {
+            denom: "usdt".to_string(),
+            exchange_rate,
+        }],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        feed(Decimal::percent(10)),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    // the rate is set once the round has enough submissions, the outlier is dropped
+    for (feeder, exchange_rate) in [
+        ("feeder1", Decimal::percent(20)),
+        ("feeder1", Decimal::percent(10)),
+        ("feeder2", Decimal::percent(11)),
+    ] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(feeder, &[]),
+            feed(exchange_rate),
+        )
+        .unwrap();
+    }
+    query_exchange_rate_info(deps.as_ref(), env.clone(), "usdt".to_string()).unwrap_err();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder3", &[]),
+        feed(Decimal::percent(50)),
+    )
+    .unwrap();
+
+    let round = query_round(deps.as_ref(), "usdt".to_string()).unwrap();
+    assert_eq!(round.round_id, 1);
+    assert_eq!(round.submissions.len(), 3);
+    let res = query_exchange_rate(
+        deps.as_ref(),
+        env.clone(),
+        ORAI_DENOM.to_string(),
+        "usdt".to_string(),
+    )
+    .unwrap();
+    assert_eq!(res.item.exchange_rate, Decimal::permille(105));
+
+    // rate is stale after max age
+    env.block.time = env.block.time.plus_seconds(101);
+    let res = query_exchange_rate_info(deps.as_ref(), env.clone(), "usdt".to_string()).unwrap();
+    assert!(res.stale);
+    query_exchange_rate(
+        deps.as_ref(),
+        env.clone(),
+        ORAI_DENOM.to_string(),
+        "usdt".to_string(),
+    )
+    .unwrap_err();
+
+    // a new round starts after the round duration
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("feeder1", &[]),
+        feed(Decimal::percent(12)),
+    )
+    .unwrap();
+    let round = query_round(deps.as_ref(), "usdt".to_string()).unwrap();
+    assert_eq!(round.round_id, 2);
+    assert_eq!(round.submissions.len(), 1);
+}
+
+#[test]
+fn exchange_rate_history() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        min_rate: None,
+        max_rate: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateHistoryConfig {
+        max_entries: Some(3),
+        retention: None,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    // the first entry is pruned by the max entries
+    let start_time = env.block.time.seconds();
+    for rate in 1..=4u64 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("admin", &[]),
+            ExecuteMsg::UpdateExchangeRate {
+                denom: "usdt".to_string(),
+                exchange_rate: Decimal::percent(rate),
+            },
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(10);
+    }
+
+    let res = query_exchange_rate_at(deps.as_ref(), "usdt".to_string(), start_time + 15).unwrap();
+    assert_eq!(res.exchange_rate, Decimal::percent(2));
+    assert_eq!(res.updated_at, start_time + 10);
+    query_exchange_rate_at(deps.as_ref(), "usdt".to_string(), start_time + 5).unwrap_err();
+
+    let res = query_exchange_rate_history(
+        deps.as_ref(),
+        "usdt".to_string(),
+        Some(start_time + 10),
+        Some(1),
+    )
+    .unwrap();
+    assert_eq!(
+        res.history,
+        vec![ExchangeRateHistoryItem {
+            time: start_time + 20,
+            exchange_rate: Decimal::percent(3),
+        }]
+    );
+
+    // entries older than the retention are pruned on the next update
+    let msg = ExecuteMsg::UpdateHistoryConfig {
+        max_entries: None,
+        retention: Some(15),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdateExchangeRate {
+            denom: "usdt".to_string(),
+            exchange_rate: Decimal::percent(5),
+        },
+    )
+    .unwrap();
+
+    let res = query_exchange_rate_history(deps.as_ref(), "usdt".to_string(), None, None).unwrap();
+    assert_eq!(
+        res.history,
+        vec![
+            ExchangeRateHistoryItem {
+                time: start_time + 30,
+                exchange_rate: Decimal::percent(4),
+            },
+            ExchangeRateHistoryItem {
+                time: start_time + 40,
+                exchange_rate: Decimal::percent(5),
+            }
+        ]
+    );
+}
+
+fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+// pair1 trades token against orai, pair2 trades long against token
+fn mock_pairs(
+    deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    long_reserve: u128,
+    token_reserve: u128,
+) {
+    deps.querier.update_wasm(move |query| match query {
+        WasmQuery::Smart { contract_addr, .. } => {
+            let reserves = match contract_addr.as_str() {
+                "pair1" => [("token", 1000u128), (ORAI_DENOM, 100u128)],
+                _ => [("long", long_reserve), ("token", token_reserve)],
+            };
+            let assets = reserves.map(|(denom, amount)| Asset {
+                info: native(denom),
+                amount: Uint128::from(amount),
+            });
+            let res = PoolResponse {
+                assets,
+                total_share: Uint128::zero(),
+            };
+            SystemResult::Ok(ContractResult::Ok(to_binary(&res).unwrap()))
+        }
+        _ => panic!("unexpected query"),
+    });
+}
+
+#[test]
+fn pool_price_source() {
+    let mut deps = mock_dependencies();
+    mock_pairs(&mut deps, 500, 1000);
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        min_rate: None,
+        max_rate: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    let source = |pair_addr: &str, twap_window: u64| PriceSource {
+        pair_addr: Addr::unchecked(pair_addr),
+        twap_window,
+        max_deviation: Some(Decimal::percent(10)),
+    };
+    let msg = ExecuteMsg::UpdatePriceSource {
+        asset_info: native("token"),
+        source: Some(source("pair1", 300)),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdatePriceSource {
+            asset_info: native("long"),
+            source: Some(source("pair2", 0)),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err(
+            "Twap window must be at least 300 seconds"
+        ))
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdatePriceSource {
+            asset_info: native("uatom"),
+            source: Some(source("pair1", 300)),
+        },
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err("Pair pair1 does not trade uatom"))
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::UpdatePriceSource {
+            asset_info: native("long"),
+            source: Some(source("pair2", 300)),
+        },
+    )
+    .unwrap();
+
+    // only feeders observe the pool prices
+    let observe = ExecuteMsg::ObservePrices {
+        asset_infos: vec![native("token"), native("long")],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::AddFeeder {
+            feeder: Addr::unchecked("keeper"),
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe.clone(),
+    )
+    .unwrap();
+    env.block.time = env.block.time.plus_seconds(300);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe.clone(),
+    )
+    .unwrap();
+
+    // assets without fed rate are priced from the pool twap, long is chained through token
+    let rate = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, env: &Env, denom: &str| {
+        query_exchange_rate(
+            deps.as_ref(),
+            env.clone(),
+            ORAI_DENOM.to_string(),
+            denom.to_string(),
+        )
+        .map(|res| res.item.exchange_rate)
+    };
+    assert_eq!(rate(&deps, &env, "token").unwrap(), Decimal::percent(1000));
+    assert_eq!(rate(&deps, &env, "long").unwrap(), Decimal::percent(500));
+
+    // fed rates are cross-checked against the pool
+    let update_rate = |exchange_rate: Decimal| ExecuteMsg::UpdateExchangeRate {
+        denom: "token".to_string(),
+        exchange_rate,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        update_rate(Decimal::percent(1200)),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err(
+            "Exchange rate of token deviates from the pool price"
+        ))
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        update_rate(Decimal::percent(1050)),
+    )
+    .unwrap();
+    let res = query_pool_price(deps.as_ref(), env.clone(), native("token")).unwrap();
+    assert_eq!(res.pool_rate, Decimal::percent(1000));
+    assert_eq!(res.exchange_rate, Some(Decimal::percent(1050)));
+    assert_eq!(res.deviation, Some(Decimal::percent(5)));
+    assert_eq!(rate(&deps, &env, "token").unwrap(), Decimal::percent(1050));
+
+    // a new price counts from its observation on, over the window ending at the last one
+    let observe = ExecuteMsg::ObservePrices {
+        asset_infos: vec![native("long")],
+    };
+    env.block.time = env.block.time.plus_seconds(150);
+    mock_pairs(&mut deps, 1000, 1000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe.clone(),
+    )
+    .unwrap();
+    assert_eq!(rate(&deps, &env, "long").unwrap(), Decimal::percent(525));
+    env.block.time = env.block.time.plus_seconds(150);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        rate(&deps, &env, "long").unwrap(),
+        Decimal::from_ratio(7875u128, 1000u128)
+    );
+
+    // the pool moved within the block is not observed again
+    mock_pairs(&mut deps, 5000, 1000);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        observe,
+    )
+    .unwrap();
+    assert_eq!(
+        rate(&deps, &env, "long").unwrap(),
+        Decimal::from_ratio(7875u128, 1000u128)
+    );
+
+    // without fresh observations there is no price
+    env.block.time = env.block.time.plus_seconds(301);
+    assert_eq!(
+        rate(&deps, &env, "long").unwrap_err(),
+        StdError::generic_err("Price observations of long are stale")
+    );
+}
+
+#[test]
+fn cross_rate_path() {
+    let mut deps = mock_dependencies();
+    mock_pairs(&mut deps, 500, 1000);
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        min_rate: None,
+        max_rate: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    // long is priced from its pair with token, token and usdt are fed
+    let msgs = vec![
+        ExecuteMsg::UpdatePriceSource {
+            asset_info: native("long"),
+            source: Some(PriceSource {
+                pair_addr: Addr::unchecked("pair2"),
+                twap_window: 300,
+                max_deviation: None,
+            }),
+        },
+        ExecuteMsg::UpdateExchangeRate {
+            denom: "token".to_string(),
+            exchange_rate: Decimal::percent(1000),
+        },
+        ExecuteMsg::UpdateExchangeRate {
+            denom: "usdt".to_string(),
+            exchange_rate: Decimal::percent(200),
+        },
+    ];
+    for msg in msgs {
+        execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    }
+
+    // constant reserves over the window
+    let msg = ExecuteMsg::AddFeeder {
+        feeder: Addr::unchecked("keeper"),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    let observe = ExecuteMsg::ObservePrices {
+        asset_infos: vec![native("long")],
+    };
+    for _ in 0..2 {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("keeper", &[]),
+            observe.clone(),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(300);
+    }
+
+    for (base, quote, exchange_rate, path) in [
+        (
+            "long",
+            "token",
+            Decimal::percent(200),
+            vec!["long", "token"],
+        ),
+        ("token", "long", Decimal::percent(50), vec!["token", "long"]),
+        (
+            "long",
+            "usdt",
+            Decimal::percent(40),
+            vec!["long", "token", ORAI_DENOM, "usdt"],
+        ),
+        (
+            ORAI_DENOM,
+            "usdt",
+            Decimal::percent(200),
+            vec![ORAI_DENOM, "usdt"],
+        ),
+        ("usdt", "usdt", Decimal::one(), vec!["usdt"]),
+    ] {
+        let res =
+            query_cross_rate(deps.as_ref(), env.clone(), native(base), native(quote)).unwrap();
+        assert_eq!(res.exchange_rate, exchange_rate);
+        assert_eq!(res.path, path.into_iter().map(native).collect::<Vec<_>>());
+    }
+
+    query_cross_rate(deps.as_ref(), env, native("usdt"), native("uatom")).unwrap_err();
+}
+
+#[test]
+fn admin_transfer_and_timelock() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        name: None,
+        version: None,
+        admin: None,
+        min_rate: None,
+        max_rate: None,
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+
+    // the proposed admin takes over only once it accepts
+    let msg = ExecuteMsg::UpdateAdmin {
+        admin: Addr::unchecked("admin2"),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("admin", &[]), msg).unwrap();
+    assert_eq!(
+        query_contract_info(deps.as_ref()).unwrap().admin,
+        Addr::unchecked("admin")
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        ExecuteMsg::AcceptAdmin {},
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err("unauthorized"))
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin2", &[]),
+        ExecuteMsg::AcceptAdmin {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_contract_info(deps.as_ref()).unwrap().admin,
+        Addr::unchecked("admin2")
+    );
+
+    // tax changes wait for the delay
+    let msg = ExecuteMsg::UpdateTimelock { delay: 100 };
+    execute(deps.as_mut(), env.clone(), mock_info("admin2", &[]), msg).unwrap();
+    let msg = ExecuteMsg::UpdateTaxRate {
+        rate: Decimal::permille(5),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+    execute(deps.as_mut(), env.clone(), mock_info("admin2", &[]), msg).unwrap();
+    query_tax_rate(deps.as_ref()).unwrap_err();
+
+    let msg = ExecuteMsg::ExecuteTimelocked { id: 1 };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("admin2", &[]),
+        msg.clone(),
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::Std(StdError::generic_err("Timelocked change 1 is not ready"))
+    );
+
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(deps.as_mut(), env, mock_info("admin2", &[]), msg).unwrap();
+    assert_eq!(
+        query_tax_rate(deps.as_ref()).unwrap().rate,
+        Decimal::permille(5)
+    );
+}
@@ -425,8 +425,8 @@ pub fn swap(
 
     let oracle_contract = OracleContract(deps.api.addr_humanize(&pair_info.oracle_addr)?);
 
-    let tax_amount = return_asset.compute_tax(&oracle_contract, &deps.querier)?;
     let receiver = to.unwrap_or_else(|| sender.clone());
+    let tax_amount = return_asset.compute_tax(&oracle_contract, &deps.querier, Some(&receiver))?;
 
     // update oracle_contract
     let mut messages: Vec<CosmosMsg> = vec![];
@@ -1,27 +1,43 @@
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response,
-    StdError, StdResult, Uint128, WasmMsg,
+    from_binary, to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cosmwasm_std::{QueryRequest, WasmQuery};
+use cw20::Cw20ReceiveMsg;
 
 use crate::state::{
-    read_config, read_last_distributed, store_config, store_last_distributed, Config,
+    count_pools, read_budget, read_budgets, read_config, read_gauge, read_last_distributed,
+    read_pool, read_pools, read_schedule, remove_gauge, remove_pool, remove_schedule, store_budget,
+    store_config, store_gauge, store_last_distributed, store_pool, store_schedule, Budget, Config,
+    GaugeInfo,
+};
+
+use oraiswap::gauge::{
+    ConfigResponse as GaugeConfigResponse, PoolWeightResponse, QueryMsg as GaugeQueryMsg,
 };
 
 use oraiswap::staking::QueryMsg as StakingQueryMsg;
-use oraiswap::staking::{ExecuteMsg as StakingExecuteMsg, RewardsPerSecResponse};
+use oraiswap::staking::{ExecuteMsg as StakingExecuteMsg, PoolInfoResponse, RewardsPerSecResponse};
 
 use oraiswap::rewarder::{
-    ConfigResponse, DistributionInfoResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    RewardAmountPerSecondResponse,
+    BudgetResponse, BudgetResponseItem, ConfigResponse, Cw20HookMsg, DistributionInfoResponse,
+    EmissionSchedule, ExecuteMsg, GaugeResponse, InstantiateMsg, MigrateMsg, PoolsResponse,
+    ProjectedEmissionResponse, QueryMsg, RewardAmountPerSecondResponse, ScheduleResponse,
 };
 
+use oraiswap::admin::{
+    accept_admin, cancel_change, propose_admin, query_pending_admin, query_timelock, queue_change,
+    store_timelock_delay, take_change,
+};
 use oraiswap::asset::{Asset, AssetInfo};
 
 // 600 seconds default
 const DEFAULT_DISTRIBUTION_INTERVAL: u64 = 600;
 
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -38,6 +54,7 @@ pub fn instantiate(
             distribution_interval: msg
                 .distribution_interval
                 .unwrap_or(DEFAULT_DISTRIBUTION_INTERVAL),
+            keeper_bounty: None,
         },
     )?;
 
@@ -46,19 +63,228 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    if is_timelocked(&msg) {
+        assert_owner(deps.as_ref(), &info.sender)?;
+        if let Some(change) = queue_change(deps.storage, &env, &msg)? {
+            return Ok(Response::new().add_attributes(change.attributes()));
+        }
+    }
+
     match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
         ExecuteMsg::UpdateConfig {
             owner,
             staking_contract,
             distribution_interval,
-        } => update_config(deps, info, owner, staking_contract, distribution_interval),
-
+            keeper_bounty,
+        } => update_config(
+            deps,
+            info,
+            owner,
+            staking_contract,
+            distribution_interval,
+            keeper_bounty,
+        ),
+        ExecuteMsg::AcceptAdmin {} => accept_owner(deps, info),
+        ExecuteMsg::UpdateTimelock { delay } => update_timelock(deps, info, delay),
+        ExecuteMsg::ExecuteTimelocked { id } => execute_timelocked(deps, env, info, id),
+        ExecuteMsg::CancelTimelocked { id } => cancel_timelocked(deps, info, id),
+        ExecuteMsg::RegisterPool { asset_info } => register_pool(deps, info, asset_info),
+        ExecuteMsg::DeregisterPool { asset_info } => deregister_pool(deps, info, asset_info),
+        ExecuteMsg::UpdateSchedule {
+            asset_info,
+            schedule,
+        } => update_schedule(deps, info, asset_info, schedule),
+
+        ExecuteMsg::UpdateGauge {
+            gauge_contract,
+            emission,
+        } => update_gauge(deps, info, gauge_contract, emission),
+        ExecuteMsg::Fund {} => fund(deps, info),
         ExecuteMsg::Distribute { asset_infos } => distribute(deps, env, asset_infos),
+        ExecuteMsg::DistributeAll { start_after, limit } => {
+            distribute_all(deps, env, info, start_after, limit)
+        }
+    }
+}
+
+pub fn accept_owner(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = accept_admin(deps.storage, deps.api, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "accept_admin"))
+}
+
+pub fn update_timelock(deps: DepsMut, info: MessageInfo, delay: u64) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+    store_timelock_delay(deps.storage, delay)?;
+
+    Ok(Response::new().add_attribute("action", "update_timelock"))
+}
+
+pub fn execute_timelocked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    match take_change(deps.storage, &env, id)? {
+        ExecuteMsg::UpdateSchedule {
+            asset_info,
+            schedule,
+        } => update_schedule(deps, info, asset_info, schedule),
+        ExecuteMsg::UpdateGauge {
+            gauge_contract,
+            emission,
+        } => update_gauge(deps, info, gauge_contract, emission),
+        ExecuteMsg::UpdateTimelock { delay } => update_timelock(deps, info, delay),
+        _ => Err(StdError::generic_err("Invalid timelocked change")),
+    }
+}
+
+pub fn cancel_timelocked(deps: DepsMut, info: MessageInfo, id: u64) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+    cancel_change(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attributes([("action", "cancel_change"), ("change_id", &id.to_string())]))
+}
+
+fn is_timelocked(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::UpdateSchedule { .. }
+            | ExecuteMsg::UpdateGauge { .. }
+            | ExecuteMsg::UpdateTimelock { .. }
+    )
+}
+
+fn assert_owner(deps: Deps, sender: &Addr) -> StdResult<()> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    Ok(())
+}
+
+pub fn update_gauge(
+    deps: DepsMut,
+    info: MessageInfo,
+    gauge_contract: Option<Addr>,
+    emission: Option<EmissionSchedule>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    match gauge_contract {
+        Some(gauge_contract) => {
+            let emission = emission
+                .ok_or_else(|| StdError::generic_err("Gauge emission schedule is required"))?;
+            emission.validate()?;
+            store_gauge(
+                deps.storage,
+                &GaugeInfo {
+                    contract: deps.api.addr_canonicalize(gauge_contract.as_str())?,
+                    emission,
+                },
+            )?;
+        }
+        None => remove_gauge(deps.storage),
+    }
+
+    Ok(Response::new().add_attribute("action", "update_gauge"))
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> StdResult<Response> {
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Fund {}) => {
+            let asset = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            };
+            let message = _fund(deps, &asset)?;
+            Ok(Response::new()
+                .add_message(message)
+                .add_attributes([("action", "fund"), ("asset", &asset.to_string())]))
+        }
+        Err(_) => Err(StdError::generic_err("invalid cw20 hook message")),
+    }
+}
+
+pub fn fund(mut deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let assets = info
+        .funds
+        .into_iter()
+        .filter(|coin| !coin.amount.is_zero())
+        .map(|coin| Asset {
+            info: AssetInfo::NativeToken { denom: coin.denom },
+            amount: coin.amount,
+        })
+        .collect::<Vec<Asset>>();
+    if assets.is_empty() {
+        return Err(StdError::generic_err("No funds sent"));
     }
+
+    let messages = assets
+        .iter()
+        .map(|asset| _fund(deps.branch(), asset))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+    let assets = assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([("action", "fund"), ("assets", &assets)]))
+}
+
+/// add the asset to its budget and forward it to the staking contract paying the rewards
+fn _fund(deps: DepsMut, asset: &Asset) -> StdResult<CosmosMsg> {
+    let config: Config = read_config(deps.storage)?;
+    let asset_key = asset.info.to_vec(deps.api)?;
+    let mut budget = read_budget(deps.storage, &asset_key)?.unwrap_or(Budget {
+        info: asset.info.to_raw(deps.api)?,
+        funded: Uint128::zero(),
+        distributed: Uint128::zero(),
+    });
+    budget.funded += asset.amount;
+    store_budget(deps.storage, &asset_key, &budget)?;
+
+    asset.into_msg(
+        None,
+        &deps.querier,
+        deps.api.addr_humanize(&config.staking_contract)?,
+    )
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    // seed the budgets so distribution does not stop until the next fund
+    for asset in msg.budgets {
+        let asset_key = asset.info.to_vec(deps.api)?;
+        let mut budget = read_budget(deps.storage, &asset_key)?.unwrap_or(Budget {
+            info: asset.info.to_raw(deps.api)?,
+            funded: Uint128::zero(),
+            distributed: Uint128::zero(),
+        });
+        budget.funded = budget.distributed + asset.amount;
+        store_budget(deps.storage, &asset_key, &budget)?;
+    }
+
     Ok(Response::default())
 }
 
@@ -68,14 +294,16 @@ pub fn update_config(
     owner: Option<Addr>,
     staking_contract: Option<Addr>,
     distribution_interval: Option<u64>,
+    keeper_bounty: Option<Asset>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
     if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
         return Err(StdError::generic_err("unauthorized"));
     }
 
+    // the new owner must accept first
     if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+        propose_admin(deps.storage, deps.api, &owner)?;
     }
 
     if let Some(staking_contract) = staking_contract {
@@ -86,15 +314,174 @@ pub fn update_config(
         config.distribution_interval = distribution_interval;
     }
 
+    if let Some(keeper_bounty) = keeper_bounty {
+        config.keeper_bounty = if keeper_bounty.amount.is_zero() {
+            None
+        } else {
+            Some(keeper_bounty.to_raw(deps.api)?)
+        };
+    }
+
     store_config(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+pub fn register_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    // the pool must exist in the staking contract
+    let _: PoolInfoResponse = deps.querier.query_wasm_smart(
+        deps.api.addr_humanize(&config.staking_contract)?,
+        &StakingQueryMsg::PoolInfo {
+            asset_info: asset_info.clone(),
+        },
+    )?;
+
+    store_pool(
+        deps.storage,
+        &asset_info.to_vec(deps.api)?,
+        &asset_info.to_raw(deps.api)?,
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "register_pool"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+pub fn deregister_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_key = asset_info.to_vec(deps.api)?;
+    if read_pool(deps.storage, &asset_key)?.is_none() {
+        return Err(StdError::generic_err("Pool is not registered"));
+    }
+    remove_pool(deps.storage, &asset_key);
+
+    Ok(Response::new().add_attributes([
+        ("action", "deregister_pool"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+pub fn update_schedule(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    schedule: Option<EmissionSchedule>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_key = asset_info.to_vec(deps.api)?;
+    match schedule {
+        Some(schedule) => {
+            schedule.validate()?;
+            store_schedule(deps.storage, &asset_key, &schedule)?;
+        }
+        None => remove_schedule(deps.storage, &asset_key),
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_schedule"),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
 /// Distribute
 /// Anyone can execute distribute operation to distribute
 pub fn distribute(deps: DepsMut, env: Env, asset_infos: Vec<AssetInfo>) -> StdResult<Response> {
     let config: Config = read_config(deps.storage)?;
+    let staking_contract = deps.api.addr_humanize(&config.staking_contract)?;
+    let rewards = _distribute_rewards(deps, &env, &config, asset_infos)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: staking_contract.to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward { rewards })?,
+            funds: vec![],
+        }))
+        .add_attribute("action", "distribute"))
+}
+
+/// DistributeAll
+/// Anyone can distribute a page of the registered pools and receive the keeper bounty
+/// in proportion to the registered pools it distributes
+pub fn distribute_all(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let staking_contract = deps.api.addr_humanize(&config.staking_contract)?;
+    let asset_infos = _read_pools(deps.as_ref(), start_after, limit)?;
+    let last_pool = asset_infos.last().map(|asset_info| asset_info.to_string());
+    let rewards = _distribute_rewards(deps.branch(), &env, &config, asset_infos)?;
+
+    let mut response = Response::new().add_attribute("action", "distribute_all");
+    if let Some(last_pool) = last_pool {
+        response = response.add_attribute("last_pool", last_pool);
+    }
+    let distributed_pools = rewards
+        .iter()
+        .filter(|reward| !reward.amount.is_zero())
+        .count();
+    if distributed_pools == 0 {
+        return Ok(response);
+    }
+
+    response = response.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: staking_contract.to_string(),
+        msg: to_binary(&StakingExecuteMsg::DepositReward { rewards })?,
+        funds: vec![],
+    }));
+
+    // the bounty is skipped rather than failing the distribution when the balance is short
+    // a full pass over the registered pools pays the whole bounty, whatever the page size
+    if let Some(keeper_bounty) = config.keeper_bounty {
+        let mut keeper_bounty = keeper_bounty.to_normal(deps.api)?;
+        keeper_bounty.amount = keeper_bounty.amount.multiply_ratio(
+            distributed_pools as u128,
+            count_pools(deps.storage).max(distributed_pools) as u128,
+        );
+        let balance = keeper_bounty
+            .info
+            .query_pool(&deps.querier, env.contract.address)?;
+        if !keeper_bounty.amount.is_zero() && balance >= keeper_bounty.amount {
+            response = response
+                .add_message(keeper_bounty.into_msg(None, &deps.querier, info.sender)?)
+                .add_attribute("keeper_bounty", keeper_bounty.to_string());
+        }
+    }
+
+    Ok(response)
+}
+
+fn _distribute_rewards(
+    deps: DepsMut,
+    env: &Env,
+    config: &Config,
+    asset_infos: Vec<AssetInfo>,
+) -> StdResult<Vec<Asset>> {
     let staking_contract = deps.api.addr_humanize(&config.staking_contract)?;
     let now = env.block.time.seconds();
     let mut rewards: Vec<Asset> = vec![];
@@ -110,42 +497,78 @@ pub fn distribute(deps: DepsMut, env: Env, asset_infos: Vec<AssetInfo>) -> StdRe
             continue;
         }
 
-        // store last distributed
-        store_last_distributed(deps.storage, &&asset_key, now)?;
-
-        // reward amount per second for a pool
-        let reward_amount =
-            _read_pool_reward_per_sec(&deps.querier, staking_contract.clone(), asset_info.clone())?;
+        // reward amount per second for a pool, its weights split the rewards between reward assets
+        let rewards_per_sec = _read_pool_rewards_per_sec(
+            &deps.querier,
+            staking_contract.clone(),
+            asset_info.clone(),
+        )?;
 
         // get total reward amount for a pool
-        let distribution_amount = Uint128::from(reward_amount.u128() * (last_time_elapsed as u128));
+        let distribution_amount =
+            _read_pool_emission(deps.as_ref(), &asset_info, last_distributed, now)?;
+
+        // pro-rate the distribution to the remaining budget
+        let budget_amount = _consume_budget(
+            deps.storage,
+            deps.api,
+            &rewards_per_sec,
+            distribution_amount,
+        )?;
+
+        // store last distributed, a clipped distribution only covers its share of the elapsed time
+        let distributed_until = if budget_amount < distribution_amount {
+            last_distributed
+                + Uint128::from(last_time_elapsed)
+                    .multiply_ratio(budget_amount, distribution_amount)
+                    .u128() as u64
+        } else {
+            now
+        };
+        store_last_distributed(deps.storage, &asset_key, distributed_until)?;
+
+        // nothing left to distribute, the pool waits for its budget to be funded
+        if budget_amount.is_zero() {
+            continue;
+        }
 
         // update rewards
         rewards.push(Asset {
             info: asset_info,
-            amount: distribution_amount,
+            amount: budget_amount,
         });
     }
 
-    Ok(Response::new()
-        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: staking_contract.to_string(),
-            msg: to_binary(&StakingExecuteMsg::DepositReward { rewards })?,
-            funds: vec![],
-        }))
-        .add_attribute("action", "distribute"))
+    Ok(rewards)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
+        QueryMsg::Timelock { start_after, limit } => {
+            to_binary(&query_timelock(deps.storage, start_after, limit)?)
+        }
         QueryMsg::DistributionInfo { asset_info } => {
             to_binary(&query_distribution_info(deps, asset_info)?)
         }
         QueryMsg::RewardAmountPerSec { asset_info } => {
-            to_binary(&query_reward_amount_per_sec(deps, asset_info)?)
+            to_binary(&query_reward_amount_per_sec(deps, env, asset_info)?)
         }
+        QueryMsg::Budget {} => to_binary(&query_budget(deps)?),
+        QueryMsg::Gauge {} => to_binary(&query_gauge(deps)?),
+        QueryMsg::Pools { start_after, limit } => to_binary(&PoolsResponse {
+            pools: _read_pools(deps, start_after, limit)?,
+        }),
+        QueryMsg::Schedule { asset_info } => to_binary(&query_schedule(deps, asset_info)?),
+        QueryMsg::ProjectedEmission {
+            asset_info,
+            start_time,
+            end_time,
+        } => to_binary(&query_projected_emission(
+            deps, asset_info, start_time, end_time,
+        )?),
     }
 }
 
@@ -155,6 +578,10 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?,
         staking_contract: deps.api.addr_humanize(&state.staking_contract)?,
         distribution_interval: state.distribution_interval,
+        keeper_bounty: state
+            .keeper_bounty
+            .map(|keeper_bounty| keeper_bounty.to_normal(deps.api))
+            .transpose()?,
     };
 
     Ok(resp)
@@ -173,8 +600,28 @@ pub fn query_distribution_info(
 
 pub fn query_reward_amount_per_sec(
     deps: Deps,
+    env: Env,
     asset_info: AssetInfo,
 ) -> StdResult<RewardAmountPerSecondResponse> {
+    if let Some(schedule) = read_schedule(deps.storage, &asset_info.to_vec(deps.api)?)? {
+        return Ok(RewardAmountPerSecondResponse {
+            reward_amount: schedule.rate_at(env.block.time.seconds())?,
+        });
+    }
+
+    if let Some(gauge) = read_gauge(deps.storage)? {
+        let res: PoolWeightResponse = deps.querier.query_wasm_smart(
+            deps.api.addr_humanize(&gauge.contract)?,
+            &GaugeQueryMsg::PoolWeight {
+                asset_info,
+                epoch: None,
+            },
+        )?;
+        return Ok(RewardAmountPerSecondResponse {
+            reward_amount: gauge.emission.rate_at(env.block.time.seconds())? * res.weight,
+        });
+    }
+
     let state = read_config(deps.storage)?;
     let reward_amount = _read_pool_reward_per_sec(
         &deps.querier,
@@ -185,15 +632,196 @@ pub fn query_reward_amount_per_sec(
     Ok(RewardAmountPerSecondResponse { reward_amount })
 }
 
+pub fn query_budget(deps: Deps) -> StdResult<BudgetResponse> {
+    let budgets = read_budgets(deps.storage)?
+        .into_iter()
+        .map(|budget| {
+            Ok(BudgetResponseItem {
+                info: budget.info.to_normal(deps.api)?,
+                funded: budget.funded,
+                distributed: budget.distributed,
+                remaining: budget.remaining(),
+            })
+        })
+        .collect::<StdResult<Vec<BudgetResponseItem>>>()?;
+
+    Ok(BudgetResponse { budgets })
+}
+
+pub fn query_schedule(deps: Deps, asset_info: AssetInfo) -> StdResult<ScheduleResponse> {
+    let schedule = read_schedule(deps.storage, &asset_info.to_vec(deps.api)?)?;
+
+    Ok(ScheduleResponse {
+        asset_info,
+        schedule,
+    })
+}
+
+pub fn query_gauge(deps: Deps) -> StdResult<GaugeResponse> {
+    Ok(match read_gauge(deps.storage)? {
+        Some(gauge) => GaugeResponse {
+            gauge_contract: Some(deps.api.addr_humanize(&gauge.contract)?),
+            emission: Some(gauge.emission),
+        },
+        None => GaugeResponse {
+            gauge_contract: None,
+            emission: None,
+        },
+    })
+}
+
+/// future gauge epochs use the latest weights
+pub fn query_projected_emission(
+    deps: Deps,
+    asset_info: AssetInfo,
+    start_time: u64,
+    end_time: u64,
+) -> StdResult<ProjectedEmissionResponse> {
+    let amount = _read_pool_emission(deps, &asset_info, start_time, end_time)?;
+
+    Ok(ProjectedEmissionResponse {
+        asset_info,
+        start_time,
+        end_time,
+        amount,
+    })
+}
+
+/// emission of a pool between start_time and end_time, from its schedule, its gauge weight
+/// of the total emission, or flat at the current staking reward per second
+fn _read_pool_emission(
+    deps: Deps,
+    asset_info: &AssetInfo,
+    start_time: u64,
+    end_time: u64,
+) -> StdResult<Uint128> {
+    if start_time > end_time {
+        return Err(StdError::generic_err("Invalid time range"));
+    }
+
+    if let Some(schedule) = read_schedule(deps.storage, &asset_info.to_vec(deps.api)?)? {
+        return schedule.emission(start_time, end_time);
+    }
+
+    if let Some(gauge) = read_gauge(deps.storage)? {
+        return _read_gauge_emission(deps, &gauge, asset_info, start_time, end_time);
+    }
+
+    let config = read_config(deps.storage)?;
+    let reward_amount = _read_pool_reward_per_sec(
+        &deps.querier,
+        deps.api.addr_humanize(&config.staking_contract)?,
+        asset_info.clone(),
+    )?;
+    Ok(reward_amount.checked_mul((end_time - start_time).into())?)
+}
+
+/// the pool weight changes at each epoch of the gauge
+fn _read_gauge_emission(
+    deps: Deps,
+    gauge: &GaugeInfo,
+    asset_info: &AssetInfo,
+    start_time: u64,
+    end_time: u64,
+) -> StdResult<Uint128> {
+    let gauge_contract = deps.api.addr_humanize(&gauge.contract)?;
+    let gauge_config: GaugeConfigResponse = deps
+        .querier
+        .query_wasm_smart(gauge_contract.clone(), &GaugeQueryMsg::Config {})?;
+
+    let mut amount = Uint128::zero();
+    let mut time = start_time;
+    while time < end_time {
+        let epoch = time.saturating_sub(gauge_config.start_time) / gauge_config.epoch_duration;
+        let epoch_end_time = gauge_config.start_time + (epoch + 1) * gauge_config.epoch_duration;
+        let next_time = end_time.min(epoch_end_time);
+        let res: PoolWeightResponse = deps.querier.query_wasm_smart(
+            gauge_contract.clone(),
+            &GaugeQueryMsg::PoolWeight {
+                asset_info: asset_info.clone(),
+                epoch: Some(epoch),
+            },
+        )?;
+        amount += gauge.emission.emission(time, next_time)? * res.weight;
+        time = next_time;
+    }
+
+    Ok(amount)
+}
+
+/// largest part of amount covered by the budgets of all the reward assets, deducted from them
+fn _consume_budget(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    rewards_per_sec: &[Asset],
+    amount: Uint128,
+) -> StdResult<Uint128> {
+    let total_weight: Uint128 = rewards_per_sec.iter().map(|a| a.amount).sum();
+    if total_weight.is_zero() {
+        return Ok(amount);
+    }
+
+    let mut budgets = vec![];
+    let mut amount = amount;
+    for reward_per_sec in rewards_per_sec.iter().filter(|a| !a.amount.is_zero()) {
+        let asset_key = reward_per_sec.info.to_vec(api)?;
+        let budget = read_budget(storage, &asset_key)?.unwrap_or(Budget {
+            info: reward_per_sec.info.to_raw(api)?,
+            funded: Uint128::zero(),
+            distributed: Uint128::zero(),
+        });
+        amount = amount.min(
+            budget
+                .remaining()
+                .checked_multiply_ratio(total_weight, reward_per_sec.amount)
+                .unwrap_or(Uint128::MAX),
+        );
+        budgets.push((asset_key, budget, reward_per_sec.amount));
+    }
+
+    for (asset_key, mut budget, weight) in budgets {
+        budget.distributed += amount.multiply_ratio(weight, total_weight);
+        store_budget(storage, &asset_key, &budget)?;
+    }
+
+    Ok(amount)
+}
+
+fn _read_pools(
+    deps: Deps,
+    start_after: Option<AssetInfo>,
+    limit: Option<u32>,
+) -> StdResult<Vec<AssetInfo>> {
+    let start_after = start_after
+        .map(|asset_info| asset_info.to_vec(deps.api))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    read_pools(deps.storage, start_after, limit)?
+        .iter()
+        .map(|asset_info| asset_info.to_normal(deps.api))
+        .collect()
+}
+
 fn _read_pool_reward_per_sec(
     querier: &QuerierWrapper,
     staking_contract: Addr,
     asset_info: AssetInfo,
 ) -> StdResult<Uint128> {
+    let rewards_per_sec = _read_pool_rewards_per_sec(querier, staking_contract, asset_info)?;
+
+    Ok(rewards_per_sec.iter().map(|a| a.amount).sum())
+}
+
+fn _read_pool_rewards_per_sec(
+    querier: &QuerierWrapper,
+    staking_contract: Addr,
+    asset_info: AssetInfo,
+) -> StdResult<Vec<Asset>> {
     let res: RewardsPerSecResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: staking_contract.to_string(),
         msg: to_binary(&StakingQueryMsg::RewardsPerSec { asset_info })?,
     }))?;
 
-    Ok(res.assets.iter().map(|a| a.amount).sum())
+    Ok(res.assets)
 }
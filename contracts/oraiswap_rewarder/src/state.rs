@@ -1,9 +1,16 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+use oraiswap::asset::{AssetInfoRaw, AssetRaw};
+use oraiswap::querier::calc_range_start;
+use oraiswap::rewarder::EmissionSchedule;
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_LAST_DISTRIBUTED: &[u8] = b"last_distributed";
+static KEY_SCHEDULE: &[u8] = b"schedule";
+static KEY_POOL: &[u8] = b"pool";
+static KEY_BUDGET: &[u8] = b"budget";
+static KEY_GAUGE: &[u8] = b"gauge";
 
 #[cw_serde]
 pub struct Config {
@@ -11,6 +18,8 @@ pub struct Config {
     pub staking_contract: CanonicalAddr,
     pub distribution_interval: u64,
     pub init_time: u64,
+    #[serde(default)]
+    pub keeper_bounty: Option<AssetRaw>,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -32,3 +41,105 @@ pub fn store_last_distributed(
 pub fn read_last_distributed(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<u64> {
     ReadonlyBucket::new(storage, KEY_LAST_DISTRIBUTED).load(asset_key)
 }
+
+pub fn store_schedule(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    schedule: &EmissionSchedule,
+) -> StdResult<()> {
+    Bucket::new(storage, KEY_SCHEDULE).save(asset_key, schedule)
+}
+
+pub fn remove_schedule(storage: &mut dyn Storage, asset_key: &[u8]) {
+    Bucket::<EmissionSchedule>::new(storage, KEY_SCHEDULE).remove(asset_key)
+}
+
+pub fn read_schedule(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+) -> StdResult<Option<EmissionSchedule>> {
+    ReadonlyBucket::new(storage, KEY_SCHEDULE).may_load(asset_key)
+}
+
+pub fn store_pool(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    asset_info: &AssetInfoRaw,
+) -> StdResult<()> {
+    Bucket::new(storage, KEY_POOL).save(asset_key, asset_info)
+}
+
+pub fn remove_pool(storage: &mut dyn Storage, asset_key: &[u8]) {
+    Bucket::<AssetInfoRaw>::new(storage, KEY_POOL).remove(asset_key)
+}
+
+pub fn read_pool(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<Option<AssetInfoRaw>> {
+    ReadonlyBucket::new(storage, KEY_POOL).may_load(asset_key)
+}
+
+/// registered pools ordered by asset key
+pub fn read_pools(
+    storage: &dyn Storage,
+    start_after: Option<Vec<u8>>,
+    limit: usize,
+) -> StdResult<Vec<AssetInfoRaw>> {
+    let start = calc_range_start(start_after);
+    ReadonlyBucket::new(storage, KEY_POOL)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, asset_info)| asset_info))
+        .collect()
+}
+
+pub fn count_pools(storage: &dyn Storage) -> usize {
+    ReadonlyBucket::<AssetInfoRaw>::new(storage, KEY_POOL)
+        .range(None, None, Order::Ascending)
+        .count()
+}
+
+#[cw_serde]
+pub struct GaugeInfo {
+    pub contract: CanonicalAddr,
+    pub emission: EmissionSchedule,
+}
+
+pub fn store_gauge(storage: &mut dyn Storage, gauge: &GaugeInfo) -> StdResult<()> {
+    singleton(storage, KEY_GAUGE).save(gauge)
+}
+
+pub fn remove_gauge(storage: &mut dyn Storage) {
+    singleton::<GaugeInfo>(storage, KEY_GAUGE).remove()
+}
+
+pub fn read_gauge(storage: &dyn Storage) -> StdResult<Option<GaugeInfo>> {
+    singleton_read(storage, KEY_GAUGE).may_load()
+}
+
+/// reward tokens sent to the staking contract and rewards deposited, by reward asset
+#[cw_serde]
+pub struct Budget {
+    pub info: AssetInfoRaw,
+    pub funded: Uint128,
+    pub distributed: Uint128,
+}
+
+impl Budget {
+    pub fn remaining(&self) -> Uint128 {
+        self.funded.saturating_sub(self.distributed)
+    }
+}
+
+pub fn store_budget(storage: &mut dyn Storage, asset_key: &[u8], budget: &Budget) -> StdResult<()> {
+    Bucket::new(storage, KEY_BUDGET).save(asset_key, budget)
+}
+
+pub fn read_budget(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<Option<Budget>> {
+    ReadonlyBucket::new(storage, KEY_BUDGET).may_load(asset_key)
+}
+
+pub fn read_budgets(storage: &dyn Storage) -> StdResult<Vec<Budget>> {
+    ReadonlyBucket::new(storage, KEY_BUDGET)
+        .range(None, None, Order::Ascending)
+        .map(|item| item.map(|(_, budget)| budget))
+        .collect()
+}
@@ -1,9 +1,113 @@
-use crate::contract::{instantiate, query_config};
+use crate::contract::{
+    execute, instantiate, migrate, query, query_config, query_projected_emission,
+};
 use cosmwasm_std::{
-    testing::{mock_dependencies, mock_env, mock_info},
-    Addr,
+    coin, from_binary,
+    testing::{
+        mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info, MockQuerier,
+    },
+    to_binary, Addr, BankMsg, ContractResult, CosmosMsg, Decimal, StdError, SystemError,
+    SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use oraiswap::admin::{PendingAdminResponse, TimelockResponse};
+use oraiswap::asset::{Asset, AssetInfo};
+use oraiswap::gauge::{
+    ConfigResponse as GaugeConfigResponse, PoolWeightResponse, QueryMsg as GaugeQueryMsg,
+};
+use oraiswap::rewarder::{
+    BudgetResponse, BudgetResponseItem, ConfigResponse, Cw20HookMsg, DistributionInfoResponse,
+    EmissionSchedule, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolsResponse, QueryMsg,
+    RewardAmountPerSecondResponse, ScheduleResponse, ScheduleSegment,
 };
-use oraiswap::rewarder::{ConfigResponse, InstantiateMsg};
+use oraiswap::staking::{
+    ExecuteMsg as StakingExecuteMsg, PoolInfoResponse, QueryMsg as StakingQueryMsg,
+    RewardsPerSecResponse,
+};
+
+/// every pool exists except unknown, the multi pool pays orai and a reward token in a 6:4 ratio,
+/// the others pay their own asset at 10 per second.
+/// The gauge epochs last 1000 seconds, pools have no weight in the first one and 25% after it,
+/// the current epoch is the second one
+fn mock_staking_querier(querier: &mut MockQuerier) {
+    querier.update_wasm(|query| match query {
+        WasmQuery::Smart { contract_addr, msg } if contract_addr == "gauge" => {
+            let res = match from_binary(msg).unwrap() {
+                GaugeQueryMsg::Config {} => to_binary(&GaugeConfigResponse {
+                    owner: Addr::unchecked("owner"),
+                    staking_contract: Addr::unchecked("staking"),
+                    vote_asset_info: AssetInfo::NativeToken {
+                        denom: "orai".to_string(),
+                    },
+                    epoch_duration: 1000,
+                    start_time: mock_env().block.time.seconds(),
+                    current_epoch: 0,
+                }),
+                GaugeQueryMsg::PoolWeight { asset_info, epoch } => to_binary(&PoolWeightResponse {
+                    asset_info,
+                    epoch: epoch.unwrap_or(1),
+                    weight: if epoch == Some(0) {
+                        Decimal::zero()
+                    } else {
+                        Decimal::percent(25)
+                    },
+                }),
+                _ => panic!("unexpected query"),
+            };
+            SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+        }
+        WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+            StakingQueryMsg::PoolInfo { asset_info } => {
+                if asset_info.to_string() == "unknown" {
+                    return SystemResult::Err(SystemError::UnsupportedRequest {
+                        kind: "pool not found".to_string(),
+                    });
+                }
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&PoolInfoResponse {
+                        asset_info,
+                        staking_token: Addr::unchecked("lp"),
+                        total_bond_amount: Uint128::zero(),
+                        reward_index: Decimal::zero(),
+                        pending_reward: Uint128::zero(),
+                        migration_index_snapshot: None,
+                        migration_deprecated_staking_token: None,
+                        unbonding_period: 0,
+                    })
+                    .unwrap(),
+                ))
+            }
+            StakingQueryMsg::RewardsPerSec { asset_info } => {
+                let assets = if asset_info.to_string() == "multi" {
+                    vec![
+                        Asset {
+                            info: AssetInfo::NativeToken {
+                                denom: "orai".to_string(),
+                            },
+                            amount: Uint128::from(6u128),
+                        },
+                        Asset {
+                            info: AssetInfo::Token {
+                                contract_addr: Addr::unchecked("reward"),
+                            },
+                            amount: Uint128::from(4u128),
+                        },
+                    ]
+                } else {
+                    vec![Asset {
+                        info: asset_info,
+                        amount: Uint128::from(10u128),
+                    }]
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&RewardsPerSecResponse { assets }).unwrap(),
+                ))
+            }
+            _ => panic!("unexpected query"),
+        },
+        _ => panic!("unexpected query"),
+    });
+}
 
 #[test]
 fn proper_initialization() {
@@ -26,6 +130,772 @@ fn proper_initialization() {
             owner: Addr::unchecked("owner"),
             staking_contract: Addr::unchecked("staking"),
             distribution_interval: 600,
+            keeper_bounty: None,
+        }
+    );
+}
+
+#[test]
+fn emission_schedules() {
+    let linear = EmissionSchedule::Linear {
+        segments: vec![
+            ScheduleSegment {
+                start_time: 100,
+                end_time: 200,
+                start_rate: Uint128::from(100u128),
+                end_rate: Uint128::from(0u128),
+            },
+            ScheduleSegment {
+                start_time: 300,
+                end_time: 400,
+                start_rate: Uint128::from(10u128),
+                end_rate: Uint128::from(30u128),
+            },
+        ],
+    };
+    linear.validate().unwrap();
+    assert_eq!(linear.emitted_until(100).unwrap(), Uint128::zero());
+    assert_eq!(linear.emitted_until(150).unwrap(), Uint128::from(3750u128));
+    assert_eq!(linear.emitted_until(250).unwrap(), Uint128::from(5000u128));
+    assert_eq!(linear.emitted_until(500).unwrap(), Uint128::from(7000u128));
+    // across the gap between segments
+    assert_eq!(linear.emission(150, 350).unwrap(), Uint128::from(2000u128));
+    assert_eq!(linear.rate_at(150).unwrap(), Uint128::from(50u128));
+    assert_eq!(linear.rate_at(250).unwrap(), Uint128::zero());
+
+    let overlapping = EmissionSchedule::Linear {
+        segments: vec![
+            ScheduleSegment {
+                start_time: 100,
+                end_time: 200,
+                start_rate: Uint128::from(1u128),
+                end_rate: Uint128::from(1u128),
+            },
+            ScheduleSegment {
+                start_time: 150,
+                end_time: 250,
+                start_rate: Uint128::from(1u128),
+                end_rate: Uint128::from(1u128),
+            },
+        ],
+    };
+    assert!(overlapping.validate().is_err());
+
+    let halving = EmissionSchedule::Halving {
+        start_time: 1000,
+        epoch_duration: 100,
+        initial_rate: Uint128::from(100u128),
+        cap: Uint128::from(16000u128),
+    };
+    halving.validate().unwrap();
+    assert_eq!(
+        halving.emitted_until(1050).unwrap(),
+        Uint128::from(5000u128)
+    );
+    assert_eq!(
+        halving.emitted_until(1150).unwrap(),
+        Uint128::from(12500u128)
+    );
+    assert_eq!(halving.rate_at(1150).unwrap(), Uint128::from(50u128));
+    // capped during the third epoch
+    assert_eq!(
+        halving.emitted_until(1250).unwrap(),
+        Uint128::from(16000u128)
+    );
+    assert_eq!(halving.emission(1250, 5000).unwrap(), Uint128::zero());
+    assert_eq!(halving.rate_at(1250).unwrap(), Uint128::zero());
+}
+
+#[test]
+fn distribute_with_schedule() {
+    let mut deps = mock_dependencies();
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(600),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    mock_staking_querier(&mut deps.querier);
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "owner".to_string(),
+        amount: Uint128::from(1_000_000u128),
+        msg: to_binary(&Cw20HookMsg::Fund {}).unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), mock_info("asset", &[]), msg).unwrap();
+
+    let schedule = EmissionSchedule::Halving {
+        start_time: now,
+        epoch_duration: 1000,
+        initial_rate: Uint128::from(100u128),
+        cap: Uint128::from(1_000_000u128),
+    };
+    let msg = ExecuteMsg::UpdateSchedule {
+        asset_info: asset_info.clone(),
+        schedule: Some(schedule),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // first distribution, nothing was emitted before the start of the schedule
+    env.block.time = env.block.time.plus_seconds(700);
+    let msg = ExecuteMsg::Distribute {
+        asset_infos: vec![asset_info.clone()],
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+
+    // from 700 to 1500, across the first halving
+    env.block.time = env.block.time.plus_seconds(800);
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+    let expected = 300 * 100 + 500 * 50;
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }) => {
+            assert_eq!(contract_addr, "staking");
+            assert_eq!(
+                from_binary::<StakingExecuteMsg>(msg).unwrap(),
+                StakingExecuteMsg::DepositReward {
+                    rewards: vec![Asset {
+                        info: asset_info.clone(),
+                        amount: Uint128::from(expected as u128),
+                    }],
+                }
+            );
+        }
+        _ => panic!("unexpected message"),
+    }
+
+    let res = query_projected_emission(deps.as_ref(), asset_info, now, now + 1500).unwrap();
+    assert_eq!(res.amount, Uint128::from(70000u128 + expected as u128));
+}
+
+#[test]
+fn distribute_all_registered_pools() {
+    let mut deps = mock_dependencies_with_balance(&[coin(150u128, "orai")]);
+    mock_staking_querier(&mut deps.querier);
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(600),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Fund {};
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(
+            "owner",
+            &[coin(100_000u128, "atom"), coin(100_000u128, "usdt")],
+        ),
+        msg,
+    )
+    .unwrap();
+
+    let keeper_bounty = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "orai".to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        staking_contract: None,
+        distribution_interval: None,
+        keeper_bounty: Some(keeper_bounty.clone()),
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap().keeper_bounty,
+        Some(keeper_bounty)
+    );
+
+    let native = |denom: &str| AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    };
+    for denom in ["usdt", "atom", "unknown"] {
+        let msg = ExecuteMsg::RegisterPool {
+            asset_info: native(denom),
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        let res = execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg);
+        assert_eq!(res.is_ok(), denom != "unknown");
+    }
+
+    let res: PoolsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.pools, vec![native("atom"), native("usdt")]);
+
+    // first page, the keeper gets the bounty share of one pool out of two
+    let msg = ExecuteMsg::DistributeAll {
+        start_after: None,
+        limit: Some(1),
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![Asset {
+                    info: native("atom"),
+                    amount: Uint128::from(6010u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![coin(50u128, "orai")],
+        })
+    );
+
+    // next page
+    let msg = ExecuteMsg::DistributeAll {
+        start_after: Some(native("atom")),
+        limit: None,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    assert_eq!(
+        res.messages[1].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "keeper".to_string(),
+            amount: vec![coin(50u128, "orai")],
+        })
+    );
+
+    // nothing to distribute before the interval, no bounty
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::DistributeAll {
+        start_after: None,
+        limit: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::DeregisterPool {
+            asset_info: native("usdt"),
+        },
+    )
+    .unwrap();
+
+    // only atom is distributed
+    env.block.time = env.block.time.plus_seconds(600);
+    let res = execute(deps.as_mut(), env, mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => assert_eq!(
+            from_binary::<StakingExecuteMsg>(msg).unwrap(),
+            StakingExecuteMsg::DepositReward {
+                rewards: vec![Asset {
+                    info: native("atom"),
+                    amount: Uint128::from(7000u128),
+                }],
+            }
+        ),
+        _ => panic!("unexpected message"),
+    }
+}
+
+#[test]
+fn distribute_within_budget() {
+    let mut deps = mock_dependencies_with_balance(&[coin(1000u128, "orai")]);
+    mock_staking_querier(&mut deps.querier);
+    let mut env = mock_env();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(600),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // funds are forwarded to the staking contract
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        ExecuteMsg::Fund {},
+    )
+    .unwrap_err();
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[coin(3000u128, "orai")]),
+        ExecuteMsg::Fund {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: "staking".to_string(),
+            amount: vec![coin(3000u128, "orai")],
+        })
+    );
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::Fund {}).unwrap(),
+    });
+    let res = execute(deps.as_mut(), env.clone(), mock_info("reward", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "reward".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "staking".to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // 601 seconds at 10 per second needs 3606 orai and 2404 reward, the reward budget allows 2500
+    let asset_info = AssetInfo::NativeToken {
+        denom: "multi".to_string(),
+    };
+    let msg = ExecuteMsg::Distribute {
+        asset_infos: vec![asset_info.clone()],
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![Asset {
+                    info: asset_info.clone(),
+                    amount: Uint128::from(2500u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let res: BudgetResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Budget {}).unwrap()).unwrap();
+    assert_eq!(
+        res.budgets,
+        vec![
+            BudgetResponseItem {
+                info: AssetInfo::NativeToken {
+                    denom: "orai".to_string(),
+                },
+                funded: Uint128::from(3000u128),
+                distributed: Uint128::from(1500u128),
+                remaining: Uint128::from(1500u128),
+            },
+            BudgetResponseItem {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("reward"),
+                },
+                funded: Uint128::from(1000u128),
+                distributed: Uint128::from(1000u128),
+                remaining: Uint128::zero(),
+            },
+        ]
+    );
+
+    // the reward budget is spent, nothing more is distributed
+    env.block.time = env.block.time.plus_seconds(600);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward { rewards: vec![] }).unwrap(),
+            funds: vec![],
+        })
+    );
+
+    // the keeper gets no bounty for the pool with a spent budget, however often it calls
+    let msg_keeper_bounty = ExecuteMsg::UpdateConfig {
+        owner: None,
+        staking_contract: None,
+        distribution_interval: None,
+        keeper_bounty: Some(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "orai".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        }),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        msg_keeper_bounty,
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[]),
+        ExecuteMsg::RegisterPool {
+            asset_info: asset_info.clone(),
+        },
+    )
+    .unwrap();
+    for _ in 0..2 {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("keeper", &[]),
+            ExecuteMsg::DistributeAll {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+    }
+
+    // the clipped distribution covered 250 of the 601 seconds
+    let res: DistributionInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::DistributionInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.last_distributed, env.block.time.seconds() - 951);
+
+    // budgets seeded on migrate, the uncovered 951 seconds are distributed
+    migrate(
+        deps.as_mut(),
+        env.clone(),
+        MigrateMsg {
+            budgets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "orai".to_string(),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("reward"),
+                    },
+                    amount: Uint128::from(10000u128),
+                },
+            ],
+        },
+    )
+    .unwrap();
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![Asset {
+                    info: asset_info,
+                    amount: Uint128::from(9510u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let res: BudgetResponse =
+        from_binary(&query(deps.as_ref(), env, QueryMsg::Budget {}).unwrap()).unwrap();
+    assert_eq!(
+        res.budgets[1],
+        BudgetResponseItem {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("reward"),
+            },
+            funded: Uint128::from(11000u128),
+            distributed: Uint128::from(4804u128),
+            remaining: Uint128::from(6196u128),
         }
     );
 }
+
+#[test]
+fn distribute_with_gauge_weights() {
+    let mut deps = mock_dependencies();
+    mock_staking_querier(&mut deps.querier);
+    let mut env = mock_env();
+    let now = env.block.time.seconds();
+
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: Some(600),
+    };
+    instantiate(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Fund {};
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner", &[coin(1_000_000u128, "atom")]),
+        msg,
+    )
+    .unwrap();
+
+    // 100 per second in total, split by the gauge weights
+    let msg = ExecuteMsg::UpdateGauge {
+        gauge_contract: Some(Addr::unchecked("gauge")),
+        emission: None,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap_err();
+    let msg = ExecuteMsg::UpdateGauge {
+        gauge_contract: Some(Addr::unchecked("gauge")),
+        emission: Some(EmissionSchedule::Linear {
+            segments: vec![ScheduleSegment {
+                start_time: now,
+                end_time: now + 1_000_000,
+                start_rate: Uint128::from(100u128),
+                end_rate: Uint128::from(100u128),
+            }],
+        }),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    execute(deps.as_mut(), env.clone(), mock_info("owner", &[]), msg).unwrap();
+
+    // from 899 to 1500, only the part in the second epoch has weight
+    env.block.time = env.block.time.plus_seconds(1500);
+    let asset_info = AssetInfo::NativeToken {
+        denom: "atom".to_string(),
+    };
+    let msg = ExecuteMsg::Distribute {
+        asset_infos: vec![asset_info.clone()],
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&StakingExecuteMsg::DepositReward {
+                rewards: vec![Asset {
+                    info: asset_info.clone(),
+                    amount: Uint128::from(12500u128),
+                }],
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let res = query_projected_emission(deps.as_ref(), asset_info.clone(), now, now + 2000).unwrap();
+    assert_eq!(res.amount, Uint128::from(25000u128));
+
+    let res: RewardAmountPerSecondResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::RewardAmountPerSec { asset_info },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.reward_amount, Uint128::from(25u128));
+}
+
+#[test]
+fn owner_transfer_and_timelock() {
+    let mut deps = mock_dependencies();
+    let msg = InstantiateMsg {
+        staking_contract: Addr::unchecked("staking"),
+        distribution_interval: None,
+    };
+    instantiate(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // the proposed owner takes over only once it accepts
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: Some(Addr::unchecked("owner2")),
+        staking_contract: None,
+        distribution_interval: None,
+        keeper_bounty: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap().owner,
+        Addr::unchecked("owner")
+    );
+    let res: PendingAdminResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::PendingAdmin {}).unwrap()).unwrap();
+    assert_eq!(res.pending_admin, Some(Addr::unchecked("owner2")));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        ExecuteMsg::AcceptAdmin {},
+    )
+    .unwrap_err();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::AcceptAdmin {},
+    )
+    .unwrap();
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap().owner,
+        Addr::unchecked("owner2")
+    );
+
+    // schedule changes wait for the delay
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        ExecuteMsg::UpdateTimelock { delay: 100 },
+    )
+    .unwrap();
+    let asset_info = AssetInfo::NativeToken {
+        denom: "orai".to_string(),
+    };
+    let msg = ExecuteMsg::UpdateSchedule {
+        asset_info: asset_info.clone(),
+        schedule: Some(EmissionSchedule::Halving {
+            start_time: 1000,
+            epoch_duration: 100,
+            initial_rate: Uint128::from(100u128),
+            cap: Uint128::from(16000u128),
+        }),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    execute(deps.as_mut(), mock_env(), mock_info("owner2", &[]), msg).unwrap();
+    let res: TimelockResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Timelock {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delay, 100);
+    assert_eq!(res.changes.len(), 1);
+    assert_eq!(res.changes[0].eta, mock_env().block.time.seconds() + 100);
+
+    let query_schedule = |deps: cosmwasm_std::Deps| -> ScheduleResponse {
+        from_binary(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::Schedule {
+                    asset_info: asset_info.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(query_schedule(deps.as_ref()).schedule, None);
+
+    let msg = ExecuteMsg::ExecuteTimelocked { id: 1 };
+    let err = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner2", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Timelocked change 1 is not ready")
+    );
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner2", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert!(query_schedule(deps.as_ref()).schedule.is_some());
+
+    // applied changes leave the queue
+    execute(deps.as_mut(), env, mock_info("owner2", &[]), msg).unwrap_err();
+}
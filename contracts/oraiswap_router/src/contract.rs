@@ -118,13 +118,20 @@ fn assert_minium_receive(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
         QueryMsg::SimulateSwapOperations {
             offer_amount,
             operations,
-        } => to_binary(&simulate_swap_operations(deps, offer_amount, operations)?),
+            to,
+        } => to_binary(&simulate_swap_operations(
+            deps,
+            env,
+            offer_amount,
+            operations,
+            to,
+        )?),
     }
 }
 
@@ -140,8 +147,10 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
 
 fn simulate_swap_operations(
     deps: Deps,
+    env: Env,
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
+    to: Option<Addr>,
 ) -> StdResult<SimulateSwapOperationsResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
     let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
@@ -154,7 +163,7 @@ fn simulate_swap_operations(
     }
 
     let mut offer_amount = offer_amount;
-    for operation in operations.into_iter() {
+    for (operation_index, operation) in operations.into_iter().enumerate() {
         let pair_config = query_pair_config(&deps.querier, factory_addr.clone())
             .or_else(|_| query_pair_config(&deps.querier, factory_addr_v2.clone()))?;
         let oracle_contract = OracleContract(pair_config.oracle_addr);
@@ -182,8 +191,11 @@ fn simulate_swap_operations(
                 };
 
                 // Deduct tax before querying simulation, with native token only
-                offer_amount = offer_amount
-                    .checked_sub(return_asset.compute_tax(&oracle_contract, &deps.querier)?)?;
+                offer_amount = offer_amount.checked_sub(return_asset.compute_tax(
+                    &oracle_contract,
+                    &deps.querier,
+                    Some(&pair_info.contract_addr),
+                )?)?;
 
                 let mut res: SimulationResponse =
                     deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
@@ -201,10 +213,19 @@ fn simulate_swap_operations(
                     amount: res.return_amount,
                 };
 
+                // the pair pays intermediate returns to the router and the last one to the receiver
+                let receiver = if operation_index + 1 == operations_len {
+                    to.clone()
+                } else {
+                    Some(env.contract.address.clone())
+                };
+
                 // Deduct tax after querying simulation, with native token only
-                res.return_amount = res
-                    .return_amount
-                    .checked_sub(return_asset.compute_tax(&oracle_contract, &deps.querier)?)?;
+                res.return_amount = res.return_amount.checked_sub(return_asset.compute_tax(
+                    &oracle_contract,
+                    &deps.querier,
+                    receiver.as_ref(),
+                )?)?;
 
                 offer_amount = res.return_amount;
             }
@@ -159,9 +159,11 @@ fn asset_into_swap_msg(
             };
 
             // deduct tax first
-            let amount = offer_asset
-                .amount
-                .checked_sub(return_asset.compute_tax(oracle_contract, &deps.querier)?)?;
+            let amount = offer_asset.amount.checked_sub(return_asset.compute_tax(
+                oracle_contract,
+                &deps.querier,
+                Some(&pair_contract),
+            )?)?;
 
             Ok(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: pair_contract.to_string(),
@@ -119,6 +119,7 @@ fn simulate_swap_operations_test() {
                 denom: ATOM_DENOM.to_string(),
             },
         }],
+        to: None,
     };
 
     let res: SimulateSwapOperationsResponse = app.query(router_addr, &msg).unwrap();
@@ -0,0 +1,120 @@
+use crate::compound::query_pool_pair;
+use crate::state::{read_config, read_pool_info, read_rewards_per_sec, Config, PoolInfo};
+use cosmwasm_std::{Decimal, Deps, StdError, StdResult, Uint128};
+use oraiswap::asset::AssetInfo;
+use oraiswap::oracle::OracleContract;
+use oraiswap::pair::{PoolResponse, QueryMsg as PairQueryMsg};
+use oraiswap::querier::{query_pair_info, query_supply};
+use oraiswap::staking::{PoolAprResponse, RewardAprItem};
+
+const SECONDS_PER_YEAR: u128 = 365 * 24 * 3600;
+
+pub fn query_pool_apr(deps: Deps, asset_info: AssetInfo) -> StdResult<PoolAprResponse> {
+    let config: Config = read_config(deps.storage)?;
+    let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_info.to_vec(deps.api)?)?;
+    let base_asset_info = AssetInfo::NativeToken {
+        denom: config.base_denom.clone(),
+    };
+
+    // both sides of the pair have the same value at the pool price
+    let pair_info = query_pool_pair(deps, &asset_info)?;
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(pair_info.contract_addr, &PairQueryMsg::Pool {})?;
+    let side_value = match pool
+        .assets
+        .iter()
+        .find(|asset| asset.info.eq(&base_asset_info))
+    {
+        Some(base_asset) => base_asset.amount,
+        None => {
+            pool.assets[0].amount
+                * query_base_price(deps, &config, &base_asset_info, &pool.assets[0].info)?
+        }
+    };
+    let total_share = query_supply(&deps.querier, pair_info.liquidity_token)?;
+    let tvl = if total_share.is_zero() {
+        Uint128::zero()
+    } else {
+        pool_info
+            .total_bond_amount
+            .multiply_ratio(side_value.checked_mul(2u128.into())?, total_share)
+    };
+
+    let mut annual_reward_value = Uint128::zero();
+    let mut rewards = vec![];
+    for reward_per_sec in read_rewards_per_sec(deps.storage, &asset_info.to_vec(deps.api)?)? {
+        let reward_per_sec = reward_per_sec.to_normal(deps.api)?;
+        let price = query_base_price(deps, &config, &base_asset_info, &reward_per_sec.info)?;
+        let reward_value = reward_per_sec.amount.checked_mul(SECONDS_PER_YEAR.into())? * price;
+        annual_reward_value += reward_value;
+        rewards.push(RewardAprItem {
+            info: reward_per_sec.info,
+            reward_per_sec: reward_per_sec.amount,
+            price,
+            annual_reward_value: reward_value,
+            apr: ratio_or_zero(reward_value, tvl),
+        });
+    }
+
+    Ok(PoolAprResponse {
+        asset_info,
+        base_denom: config.base_denom,
+        total_bond_amount: pool_info.total_bond_amount,
+        tvl,
+        annual_reward_value,
+        apr: ratio_or_zero(annual_reward_value, tvl),
+        rewards,
+    })
+}
+
+/// price of the asset in the base denom, from its pair with the base denom
+/// or the oracle exchange rate for native denoms without pair
+fn query_base_price(
+    deps: Deps,
+    config: &Config,
+    base_asset_info: &AssetInfo,
+    asset_info: &AssetInfo,
+) -> StdResult<Decimal> {
+    if asset_info.eq(base_asset_info) {
+        return Ok(Decimal::one());
+    }
+
+    let pair_price = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.factory_addr)?,
+        &[base_asset_info.clone(), asset_info.clone()],
+    )
+    .and_then(|pair_info| {
+        let base_reserve =
+            base_asset_info.query_pool(&deps.querier, pair_info.contract_addr.clone())?;
+        let reserve = asset_info.query_pool(&deps.querier, pair_info.contract_addr)?;
+        if reserve.is_zero() {
+            return Err(StdError::generic_err("Pair has no liquidity"));
+        }
+        Ok(Decimal::from_ratio(base_reserve, reserve))
+    });
+
+    match (pair_price, asset_info) {
+        (Ok(price), _) => Ok(price),
+        (Err(_), AssetInfo::NativeToken { denom }) => {
+            let oracle_contract = OracleContract(deps.api.addr_humanize(&config.oracle_addr)?);
+            Ok(oracle_contract
+                .query_exchange_rate(&deps.querier, denom.clone(), config.base_denom.clone())?
+                .item
+                .exchange_rate)
+        }
+        (Err(_), _) => Err(StdError::generic_err(format!(
+            "Cannot price {} in {}",
+            asset_info, config.base_denom
+        ))),
+    }
+}
+
+fn ratio_or_zero(numerator: Uint128, denominator: Uint128) -> Decimal {
+    if denominator.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(numerator, denominator)
+    }
+}
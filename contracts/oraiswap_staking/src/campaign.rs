@@ -0,0 +1,502 @@
+use crate::state::{
+    campaign_rewards_store, next_campaign_id, read_campaign, read_campaign_reward_asset,
+    read_campaign_reward_assets, read_campaign_rewards, read_closed_reward_index,
+    read_closed_reward_indexes, read_config, read_is_migrated, read_last_campaign_id,
+    read_pool_campaigns, read_pool_info, remove_campaign_reward_asset, rewards_read,
+    store_campaign, store_campaign_reward_asset, store_closed_reward_index, store_pool_campaigns,
+    Campaign, CampaignRewardIndex, CampaignRewardInfo, CampaignRewards, PoolCampaigns,
+};
+use cosmwasm_std::{
+    Addr, Api, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Uint128,
+};
+use oraiswap::asset::{Asset, AssetInfo, AssetRaw};
+use oraiswap::staking::{
+    CampaignResponse, CampaignRewardAssetsResponse, CampaignRewardsResponse, CampaignsResponse,
+};
+
+// bound the campaigns updated on every bond change of the pool
+const MAX_ACTIVE_CAMPAIGNS: usize = 10;
+const MAX_CAMPAIGN_REWARD_ASSETS: usize = 5;
+
+pub fn create_campaign(
+    deps: DepsMut,
+    env: Env,
+    funder: Addr,
+    asset_info: AssetInfo,
+    reward: Asset,
+    start_time: u64,
+    end_time: u64,
+) -> StdResult<Response> {
+    if reward.amount.is_zero() {
+        return Err(StdError::generic_err("Campaign reward must not be zero"));
+    }
+
+    // only allowed reward assets above their minimum can take one of the bounded slots
+    let minimum_reward = read_campaign_reward_asset(deps.storage, &reward.info.to_vec(deps.api)?)?
+        .ok_or_else(|| StdError::generic_err("Campaign reward asset is not allowed"))?;
+    if reward.amount < minimum_reward.amount {
+        return Err(StdError::generic_err(format!(
+            "Campaign reward must be at least {}",
+            minimum_reward.to_normal(deps.api)?
+        )));
+    }
+
+    let now = env.block.time.seconds();
+    if start_time < now || end_time <= start_time {
+        return Err(StdError::generic_err("Invalid campaign time range"));
+    }
+
+    let asset_key = &asset_info.to_vec(deps.api)?;
+    let pool_info = read_pool_info(deps.storage, asset_key)?;
+    let mut pool_campaigns = update_pool_campaigns(
+        deps.storage,
+        asset_key,
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    if pool_campaigns.campaign_ids.len() >= MAX_ACTIVE_CAMPAIGNS {
+        return Err(StdError::generic_err(
+            "Too many active campaigns on the pool",
+        ));
+    }
+
+    let campaign_id = next_campaign_id(deps.storage)?;
+    let reward_raw = reward.to_raw(deps.api)?;
+    if !pool_campaigns
+        .reward_indexes
+        .iter()
+        .any(|reward_index| reward_index.info.eq(&reward_raw.info))
+    {
+        if pool_campaigns.reward_indexes.len() >= MAX_CAMPAIGN_REWARD_ASSETS {
+            return Err(StdError::generic_err(
+                "Too many campaign reward assets on the pool",
+            ));
+        }
+        pool_campaigns.reward_indexes.push(CampaignRewardIndex {
+            info: reward_raw.info.clone(),
+            index: Decimal::zero(),
+            index_id: campaign_id,
+        });
+    }
+
+    let campaign = Campaign {
+        campaign_id,
+        asset_info: asset_info.to_raw(deps.api)?,
+        funder: deps.api.addr_canonicalize(funder.as_str())?,
+        reward: reward_raw,
+        start_time,
+        end_time,
+        unused_amount: Uint128::zero(),
+    };
+    store_campaign(deps.storage, &campaign)?;
+
+    pool_campaigns.campaign_ids.push(campaign.campaign_id);
+    pool_campaigns.last_update_time = now;
+    store_pool_campaigns(deps.storage, asset_key, &pool_campaigns)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "create_campaign"),
+        ("campaign_id", &campaign.campaign_id.to_string()),
+        ("asset_info", &asset_info.to_string()),
+        ("funder", funder.as_str()),
+        ("reward", &reward.to_string()),
+        ("start_time", &start_time.to_string()),
+        ("end_time", &end_time.to_string()),
+    ]))
+}
+
+/// allow a campaign reward asset with its minimum reward, or disallow it without minimum
+pub fn update_campaign_reward_asset(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_info: AssetInfo,
+    minimum_reward: Option<Uint128>,
+) -> StdResult<Response> {
+    let config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_key = reward_info.to_vec(deps.api)?;
+    match minimum_reward {
+        Some(amount) => store_campaign_reward_asset(
+            deps.storage,
+            &asset_key,
+            &Asset {
+                info: reward_info.clone(),
+                amount,
+            }
+            .to_raw(deps.api)?,
+        )?,
+        None => remove_campaign_reward_asset(deps.storage, &asset_key),
+    }
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_campaign_reward_asset"),
+        ("reward_info", &reward_info.to_string()),
+    ]))
+}
+
+pub fn refund_campaign(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    campaign_id: u64,
+) -> StdResult<Response> {
+    let campaign = read_campaign(deps.storage, campaign_id)?;
+    if campaign.funder != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let now = env.block.time.seconds();
+    if now < campaign.end_time {
+        return Err(StdError::generic_err("Campaign has not ended"));
+    }
+
+    // stream the campaign until its end to settle the unused amount
+    let asset_key = campaign.asset_info.as_bytes();
+    let pool_info = read_pool_info(deps.storage, asset_key)?;
+    update_pool_campaigns(
+        deps.storage,
+        asset_key,
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    let mut campaign = read_campaign(deps.storage, campaign_id)?;
+    if campaign.unused_amount.is_zero() {
+        return Err(StdError::generic_err("Nothing to refund"));
+    }
+
+    let refund_asset = AssetRaw {
+        info: campaign.reward.info.clone(),
+        amount: campaign.unused_amount,
+    }
+    .to_normal(deps.api)?;
+    campaign.unused_amount = Uint128::zero();
+    store_campaign(deps.storage, &campaign)?;
+
+    Ok(Response::new()
+        .add_message(refund_asset.into_msg(None, &deps.querier, info.sender)?)
+        .add_attributes([
+            ("action", "refund_campaign"),
+            ("campaign_id", &campaign_id.to_string()),
+            ("refund_asset", &refund_asset.to_string()),
+        ]))
+}
+
+/// stream the active campaigns of the pool into the reward indexes until now,
+/// must be called before the total bond amount of the pool changes
+pub fn update_pool_campaigns(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    total_bond_amount: Uint128,
+    now: u64,
+) -> StdResult<PoolCampaigns> {
+    let mut pool_campaigns = read_pool_campaigns(storage, asset_key)?;
+    if pool_campaigns.campaign_ids.is_empty() {
+        return Ok(pool_campaigns);
+    }
+
+    let mut campaigns = read_campaigns(storage, &pool_campaigns.campaign_ids)?;
+    let closed_indexes =
+        accrue_pool_campaigns(&mut pool_campaigns, &mut campaigns, total_bond_amount, now);
+    for campaign in campaigns.iter() {
+        store_campaign(storage, campaign)?;
+    }
+    for reward_index in closed_indexes.iter() {
+        store_closed_reward_index(storage, asset_key, reward_index)?;
+    }
+    store_pool_campaigns(storage, asset_key, &pool_campaigns)?;
+
+    Ok(pool_campaigns)
+}
+
+/// withdraw the campaign rewards of the bond amount to pending reward, before changing share
+pub fn settle_campaign_rewards(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    bond_amount: Uint128,
+    total_bond_amount: Uint128,
+    now: u64,
+) -> StdResult<()> {
+    let pool_campaigns = update_pool_campaigns(storage, asset_key, total_bond_amount, now)?;
+    // no campaign was ever created on the pool
+    if pool_campaigns.last_update_time == 0 {
+        return Ok(());
+    }
+
+    let mut campaign_rewards = read_campaign_rewards(storage, staker_addr, asset_key)?;
+    accrue_campaign_rewards(
+        storage,
+        asset_key,
+        &mut campaign_rewards,
+        &pool_campaigns,
+        &[],
+        bond_amount,
+    )?;
+    campaign_rewards_store(storage, staker_addr).save(asset_key, &campaign_rewards)
+}
+
+/// take the pending campaign rewards, rewards must be settled before calling this
+pub fn withdraw_campaign_rewards(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    close_position: bool,
+) -> StdResult<Vec<AssetRaw>> {
+    let mut campaign_rewards = read_campaign_rewards(storage, staker_addr, asset_key)?;
+    let reward_assets: Vec<AssetRaw> = campaign_rewards
+        .rewards
+        .iter_mut()
+        .filter(|campaign_reward| !campaign_reward.pending_reward.is_zero())
+        .map(|campaign_reward| AssetRaw {
+            info: campaign_reward.info.clone(),
+            amount: std::mem::take(&mut campaign_reward.pending_reward),
+        })
+        .collect();
+
+    if close_position {
+        campaign_rewards_store(storage, staker_addr).remove(asset_key);
+    } else if !reward_assets.is_empty() {
+        campaign_rewards_store(storage, staker_addr).save(asset_key, &campaign_rewards)?;
+    }
+
+    Ok(reward_assets)
+}
+
+fn read_campaigns(storage: &dyn Storage, campaign_ids: &[u64]) -> StdResult<Vec<Campaign>> {
+    campaign_ids
+        .iter()
+        .map(|campaign_id| read_campaign(storage, *campaign_id))
+        .collect()
+}
+
+/// returns the reward indexes closed because all the campaigns of their reward asset ended
+fn accrue_pool_campaigns(
+    pool_campaigns: &mut PoolCampaigns,
+    campaigns: &mut [Campaign],
+    total_bond_amount: Uint128,
+    now: u64,
+) -> Vec<CampaignRewardIndex> {
+    if now <= pool_campaigns.last_update_time {
+        return vec![];
+    }
+
+    for campaign in campaigns.iter_mut() {
+        let amount = campaign.streamed_amount(now)
+            - campaign.streamed_amount(pool_campaigns.last_update_time);
+        if amount.is_zero() {
+            continue;
+        }
+
+        // nobody can earn the reward, it goes back to the funder
+        if total_bond_amount.is_zero() {
+            campaign.unused_amount += amount;
+            continue;
+        }
+
+        if let Some(reward_index) = pool_campaigns
+            .reward_indexes
+            .iter_mut()
+            .find(|reward_index| reward_index.info.eq(&campaign.reward.info))
+        {
+            reward_index.index += Decimal::from_ratio(amount, total_bond_amount);
+        }
+    }
+
+    // ended campaigns are fully streamed, their slots and reward indexes are freed
+    pool_campaigns.campaign_ids = campaigns
+        .iter()
+        .filter(|campaign| campaign.end_time > now)
+        .map(|campaign| campaign.campaign_id)
+        .collect();
+    pool_campaigns.last_update_time = now;
+
+    let (reward_indexes, closed_indexes) = std::mem::take(&mut pool_campaigns.reward_indexes)
+        .into_iter()
+        .partition(|reward_index: &CampaignRewardIndex| {
+            campaigns.iter().any(|campaign| {
+                campaign.end_time > now && campaign.reward.info.eq(&reward_index.info)
+            })
+        });
+    pool_campaigns.reward_indexes = reward_indexes;
+    closed_indexes
+}
+
+/// the closed indexes not stored yet are given, the others are read from the storage
+fn accrue_campaign_rewards(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    campaign_rewards: &mut CampaignRewards,
+    pool_campaigns: &PoolCampaigns,
+    closed_indexes: &[CampaignRewardIndex],
+    bond_amount: Uint128,
+) -> StdResult<()> {
+    let is_active = |index_id: u64| {
+        pool_campaigns
+            .reward_indexes
+            .iter()
+            .any(|reward_index| reward_index.index_id == index_id)
+    };
+
+    for campaign_reward in campaign_rewards.rewards.iter_mut() {
+        let index = match pool_campaigns
+            .reward_indexes
+            .iter()
+            .chain(closed_indexes)
+            .find(|reward_index| reward_index.index_id == campaign_reward.index_id)
+        {
+            Some(reward_index) => reward_index.index,
+            None => read_closed_reward_index(storage, asset_key, campaign_reward.index_id)?.index,
+        };
+        campaign_reward.pending_reward +=
+            (bond_amount * index).checked_sub(bond_amount * campaign_reward.index)?;
+        campaign_reward.index = index;
+    }
+
+    // indexes opened since the last settlement start from zero, closed ones included
+    let last_campaign_id = campaign_rewards.last_campaign_id;
+    let mut new_indexes: Vec<CampaignRewardIndex> = pool_campaigns
+        .reward_indexes
+        .iter()
+        .chain(closed_indexes)
+        .filter(|reward_index| reward_index.index_id > last_campaign_id)
+        .cloned()
+        .collect();
+    if !bond_amount.is_zero() {
+        new_indexes.extend(read_closed_reward_indexes(
+            storage,
+            asset_key,
+            last_campaign_id,
+        )?);
+    }
+    for reward_index in new_indexes {
+        if !campaign_rewards
+            .rewards
+            .iter()
+            .any(|campaign_reward| campaign_reward.index_id == reward_index.index_id)
+        {
+            campaign_rewards.rewards.push(CampaignRewardInfo {
+                pending_reward: bond_amount * reward_index.index,
+                info: reward_index.info,
+                index: reward_index.index,
+                index_id: reward_index.index_id,
+            });
+        }
+    }
+
+    // nothing accrues on closed indexes anymore once they are paid
+    campaign_rewards.rewards.retain(|campaign_reward| {
+        !campaign_reward.pending_reward.is_zero() || is_active(campaign_reward.index_id)
+    });
+    campaign_rewards.last_campaign_id = read_last_campaign_id(storage)?;
+
+    Ok(())
+}
+
+fn to_campaign_response(api: &dyn Api, campaign: Campaign) -> StdResult<CampaignResponse> {
+    Ok(CampaignResponse {
+        campaign_id: campaign.campaign_id,
+        asset_info: campaign.asset_info.to_normal(api)?,
+        funder: api.addr_humanize(&campaign.funder)?,
+        reward: campaign.reward.to_normal(api)?,
+        start_time: campaign.start_time,
+        end_time: campaign.end_time,
+        unused_amount: campaign.unused_amount,
+    })
+}
+
+pub fn query_campaign(deps: Deps, campaign_id: u64) -> StdResult<CampaignResponse> {
+    to_campaign_response(deps.api, read_campaign(deps.storage, campaign_id)?)
+}
+
+pub fn query_campaigns(deps: Deps, asset_info: AssetInfo) -> StdResult<CampaignsResponse> {
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let pool_campaigns = read_pool_campaigns(deps.storage, &asset_key)?;
+    let campaigns = read_campaigns(deps.storage, &pool_campaigns.campaign_ids)?
+        .into_iter()
+        .map(|campaign| to_campaign_response(deps.api, campaign))
+        .collect::<StdResult<Vec<CampaignResponse>>>()?;
+
+    Ok(CampaignsResponse { campaigns })
+}
+
+pub fn query_campaign_reward_assets(deps: Deps) -> StdResult<CampaignRewardAssetsResponse> {
+    let reward_assets = read_campaign_reward_assets(deps.storage)?
+        .into_iter()
+        .map(|minimum_reward| minimum_reward.to_normal(deps.api))
+        .collect::<StdResult<Vec<Asset>>>()?;
+
+    Ok(CampaignRewardAssetsResponse { reward_assets })
+}
+
+pub fn query_campaign_rewards(
+    deps: Deps,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+) -> StdResult<CampaignRewardsResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let pool_info = read_pool_info(deps.storage, &asset_key)?;
+
+    // positions of a deprecated staking token do not earn campaign rewards
+    let bond_amount = match rewards_read(deps.storage, &staker_addr_raw).may_load(&asset_key)? {
+        Some(_)
+            if pool_info.migration_params.is_some()
+                && !read_is_migrated(deps.storage, &asset_key, &staker_addr_raw) =>
+        {
+            Uint128::zero()
+        }
+        Some(reward_info) => reward_info.effective_bond_amount(),
+        None => Uint128::zero(),
+    };
+
+    let mut pool_campaigns = read_pool_campaigns(deps.storage, &asset_key)?;
+    let mut campaigns = read_campaigns(deps.storage, &pool_campaigns.campaign_ids)?;
+    let closed_indexes = accrue_pool_campaigns(
+        &mut pool_campaigns,
+        &mut campaigns,
+        pool_info.total_effective_bond_amount(),
+        env.block.time.seconds(),
+    );
+
+    let mut campaign_rewards = read_campaign_rewards(deps.storage, &staker_addr_raw, &asset_key)?;
+    accrue_campaign_rewards(
+        deps.storage,
+        &asset_key,
+        &mut campaign_rewards,
+        &pool_campaigns,
+        &closed_indexes,
+        bond_amount,
+    )?;
+
+    // a reward asset can have a closed and a new index
+    let mut pending_rewards: Vec<AssetRaw> = vec![];
+    for campaign_reward in campaign_rewards.rewards {
+        match pending_rewards
+            .iter_mut()
+            .find(|pending_reward| pending_reward.info.eq(&campaign_reward.info))
+        {
+            Some(pending_reward) => pending_reward.amount += campaign_reward.pending_reward,
+            None => pending_rewards.push(AssetRaw {
+                info: campaign_reward.info,
+                amount: campaign_reward.pending_reward,
+            }),
+        }
+    }
+    let pending_rewards = pending_rewards
+        .into_iter()
+        .map(|pending_reward| pending_reward.to_normal(deps.api))
+        .collect::<StdResult<Vec<Asset>>>()?;
+
+    Ok(CampaignRewardsResponse {
+        staker_addr,
+        asset_info,
+        pending_rewards,
+    })
+}
@@ -0,0 +1,395 @@
+use crate::rewards::process_reward_assets;
+use crate::state::{read_config, read_pool_info, Config, PoolInfo};
+use cosmwasm_std::{
+    coin, to_binary, Addr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Response, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use oraiswap::asset::{Asset, AssetInfo, PairInfo};
+use oraiswap::oracle::OracleContract;
+use oraiswap::pair::ExecuteMsg as PairExecuteMsg;
+use oraiswap::querier::{query_pair_info_from_pair, query_token_balance};
+use oraiswap::router::{
+    Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg, SwapOperation,
+};
+use oraiswap::staking::ExecuteMsg;
+
+pub fn compound(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+    slippage_tolerance: Option<Decimal>,
+    reward_minimum_receives: Vec<Asset>,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let router_addr = match &config.router_addr {
+        Some(router_addr) => deps.api.addr_humanize(router_addr)?,
+        None => return Err(StdError::generic_err("Router is not set")),
+    };
+    let pair_info = query_pool_pair(deps.as_ref(), &asset_info)?;
+    let oracle_contract = OracleContract(pair_info.oracle_addr.clone());
+
+    // take all pending rewards of the pool, campaign rewards included
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let reward_assets = process_reward_assets(
+        deps.storage,
+        &staker_addr_raw,
+        &Some(asset_info.to_vec(deps.api)?),
+        true,
+        env.block.time.seconds(),
+    )?;
+
+    // rewards in one of the pool assets are kept, the others are swapped into the base asset,
+    // which the pool must hold to count the swapped amount
+    let base_asset_info = AssetInfo::NativeToken {
+        denom: config.base_denom,
+    };
+    let mut assets = pair_info.asset_infos.clone().map(|info| Asset {
+        info,
+        amount: Uint128::zero(),
+    });
+    let holds_base_asset = assets.iter().any(|asset| asset.info.eq(&base_asset_info));
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for reward_asset in reward_assets {
+        let reward_asset = reward_asset.to_normal(deps.api)?;
+        if reward_asset.amount.is_zero() {
+            continue;
+        }
+
+        match assets
+            .iter_mut()
+            .find(|asset| asset.info.eq(&reward_asset.info))
+        {
+            Some(asset) => asset.amount += reward_asset.amount,
+            None => {
+                if !holds_base_asset {
+                    return Err(StdError::generic_err(format!(
+                        "Can not compound {} into a pool without {}",
+                        reward_asset.info, base_asset_info
+                    )));
+                }
+                let reward_minimum_receive = reward_minimum_receives
+                    .iter()
+                    .find(|minimum_receive| minimum_receive.info.eq(&reward_asset.info))
+                    .map(|minimum_receive| minimum_receive.amount)
+                    .ok_or_else(|| {
+                        StdError::generic_err(format!(
+                            "No minimum receive for swapping {}",
+                            reward_asset.info
+                        ))
+                    })?;
+                messages.push(swap_msg(
+                    &router_addr,
+                    &oracle_contract,
+                    &deps.querier,
+                    reward_asset,
+                    base_asset_info.clone(),
+                    reward_minimum_receive,
+                )?)
+            }
+        }
+    }
+
+    if messages.is_empty() && assets.iter().all(|asset| asset.amount.is_zero()) {
+        return Err(StdError::generic_err("No reward to compound"));
+    }
+
+    // get current balances to later compute the swapped amounts
+    let prev_balances = query_balances(&deps.querier, &assets, &env.contract.address)?;
+
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::CompoundHook {
+                asset_info: asset_info.clone(),
+                staker_addr: staker_addr.clone(),
+                assets,
+                prev_balances,
+                slippage_tolerance,
+                balanced: false,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "compound"),
+        ("staker_addr", staker_addr.as_str()),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn compound_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    staker_addr: Addr,
+    assets: [Asset; 2],
+    prev_balances: [Uint128; 2],
+    slippage_tolerance: Option<Decimal>,
+    balanced: bool,
+) -> StdResult<Response> {
+    // only can be called by itself
+    if info.sender != env.contract.address {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let config: Config = read_config(deps.storage)?;
+    let pair_info = query_pool_pair(deps.as_ref(), &asset_info)?;
+    let oracle_contract = OracleContract(pair_info.oracle_addr.clone());
+
+    // add the balance changes of the swaps executed since the previous step
+    let mut assets = assets;
+    let balances = query_balances(&deps.querier, &assets, &env.contract.address)?;
+    for ((asset, balance), prev_balance) in assets.iter_mut().zip(balances).zip(prev_balances) {
+        asset.amount = (asset.amount + balance).checked_sub(prev_balance)?;
+    }
+
+    if !balanced {
+        if let Some((offer_asset, ask_asset_info, spot_return)) =
+            balancing_swap(&deps.querier, &pair_info, &assets)?
+        {
+            let router_addr = match &config.router_addr {
+                Some(router_addr) => deps.api.addr_humanize(router_addr)?,
+                None => return Err(StdError::generic_err("Router is not set")),
+            };
+            // the swap can not receive less than its pool price return net of the slippage tolerance
+            let minimum_receive = match slippage_tolerance {
+                Some(slippage_tolerance) if slippage_tolerance <= Decimal::one() => {
+                    spot_return * (Decimal::one() - slippage_tolerance)
+                }
+                _ => {
+                    return Err(StdError::generic_err(
+                        "Slippage tolerance is required to balance the compounded assets",
+                    ))
+                }
+            };
+            let offer_asset_str = offer_asset.to_string();
+
+            // 1. Swap half of the excess asset through the router
+            // 2. Execute the hook again to provide the balanced amounts
+            return Ok(Response::new()
+                .add_messages(vec![
+                    swap_msg(
+                        &router_addr,
+                        &oracle_contract,
+                        &deps.querier,
+                        offer_asset,
+                        ask_asset_info,
+                        minimum_receive,
+                    )?,
+                    WasmMsg::Execute {
+                        contract_addr: env.contract.address.to_string(),
+                        msg: to_binary(&ExecuteMsg::CompoundHook {
+                            asset_info,
+                            staker_addr,
+                            assets,
+                            prev_balances: balances,
+                            slippage_tolerance,
+                            balanced: true,
+                        })?,
+                        funds: vec![],
+                    }
+                    .into(),
+                ])
+                .add_attributes([
+                    ("action", "compound_swap"),
+                    ("offer_asset", &offer_asset_str),
+                ]));
+        }
+    }
+
+    if assets.iter().any(|asset| asset.amount.is_zero()) {
+        return Err(StdError::generic_err("Compound amount is too small"));
+    }
+
+    // get current lp token amount to later compute the recived amount
+    let prev_staking_token_amount = query_token_balance(
+        &deps.querier,
+        pair_info.liquidity_token.clone(),
+        env.contract.address.clone(),
+    )?;
+
+    // 1. Increase allowance of the token assets for pair contract
+    // 2. Provide liquidity, native assets net of tax
+    // 3. Execute staking hook, will stake in the name of the staker
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    for asset in assets.iter_mut() {
+        match &asset.info {
+            AssetInfo::Token { contract_addr } => messages.push(
+                WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair_info.contract_addr.to_string(),
+                        amount: asset.amount,
+                        expires: None,
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            ),
+            AssetInfo::NativeToken { denom } => {
+                asset.amount = asset.amount.checked_sub(asset.compute_tax(
+                    &oracle_contract,
+                    &deps.querier,
+                    Some(&pair_info.contract_addr),
+                )?)?;
+                funds.push(coin(asset.amount.u128(), denom));
+            }
+        }
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let provided_assets = assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>()
+        .join(",");
+
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: pair_info.contract_addr.to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets,
+                slippage_tolerance,
+                receiver: None,
+            })?,
+            funds,
+        }
+        .into(),
+    );
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AutoStakeHook {
+                asset_info,
+                staking_token: pair_info.liquidity_token,
+                staker_addr,
+                prev_staking_token_amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes([("action", "compound_provide"), ("assets", &provided_assets)]))
+}
+
+/// pair minting the pool staking token, the pool can be keyed by any of its assets or the lp token
+pub fn query_pool_pair(deps: Deps, asset_info: &AssetInfo) -> StdResult<PairInfo> {
+    let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_info.to_vec(deps.api)?)?;
+    let staking_token = deps.api.addr_humanize(&pool_info.staking_token)?;
+    let minter: Option<MinterResponse> = deps
+        .querier
+        .query_wasm_smart(staking_token.clone(), &Cw20QueryMsg::Minter {})?;
+    let pair_info = match minter {
+        Some(minter) => query_pair_info_from_pair(&deps.querier, Addr::unchecked(minter.minter))?,
+        None => return Err(StdError::generic_err("Invalid staking token")),
+    };
+
+    if pair_info.liquidity_token != staking_token {
+        return Err(StdError::generic_err("Invalid staking token"));
+    }
+
+    Ok(pair_info)
+}
+
+fn query_balances(
+    querier: &QuerierWrapper,
+    assets: &[Asset; 2],
+    addr: &Addr,
+) -> StdResult<[Uint128; 2]> {
+    Ok([
+        assets[0].info.query_pool(querier, addr.clone())?,
+        assets[1].info.query_pool(querier, addr.clone())?,
+    ])
+}
+
+/// half of the amount exceeding the pool ratio, to swap into the other asset,
+/// with its return at the pool price
+fn balancing_swap(
+    querier: &QuerierWrapper,
+    pair_info: &PairInfo,
+    assets: &[Asset; 2],
+) -> StdResult<Option<(Asset, AssetInfo, Uint128)>> {
+    let reserves = query_balances(querier, assets, &pair_info.contract_addr)?;
+    if reserves.iter().any(|reserve| reserve.is_zero()) {
+        return Err(StdError::generic_err("Pool has no liquidity"));
+    }
+
+    let (offer_index, ask_index) =
+        if assets[0].amount > assets[1].amount.multiply_ratio(reserves[0], reserves[1]) {
+            (0, 1)
+        } else {
+            (1, 0)
+        };
+    let matched_amount = assets[ask_index]
+        .amount
+        .multiply_ratio(reserves[offer_index], reserves[ask_index]);
+    let offer_amount = assets[offer_index]
+        .amount
+        .saturating_sub(matched_amount)
+        .multiply_ratio(1u128, 2u128);
+
+    if offer_amount.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some((
+        Asset {
+            info: assets[offer_index].info.clone(),
+            amount: offer_amount,
+        },
+        assets[ask_index].info.clone(),
+        offer_amount.multiply_ratio(reserves[ask_index], reserves[offer_index]),
+    )))
+}
+
+fn swap_msg(
+    router_addr: &Addr,
+    oracle_contract: &OracleContract,
+    querier: &QuerierWrapper,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    minimum_receive: Uint128,
+) -> StdResult<CosmosMsg> {
+    let operations = vec![SwapOperation::OraiSwap {
+        offer_asset_info: offer_asset.info.clone(),
+        ask_asset_info,
+    }];
+
+    // the swapped amount is returned to this contract
+    Ok(match &offer_asset.info {
+        AssetInfo::NativeToken { .. } => WasmMsg::Execute {
+            contract_addr: router_addr.to_string(),
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive: Some(minimum_receive),
+                to: None,
+            })?,
+            funds: vec![offer_asset.deduct_tax(oracle_contract, querier, Some(router_addr))?],
+        }
+        .into(),
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: router_addr.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                    operations,
+                    minimum_receive: Some(minimum_receive),
+                    to: None,
+                })?,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
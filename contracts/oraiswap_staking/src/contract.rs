@@ -2,11 +2,24 @@
 use cosmwasm_std::entry_point;
 
 // use crate::migration::migrate_rewards_store;
+use crate::apr::query_pool_apr;
+use crate::campaign::{
+    create_campaign, query_campaign, query_campaign_reward_assets, query_campaign_rewards,
+    query_campaigns, refund_campaign, update_campaign_reward_asset, update_pool_campaigns,
+};
+use crate::compound::{compound, compound_hook};
+use crate::lock::{
+    lock, query_lock_tiers, query_locks, release_expired, unlock, update_lock_tiers,
+};
 use crate::rewards::{
     deposit_reward, process_reward_assets, query_all_reward_infos, query_reward_info,
     withdraw_reward, withdraw_reward_others,
 };
-use crate::staking::{auto_stake, auto_stake_hook, bond, unbond, update_list_stakers};
+use crate::staking::{
+    auto_stake, auto_stake_hook, bond, checkpoint_total_bond, claim_unbonded, emergency_unbond,
+    query_bond_at, query_claims, query_reward_recipient, query_total_bond_at, set_reward_recipient,
+    transfer_position, unbond, update_list_stakers,
+};
 use crate::state::{
     read_config, read_pool_info, read_rewards_per_sec, stakers_read, store_config, store_pool_info,
     store_rewards_per_sec, Config, MigrationParams, PoolInfo,
@@ -16,6 +29,10 @@ use cosmwasm_std::{
     from_binary, to_binary, Addr, Binary, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo,
     Order, Response, StdError, StdResult, Uint128,
 };
+use oraiswap::admin::{
+    accept_admin, cancel_change, propose_admin, query_pending_admin, query_timelock, queue_change,
+    store_timelock_delay, take_change,
+};
 use oraiswap::asset::{Asset, AssetInfo, AssetRaw, ORAI_DENOM};
 use oraiswap::staking::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolInfoResponse,
@@ -42,6 +59,8 @@ pub fn instantiate(
             factory_addr: deps.api.addr_canonicalize(msg.factory_addr.as_str())?,
             // default base_denom pass to factory is orai token
             base_denom: msg.base_denom.unwrap_or(ORAI_DENOM.to_string()),
+            router_addr: None,
+            emergency: false,
         },
     )?;
 
@@ -50,24 +69,101 @@ pub fn instantiate(
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+    if is_timelocked(&msg) {
+        assert_owner(deps.as_ref(), &info.sender)?;
+        if let Some(change) = queue_change(deps.storage, &env, &msg)? {
+            return Ok(Response::new().add_attributes(change.attributes()));
+        }
+    }
+
     match msg {
-        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
-        ExecuteMsg::UpdateConfig { rewarder, owner } => update_config(deps, info, owner, rewarder),
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig {
+            rewarder,
+            owner,
+            router,
+        } => update_config(deps, info, owner, rewarder, router),
+        ExecuteMsg::AcceptAdmin {} => accept_owner(deps, info),
+        ExecuteMsg::UpdateTimelock { delay } => update_timelock(deps, info, delay),
+        ExecuteMsg::ExecuteTimelocked { id } => execute_timelocked(deps, env, info, id),
+        ExecuteMsg::CancelTimelocked { id } => cancel_timelocked(deps, info, id),
         ExecuteMsg::UpdateRewardsPerSec { asset_info, assets } => {
-            update_rewards_per_sec(deps, info, asset_info, assets)
+            update_rewards_per_sec(deps, env, info, asset_info, assets)
         }
         ExecuteMsg::DepositReward { rewards } => deposit_reward(deps, info, rewards),
+        ExecuteMsg::SetEmergency { emergency } => set_emergency(deps, info, emergency),
+        ExecuteMsg::UpdateCampaignRewardAsset {
+            reward_info,
+            minimum_reward,
+        } => update_campaign_reward_asset(deps, info, reward_info, minimum_reward),
+        ExecuteMsg::UpdateLockTiers {
+            tiers,
+            early_unlock_penalty,
+        } => update_lock_tiers(deps, info, tiers, early_unlock_penalty),
         ExecuteMsg::RegisterAsset {
             asset_info,
             staking_token,
         } => register_asset(deps, info, asset_info, staking_token),
+        ExecuteMsg::UpdateUnbondingPeriod {
+            asset_info,
+            unbonding_period,
+        } => update_unbonding_period(deps, info, asset_info, unbonding_period),
         ExecuteMsg::DeprecateStakingToken {
             asset_info,
             new_staking_token,
-        } => deprecate_staking_token(deps, info, asset_info, new_staking_token),
+        } => deprecate_staking_token(deps, env, info, asset_info, new_staking_token),
         ExecuteMsg::Unbond { asset_info, amount } => {
             unbond(deps, env, info.sender, asset_info, amount)
         }
+        ExecuteMsg::CreateCampaign {
+            asset_info,
+            reward,
+            start_time,
+            end_time,
+        } => {
+            // cw20 reward is sent through the cw20 hook
+            if !reward.is_native_token() {
+                return Err(StdError::generic_err(
+                    "Token reward must be sent with the cw20 hook",
+                ));
+            }
+            reward.assert_sent_native_token_balance(&info)?;
+            create_campaign(
+                deps,
+                env,
+                info.sender,
+                asset_info,
+                reward,
+                start_time,
+                end_time,
+            )
+        }
+        ExecuteMsg::RefundCampaign { campaign_id } => refund_campaign(deps, env, info, campaign_id),
+        ExecuteMsg::ClaimUnbonded {} => claim_unbonded(deps, env, info.sender),
+        ExecuteMsg::EmergencyUnbond { asset_info } => {
+            emergency_unbond(deps, env, info.sender, asset_info)
+        }
+        ExecuteMsg::Lock {
+            asset_info,
+            amount,
+            duration,
+        } => lock(deps, env, info.sender, asset_info, amount, duration),
+        ExecuteMsg::Unlock {
+            asset_info,
+            lock_id,
+        } => unlock(deps, env, info.sender, asset_info, lock_id),
+        ExecuteMsg::ReleaseExpiredLocks {
+            staker_addr,
+            asset_info,
+        } => release_expired(deps, env, staker_addr, asset_info),
+        ExecuteMsg::SetRewardRecipient { recipient } => {
+            set_reward_recipient(deps, info.sender, recipient)
+        }
+        ExecuteMsg::TransferPosition {
+            asset_info,
+            amount,
+            to,
+        } => transfer_position(deps, env, info.sender, asset_info, amount, to),
         ExecuteMsg::Withdraw { asset_info } => withdraw_reward(deps, env, info, asset_info),
         ExecuteMsg::WithdrawOthers {
             asset_info,
@@ -91,6 +187,36 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             staker_addr,
             prev_staking_token_amount,
         ),
+        ExecuteMsg::Compound {
+            asset_info,
+            slippage_tolerance,
+            reward_minimum_receives,
+        } => compound(
+            deps,
+            env,
+            info.sender,
+            asset_info,
+            slippage_tolerance,
+            reward_minimum_receives,
+        ),
+        ExecuteMsg::CompoundHook {
+            asset_info,
+            staker_addr,
+            assets,
+            prev_balances,
+            slippage_tolerance,
+            balanced,
+        } => compound_hook(
+            deps,
+            env,
+            info,
+            asset_info,
+            staker_addr,
+            assets,
+            prev_balances,
+            slippage_tolerance,
+            balanced,
+        ),
         ExecuteMsg::UpdateListStakers {
             asset_info,
             stakers,
@@ -100,6 +226,7 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
 
 pub fn receive_cw20(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> StdResult<Response> {
@@ -129,11 +256,30 @@ pub fn receive_cw20(
 
             bond(
                 deps,
+                env,
                 Addr::unchecked(cw20_msg.sender),
                 asset_info,
                 cw20_msg.amount,
             )
         }
+        Ok(Cw20HookMsg::CreateCampaign {
+            asset_info,
+            start_time,
+            end_time,
+        }) => create_campaign(
+            deps,
+            env,
+            Addr::unchecked(cw20_msg.sender),
+            asset_info,
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            start_time,
+            end_time,
+        ),
         Err(_) => Err(StdError::generic_err("invalid cw20 hook message")),
     }
 }
@@ -143,6 +289,7 @@ pub fn update_config(
     info: MessageInfo,
     owner: Option<Addr>,
     rewarder: Option<Addr>,
+    router: Option<Addr>,
 ) -> StdResult<Response> {
     let mut config: Config = read_config(deps.storage)?;
 
@@ -150,22 +297,84 @@ pub fn update_config(
         return Err(StdError::generic_err("unauthorized"));
     }
 
+    // the new owner must accept first
     if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(owner.as_str())?;
+        propose_admin(deps.storage, deps.api, &owner)?;
     }
 
     if let Some(rewarder) = rewarder {
         config.rewarder = deps.api.addr_canonicalize(rewarder.as_str())?;
     }
 
+    if let Some(router) = router {
+        config.router_addr = Some(deps.api.addr_canonicalize(router.as_str())?);
+    }
+
     store_config(deps.storage, &config)?;
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+pub fn accept_owner(deps: DepsMut, info: MessageInfo) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+    config.owner = accept_admin(deps.storage, deps.api, &info.sender)?;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "accept_admin"))
+}
+
+pub fn update_timelock(deps: DepsMut, info: MessageInfo, delay: u64) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+    store_timelock_delay(deps.storage, delay)?;
+
+    Ok(Response::new().add_attribute("action", "update_timelock"))
+}
+
+pub fn execute_timelocked(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+
+    match take_change(deps.storage, &env, id)? {
+        ExecuteMsg::UpdateRewardsPerSec { asset_info, assets } => {
+            update_rewards_per_sec(deps, env, info, asset_info, assets)
+        }
+        ExecuteMsg::UpdateTimelock { delay } => update_timelock(deps, info, delay),
+        _ => Err(StdError::generic_err("Invalid timelocked change")),
+    }
+}
+
+pub fn cancel_timelocked(deps: DepsMut, info: MessageInfo, id: u64) -> StdResult<Response> {
+    assert_owner(deps.as_ref(), &info.sender)?;
+    cancel_change(deps.storage, id)?;
+
+    Ok(Response::new()
+        .add_attributes([("action", "cancel_change"), ("change_id", &id.to_string())]))
+}
+
+fn is_timelocked(msg: &ExecuteMsg) -> bool {
+    matches!(
+        msg,
+        ExecuteMsg::UpdateRewardsPerSec { .. } | ExecuteMsg::UpdateTimelock { .. }
+    )
+}
+
+fn assert_owner(deps: Deps, sender: &Addr) -> StdResult<()> {
+    let config: Config = read_config(deps.storage)?;
+    if deps.api.addr_canonicalize(sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    Ok(())
+}
+
 // need to withdraw all rewards of the stakers belong to the pool
 // may need to call withdraw from backend side by querying all stakers with pagination in case out of gas
 fn update_rewards_per_sec(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     asset_info: AssetInfo,
     assets: Vec<Asset>,
@@ -196,6 +405,7 @@ fn update_rewards_per_sec(
             &staker_addr_raw,
             &Some(asset_key.clone()),
             false,
+            env.block.time.seconds(),
         )?;
     }
 
@@ -237,6 +447,8 @@ fn register_asset(
             reward_index: Decimal::zero(),
             pending_reward: Uint128::zero(),
             migration_params: None,
+            total_boost_amount: Uint128::zero(),
+            unbonding_period: 0,
         },
     )?;
 
@@ -246,8 +458,49 @@ fn register_asset(
     ]))
 }
 
+fn set_emergency(deps: DepsMut, info: MessageInfo, emergency: bool) -> StdResult<Response> {
+    let mut config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    config.emergency = emergency;
+    store_config(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "set_emergency"),
+        ("emergency", &emergency.to_string()),
+    ]))
+}
+
+fn update_unbonding_period(
+    deps: DepsMut,
+    info: MessageInfo,
+    asset_info: AssetInfo,
+    unbonding_period: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+
+    if config.owner != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, &asset_key)?;
+    pool_info.unbonding_period = unbonding_period;
+    store_pool_info(deps.storage, &asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "update_unbonding_period"),
+        ("asset_info", &asset_info.to_string()),
+        ("unbonding_period", &unbonding_period.to_string()),
+    ]))
+}
+
 fn deprecate_staking_token(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     asset_info: AssetInfo,
     new_staking_token: Addr,
@@ -268,8 +521,18 @@ fn deprecate_staking_token(
     }
 
     let deprecated_token_addr = deps.api.addr_humanize(&pool_info.staking_token)?;
+    checkpoint_total_bond(deps.storage, &asset_key, env.block.height)?;
+
+    // stream campaigns to the bonded amount before it is reset
+    update_pool_campaigns(
+        deps.storage,
+        &asset_key,
+        pool_info.total_effective_bond_amount(),
+        env.block.time.seconds(),
+    )?;
 
     pool_info.total_bond_amount = Uint128::zero();
+    pool_info.total_boost_amount = Uint128::zero();
     pool_info.migration_params = Some(MigrationParams {
         index_snapshot: pool_info.reward_index,
         deprecated_staking_token: pool_info.staking_token,
@@ -290,9 +553,13 @@ fn deprecate_staking_token(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::PendingAdmin {} => to_binary(&query_pending_admin(deps.storage, deps.api)?),
+        QueryMsg::Timelock { start_after, limit } => {
+            to_binary(&query_timelock(deps.storage, start_after, limit)?)
+        }
         QueryMsg::PoolInfo { asset_info } => to_binary(&query_pool_info(deps, asset_info)?),
         QueryMsg::RewardsPerSec { asset_info } => {
             to_binary(&query_rewards_per_sec(deps, asset_info)?)
@@ -313,6 +580,31 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order,
         )?),
+        QueryMsg::Campaign { campaign_id } => to_binary(&query_campaign(deps, campaign_id)?),
+        QueryMsg::Campaigns { asset_info } => to_binary(&query_campaigns(deps, asset_info)?),
+        QueryMsg::CampaignRewardAssets {} => to_binary(&query_campaign_reward_assets(deps)?),
+        QueryMsg::CampaignRewards {
+            staker_addr,
+            asset_info,
+        } => to_binary(&query_campaign_rewards(deps, env, staker_addr, asset_info)?),
+        QueryMsg::Claims { staker_addr } => to_binary(&query_claims(deps, staker_addr)?),
+        QueryMsg::BondAt {
+            staker_addr,
+            asset_info,
+            height,
+        } => to_binary(&query_bond_at(deps, staker_addr, asset_info, height)?),
+        QueryMsg::TotalBondAt { asset_info, height } => {
+            to_binary(&query_total_bond_at(deps, asset_info, height)?)
+        }
+        QueryMsg::PoolApr { asset_info } => to_binary(&query_pool_apr(deps, asset_info)?),
+        QueryMsg::RewardRecipient { staker_addr } => {
+            to_binary(&query_reward_recipient(deps, staker_addr)?)
+        }
+        QueryMsg::LockTiers {} => to_binary(&query_lock_tiers(deps)?),
+        QueryMsg::Locks {
+            staker_addr,
+            asset_info,
+        } => to_binary(&query_locks(deps, staker_addr, asset_info)?),
     }
 }
 
@@ -324,6 +616,11 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         oracle_addr: deps.api.addr_humanize(&state.oracle_addr)?,
         factory_addr: deps.api.addr_humanize(&state.factory_addr)?,
         base_denom: state.base_denom,
+        router_addr: state
+            .router_addr
+            .map(|router_addr| deps.api.addr_humanize(&router_addr))
+            .transpose()?,
+        emergency: state.emergency,
     };
 
     Ok(resp)
@@ -346,6 +643,7 @@ pub fn query_pool_info(deps: Deps, asset_info: AssetInfo) -> StdResult<PoolInfoR
         migration_index_snapshot: pool_info
             .migration_params
             .map(|params| params.index_snapshot),
+        unbonding_period: pool_info.unbonding_period,
     })
 }
 
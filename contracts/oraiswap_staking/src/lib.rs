@@ -1,4 +1,8 @@
+mod apr;
+mod campaign;
+mod compound;
 pub mod contract;
+mod lock;
 mod migration;
 mod rewards;
 mod staking;
@@ -0,0 +1,388 @@
+use crate::campaign::settle_campaign_rewards;
+use crate::rewards::before_share_change;
+use crate::staking::checkpoint_bond;
+use crate::state::{
+    locks_store, next_lock_id, read_config, read_is_migrated, read_lock_config, read_locks,
+    read_pool_info, rewards_read, rewards_store, store_lock_config, store_pool_info, Config, Lock,
+    LockConfig, PoolInfo, RewardInfo,
+};
+use cosmwasm_std::{
+    attr, to_binary, Addr, CanonicalAddr, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+    StdError, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use oraiswap::asset::AssetInfo;
+use oraiswap::staking::{LockResponse, LockTier, LockTiersResponse, LocksResponse};
+
+pub fn update_lock_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<LockTier>,
+    early_unlock_penalty: Decimal,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if tiers
+        .iter()
+        .any(|tier| tier.duration == 0 || tier.boost < Decimal::one())
+    {
+        return Err(StdError::generic_err(
+            "Lock duration must be positive and boost at least one",
+        ));
+    }
+
+    if early_unlock_penalty > Decimal::one() {
+        return Err(StdError::generic_err(
+            "Early unlock penalty must not exceed one",
+        ));
+    }
+
+    store_lock_config(
+        deps.storage,
+        &LockConfig {
+            tiers,
+            early_unlock_penalty,
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("action", "update_lock_tiers"))
+}
+
+pub fn lock(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+    amount: Uint128,
+    duration: u64,
+) -> StdResult<Response> {
+    let lock_config = read_lock_config(deps.storage)?;
+    let tier = match lock_config
+        .tiers
+        .into_iter()
+        .find(|tier| tier.duration == duration)
+    {
+        Some(tier) => tier,
+        None => return Err(StdError::generic_err("Invalid lock duration")),
+    };
+
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Cannot lock zero amount"));
+    }
+
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = &asset_info.to_vec(deps.api)?;
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, asset_key)?;
+    let mut reward_info: RewardInfo = rewards_read(deps.storage, &staker_addr_raw)
+        .may_load(asset_key)?
+        .ok_or_else(|| StdError::generic_err("Cannot lock more than unlocked bond amount"))?;
+
+    if pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, asset_key, &staker_addr_raw)
+    {
+        return Err(StdError::generic_err(
+            "The LP token for this asset has been deprecated, withdraw all your deprecated tokens to migrate your position",
+        ));
+    }
+
+    // Withdraw reward to pending reward; before changing share
+    let now = env.block.time.seconds();
+    before_share_change(pool_info.reward_index, &mut reward_info)?;
+    settle_campaign_rewards(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        reward_info.effective_bond_amount(),
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    let locked_amount = release_expired_locks(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        now,
+        &mut reward_info,
+        &mut pool_info,
+        true,
+    )?;
+    if locked_amount + amount > reward_info.bond_amount {
+        return Err(StdError::generic_err(
+            "Cannot lock more than unlocked bond amount",
+        ));
+    }
+
+    let lock = Lock {
+        lock_id: next_lock_id(deps.storage)?,
+        amount,
+        boost_amount: amount * (tier.boost - Decimal::one()),
+        unlock_time: now + duration,
+    };
+
+    reward_info.boost_amount += lock.boost_amount;
+    pool_info.total_boost_amount += lock.boost_amount;
+
+    let mut locks = read_locks(deps.storage, &staker_addr_raw, asset_key)?;
+    locks.push(lock.clone());
+    locks_store(deps.storage, &staker_addr_raw).save(asset_key, &locks)?;
+    rewards_store(deps.storage, &staker_addr_raw).save(asset_key, &reward_info)?;
+    store_pool_info(deps.storage, asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "lock"),
+        ("staker_addr", staker_addr.as_str()),
+        ("asset_info", &asset_info.to_string()),
+        ("lock_id", &lock.lock_id.to_string()),
+        ("amount", &amount.to_string()),
+        ("boost_amount", &lock.boost_amount.to_string()),
+        ("unlock_time", &lock.unlock_time.to_string()),
+    ]))
+}
+
+pub fn unlock(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+    lock_id: u64,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    let lock_config = read_lock_config(deps.storage)?;
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = &asset_info.to_vec(deps.api)?;
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, asset_key)?;
+    let mut reward_info: RewardInfo =
+        rewards_read(deps.storage, &staker_addr_raw).load(asset_key)?;
+
+    // the penalty is deducted from the bond amount
+    checkpoint_bond(deps.storage, &staker_addr_raw, asset_key, env.block.height)?;
+
+    let mut locks = read_locks(deps.storage, &staker_addr_raw, asset_key)?;
+    let lock = match locks.iter().position(|lock| lock.lock_id == lock_id) {
+        Some(index) => locks.remove(index),
+        None => return Err(StdError::generic_err("Lock not found")),
+    };
+
+    // if the lp token was migrated, the position is not counted in the pool anymore
+    let should_migrate = pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, asset_key, &staker_addr_raw);
+    let (pool_index, staking_token) = match &pool_info.migration_params {
+        Some(params) if should_migrate => (
+            params.index_snapshot,
+            params.deprecated_staking_token.clone(),
+        ),
+        _ => (pool_info.reward_index, pool_info.staking_token.clone()),
+    };
+
+    // Withdraw reward to pending reward; before changing share
+    let now = env.block.time.seconds();
+    before_share_change(pool_index, &mut reward_info)?;
+    settle_campaign_rewards(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        if should_migrate {
+            Uint128::zero()
+        } else {
+            reward_info.effective_bond_amount()
+        },
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    // unlocking before the unlock time forfeits part of the locked amount
+    let penalty_amount = if now < lock.unlock_time {
+        lock.amount * lock_config.early_unlock_penalty
+    } else {
+        Uint128::zero()
+    };
+
+    reward_info.boost_amount = reward_info.boost_amount.checked_sub(lock.boost_amount)?;
+    reward_info.bond_amount = reward_info.bond_amount.checked_sub(penalty_amount)?;
+    if !should_migrate {
+        pool_info.total_boost_amount = pool_info
+            .total_boost_amount
+            .checked_sub(lock.boost_amount)?;
+        pool_info.total_bond_amount = pool_info.total_bond_amount.checked_sub(penalty_amount)?;
+    }
+
+    if locks.is_empty() {
+        locks_store(deps.storage, &staker_addr_raw).remove(asset_key);
+    } else {
+        locks_store(deps.storage, &staker_addr_raw).save(asset_key, &locks)?;
+    }
+    release_expired_locks(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        now,
+        &mut reward_info,
+        &mut pool_info,
+        !should_migrate,
+    )?;
+    rewards_store(deps.storage, &staker_addr_raw).save(asset_key, &reward_info)?;
+    store_pool_info(deps.storage, asset_key, &pool_info)?;
+
+    let mut response = Response::new();
+    if !penalty_amount.is_zero() {
+        response = response.add_message(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&staking_token)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: deps.api.addr_humanize(&config.owner)?.to_string(),
+                amount: penalty_amount,
+            })?,
+            funds: vec![],
+        });
+    }
+
+    Ok(response.add_attributes([
+        attr("action", "unlock"),
+        attr("staker_addr", staker_addr.as_str()),
+        attr("asset_info", asset_info.to_string()),
+        attr("lock_id", lock_id.to_string()),
+        attr("amount", lock.amount.to_string()),
+        attr("penalty_amount", penalty_amount.to_string()),
+    ]))
+}
+
+/// anyone can release the expired locks of a staker, so their boost stops diluting the pool
+pub fn release_expired(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+) -> StdResult<Response> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = &asset_info.to_vec(deps.api)?;
+    let now = env.block.time.seconds();
+    if !read_locks(deps.storage, &staker_addr_raw, asset_key)?
+        .iter()
+        .any(|lock| lock.unlock_time <= now)
+    {
+        return Err(StdError::generic_err("No expired lock"));
+    }
+
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, asset_key)?;
+    let mut reward_info: RewardInfo =
+        rewards_read(deps.storage, &staker_addr_raw).load(asset_key)?;
+
+    // if the lp token was migrated, the position is not counted in the pool anymore
+    let should_migrate = pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, asset_key, &staker_addr_raw);
+    let pool_index = match &pool_info.migration_params {
+        Some(params) if should_migrate => params.index_snapshot,
+        _ => pool_info.reward_index,
+    };
+
+    // Withdraw reward to pending reward; before changing share
+    before_share_change(pool_index, &mut reward_info)?;
+    settle_campaign_rewards(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        if should_migrate {
+            Uint128::zero()
+        } else {
+            reward_info.effective_bond_amount()
+        },
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    release_expired_locks(
+        deps.storage,
+        &staker_addr_raw,
+        asset_key,
+        now,
+        &mut reward_info,
+        &mut pool_info,
+        !should_migrate,
+    )?;
+    rewards_store(deps.storage, &staker_addr_raw).save(asset_key, &reward_info)?;
+    store_pool_info(deps.storage, asset_key, &pool_info)?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "release_expired_locks"),
+        ("staker_addr", staker_addr.as_str()),
+        ("asset_info", &asset_info.to_string()),
+    ]))
+}
+
+/// remove the locks past their unlock time with their boost, returns the amount still locked.
+/// The reward must be withdrawn to pending reward before calling this
+pub fn release_expired_locks(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    now: u64,
+    reward_info: &mut RewardInfo,
+    pool_info: &mut PoolInfo,
+    update_pool: bool,
+) -> StdResult<Uint128> {
+    let (expired_locks, locks): (Vec<Lock>, Vec<Lock>) =
+        read_locks(storage, staker_addr, asset_key)?
+            .into_iter()
+            .partition(|lock| lock.unlock_time <= now);
+
+    if !expired_locks.is_empty() {
+        let expired_boost_amount: Uint128 =
+            expired_locks.iter().map(|lock| lock.boost_amount).sum();
+        reward_info.boost_amount = reward_info.boost_amount.checked_sub(expired_boost_amount)?;
+        if update_pool {
+            pool_info.total_boost_amount = pool_info
+                .total_boost_amount
+                .checked_sub(expired_boost_amount)?;
+        }
+
+        if locks.is_empty() {
+            locks_store(storage, staker_addr).remove(asset_key);
+        } else {
+            locks_store(storage, staker_addr).save(asset_key, &locks)?;
+        }
+    }
+
+    Ok(locks.iter().map(|lock| lock.amount).sum())
+}
+
+pub fn query_lock_tiers(deps: Deps) -> StdResult<LockTiersResponse> {
+    let lock_config = read_lock_config(deps.storage)?;
+    Ok(LockTiersResponse {
+        tiers: lock_config.tiers,
+        early_unlock_penalty: lock_config.early_unlock_penalty,
+    })
+}
+
+pub fn query_locks(
+    deps: Deps,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+) -> StdResult<LocksResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let (bond_amount, effective_bond_amount) = rewards_read(deps.storage, &staker_addr_raw)
+        .may_load(&asset_key)?
+        .map(|reward_info| (reward_info.bond_amount, reward_info.effective_bond_amount()))
+        .unwrap_or_default();
+
+    let locks = read_locks(deps.storage, &staker_addr_raw, &asset_key)?
+        .into_iter()
+        .map(|lock| LockResponse {
+            lock_id: lock.lock_id,
+            amount: lock.amount,
+            boost_amount: lock.boost_amount,
+            unlock_time: lock.unlock_time,
+        })
+        .collect();
+
+    Ok(LocksResponse {
+        staker_addr,
+        asset_info,
+        bond_amount,
+        effective_bond_amount,
+        locks,
+    })
+}
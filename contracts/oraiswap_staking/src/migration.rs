@@ -64,6 +64,7 @@ pub fn migrate_rewards_store(
                 bond_amount: reward_info.bond_amount,
                 pending_reward: reward_info.pending_reward,
                 pending_withdraw: vec![],
+                boost_amount: Uint128::zero(),
             };
             rewards_store(store, &staker_addr).save(&asset_key, &new_reward_info)?;
         }
@@ -1,8 +1,10 @@
 use std::convert::TryFrom;
 
+use crate::campaign::{settle_campaign_rewards, withdraw_campaign_rewards};
+use crate::lock::release_expired_locks;
 use crate::state::{
-    read_config, read_is_migrated, read_pool_info, read_rewards_per_sec, rewards_read,
-    rewards_store, stakers_read, store_pool_info, PoolInfo, RewardInfo,
+    read_config, read_is_migrated, read_pool_info, read_reward_recipient, read_rewards_per_sec,
+    rewards_read, rewards_store, stakers_read, store_pool_info, PoolInfo, RewardInfo,
 };
 use cosmwasm_std::{
     Addr, Api, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response,
@@ -38,12 +40,14 @@ pub fn deposit_reward(
         let mut normal_reward = asset.amount;
 
         // normal rewards are array of Assets
-        if pool_info.total_bond_amount.is_zero() {
+        // rewards are shared by the effective bond, locked bonds are boosted
+        let total_effective_bond_amount = pool_info.total_effective_bond_amount();
+        if total_effective_bond_amount.is_zero() {
             pool_info.pending_reward += normal_reward;
         } else {
             normal_reward += pool_info.pending_reward;
             let normal_reward_per_bond =
-                Decimal::from_ratio(normal_reward, pool_info.total_bond_amount);
+                Decimal::from_ratio(normal_reward, total_effective_bond_amount);
             pool_info.reward_index = pool_info.reward_index + normal_reward_per_bond;
             pool_info.pending_reward = Uint128::zero();
         }
@@ -62,21 +66,31 @@ pub fn deposit_reward(
 // withdraw all rewards or single reward depending on asset_token
 pub fn withdraw_reward(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     asset_info: Option<AssetInfo>,
 ) -> StdResult<Response> {
     let staker_addr = deps.api.addr_canonicalize(info.sender.as_str())?;
     let asset_key = asset_info.map_or(None, |a| a.to_vec(deps.api).ok());
 
-    let reward_assets = process_reward_assets(deps.storage, &staker_addr, &asset_key, true)?;
-
+    let reward_assets = process_reward_assets(
+        deps.storage,
+        &staker_addr,
+        &asset_key,
+        true,
+        env.block.time.seconds(),
+    )?;
+
+    // rewards go to the recipient set by the staker
+    let reward_recipient = deps
+        .api
+        .addr_humanize(&read_reward_recipient(deps.storage, &staker_addr)?)?;
     let messages = reward_assets
         .into_iter()
         .map(|ra| {
             Ok(ra
                 .to_normal(deps.api)?
-                .into_msg(None, &deps.querier, info.sender.clone())?)
+                .into_msg(None, &deps.querier, reward_recipient.clone())?)
         })
         .collect::<StdResult<Vec<CosmosMsg>>>()?;
 
@@ -87,7 +101,7 @@ pub fn withdraw_reward(
 
 pub fn withdraw_reward_others(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     staker_addrs: Vec<Addr>,
     asset_info: Option<AssetInfo>,
@@ -105,7 +119,13 @@ pub fn withdraw_reward_others(
     // withdraw reward for each staker
     for staker_addr in staker_addrs {
         let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
-        process_reward_assets(deps.storage, &staker_addr_raw, &asset_key.clone(), false)?;
+        process_reward_assets(
+            deps.storage,
+            &staker_addr_raw,
+            &asset_key.clone(),
+            false,
+            env.block.time.seconds(),
+        )?;
     }
 
     Ok(Response::new().add_attribute("action", "withdraw_reward_others"))
@@ -126,11 +146,13 @@ fn update_reward_assets_amount(reward_assets: &mut Vec<AssetRaw>, rw: AssetRaw,
 }
 
 // this function will return total asset to reward, then later can be updated as pending_withdraw, or send to client
+// expired locks are released on the way, so their boost stops accruing
 pub fn process_reward_assets(
     storage: &mut dyn Storage,
     staker_addr: &CanonicalAddr,
     asset_key: &Option<Vec<u8>>,
     do_withdraw: bool,
+    now: u64,
 ) -> StdResult<Vec<AssetRaw>> {
     let rewards_bucket = rewards_read(storage, staker_addr);
 
@@ -153,19 +175,41 @@ pub fn process_reward_assets(
 
     for reward_pair in reward_pairs {
         let (asset_key, mut reward_info) = reward_pair;
-        let pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
+        let mut pool_info: PoolInfo = read_pool_info(storage, &asset_key)?;
 
         // Withdraw reward to pending reward
         // if the lp token was migrated, and the user did not close their position yet, cap the reward at the snapshot
-        let pool_index = if pool_info.migration_params.is_some()
-            && !read_is_migrated(storage, &asset_key, staker_addr)
-        {
-            pool_info.migration_params.unwrap().index_snapshot
-        } else {
-            pool_info.reward_index
+        let should_migrate = pool_info.migration_params.is_some()
+            && !read_is_migrated(storage, &asset_key, staker_addr);
+        let pool_index = match &pool_info.migration_params {
+            Some(params) if should_migrate => params.index_snapshot,
+            _ => pool_info.reward_index,
         };
 
         before_share_change(pool_index, &mut reward_info)?;
+        settle_campaign_rewards(
+            storage,
+            staker_addr,
+            &asset_key,
+            if should_migrate {
+                Uint128::zero()
+            } else {
+                reward_info.effective_bond_amount()
+            },
+            pool_info.total_effective_bond_amount(),
+            now,
+        )?;
+
+        release_expired_locks(
+            storage,
+            staker_addr,
+            &asset_key,
+            now,
+            &mut reward_info,
+            &mut pool_info,
+            !should_migrate,
+        )?;
+        store_pool_info(storage, &asset_key, &pool_info)?;
 
         if !reward_info.pending_reward.is_zero() {
             // calculate and accumulate the reward amount
@@ -189,9 +233,19 @@ pub fn process_reward_assets(
             reward_info.pending_reward = Uint128::zero();
         }
 
-        // if withdraw, then update reward_assets to create MsgSend
+        // if withdraw, then update reward_assets to create MsgSend, including the campaign rewards
         if do_withdraw {
-            for rw in reward_info.pending_withdraw {
+            let campaign_rewards = withdraw_campaign_rewards(
+                storage,
+                staker_addr,
+                &asset_key,
+                reward_info.bond_amount.is_zero(),
+            )?;
+            for rw in reward_info
+                .pending_withdraw
+                .into_iter()
+                .chain(campaign_rewards)
+            {
                 update_reward_assets_amount(&mut reward_assets, rw.clone(), rw.amount);
             }
             reward_info.pending_withdraw = vec![];
@@ -208,10 +262,11 @@ pub fn process_reward_assets(
     Ok(reward_assets)
 }
 
-// withdraw reward to pending reward
+// withdraw reward to pending reward, the share is the effective bond amount including lock boosts
 pub fn before_share_change(pool_index: Decimal, reward_info: &mut RewardInfo) -> StdResult<()> {
-    let pending_reward = (reward_info.bond_amount * pool_index)
-        .checked_sub(reward_info.bond_amount * reward_info.index)?;
+    let effective_bond_amount = reward_info.effective_bond_amount();
+    let pending_reward = (effective_bond_amount * pool_index)
+        .checked_sub(effective_bond_amount * reward_info.index)?;
 
     reward_info.index = pool_index;
     reward_info.pending_reward += pending_reward;
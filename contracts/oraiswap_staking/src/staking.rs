@@ -1,32 +1,48 @@
+use crate::campaign::{settle_campaign_rewards, withdraw_campaign_rewards};
+use crate::lock::release_expired_locks;
 use crate::rewards::before_share_change;
 use crate::state::{
-    read_config, read_is_migrated, read_pool_info, rewards_read, rewards_store, stakers_store,
-    store_is_migrated, store_pool_info, Config, PoolInfo, RewardInfo,
+    campaign_rewards_store, locks_store, read_bond_changelog, read_claims, read_config,
+    read_is_migrated, read_lock_config, read_locks, read_pool_info, read_reward_recipient,
+    read_total_bond_changelog, rewards_read, rewards_store, stakers_store, store_bond_changelog,
+    store_claims, store_is_migrated, store_pool_info, store_reward_recipient,
+    store_total_bond_changelog, Claim, Config, PoolInfo, RewardInfo,
 };
 use cosmwasm_std::{
-    attr, to_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
-    Response, StdError, StdResult, Storage, Uint128, WasmMsg,
+    attr, to_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Storage, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 use oraiswap::asset::{Asset, AssetInfo, PairInfo};
 use oraiswap::oracle::OracleContract;
 use oraiswap::pair::ExecuteMsg as PairExecuteMsg;
 use oraiswap::querier::{query_pair_info, query_token_balance};
-use oraiswap::staking::ExecuteMsg;
+use oraiswap::staking::{
+    BondAtResponse, Claim as ClaimResponse, ClaimsResponse, ExecuteMsg, RewardRecipientResponse,
+    TotalBondAtResponse,
+};
 
 pub fn bond(
     deps: DepsMut,
+    env: Env,
     staker_addr: Addr,
     asset_info: AssetInfo,
     amount: Uint128,
 ) -> StdResult<Response> {
     let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    checkpoint_bond(
+        deps.storage,
+        &staker_addr_raw,
+        &asset_info.to_vec(deps.api)?,
+        env.block.height,
+    )?;
     _increase_bond_amount(
         deps.storage,
         deps.api,
         &staker_addr_raw,
         &asset_info,
         amount,
+        env.block.time.seconds(),
     )?;
 
     Ok(Response::new().add_attributes([
@@ -39,35 +55,60 @@ pub fn bond(
 
 pub fn unbond(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     staker_addr: Addr,
     asset_info: AssetInfo,
     amount: Uint128,
 ) -> StdResult<Response> {
     let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
-    let (staking_token, reward_assets) = _decrease_bond_amount(
+    checkpoint_bond(
+        deps.storage,
+        &staker_addr_raw,
+        &asset_info.to_vec(deps.api)?,
+        env.block.height,
+    )?;
+    let (staking_token, reward_assets, unbonding_period) = _decrease_bond_amount(
         deps.storage,
         deps.api,
         &staker_addr_raw,
         &asset_info,
         amount,
+        env.block.time.seconds(),
     )?;
     let staking_token_addr = deps.api.addr_humanize(&staking_token)?;
-    let mut messages = vec![WasmMsg::Execute {
-        contract_addr: staking_token_addr.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Transfer {
-            recipient: staker_addr.to_string(),
+    let mut messages: Vec<CosmosMsg> = vec![];
+    if unbonding_period == 0 {
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: staking_token_addr.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: staker_addr.to_string(),
+                    amount,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    } else {
+        // the amount waits in a claim without earning rewards
+        let mut claims = read_claims(deps.storage, &staker_addr_raw)?;
+        claims.push(Claim {
+            asset_info: asset_info.to_raw(deps.api)?,
+            staking_token,
             amount,
-        })?,
-        funds: vec![],
+            release_at: env.block.time.seconds() + unbonding_period,
+        });
+        store_claims(deps.storage, &staker_addr_raw, claims)?;
     }
-    .into()];
 
     // withdraw pending_withdraw assets (accumulated when changing reward_per_sec)
+    let reward_recipient = deps
+        .api
+        .addr_humanize(&read_reward_recipient(deps.storage, &staker_addr_raw)?)?;
     messages.extend(
         reward_assets
             .into_iter()
-            .map(|ra| Ok(ra.into_msg(None, &deps.querier, staker_addr.clone())?))
+            .map(|ra| ra.into_msg(None, &deps.querier, reward_recipient.clone()))
             .collect::<StdResult<Vec<CosmosMsg>>>()?,
     );
 
@@ -80,6 +121,330 @@ pub fn unbond(
     ]))
 }
 
+pub fn emergency_unbond(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+) -> StdResult<Response> {
+    let config: Config = read_config(deps.storage)?;
+    if !config.emergency {
+        return Err(StdError::generic_err("Emergency unbond is not enabled"));
+    }
+
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = &asset_info.to_vec(deps.api)?;
+    let mut pool_info: PoolInfo = read_pool_info(deps.storage, asset_key)?;
+    let reward_info: RewardInfo = rewards_read(deps.storage, &staker_addr_raw)
+        .may_load(asset_key)?
+        .ok_or_else(|| StdError::generic_err("No bond amount to unbond"))?;
+    checkpoint_bond(deps.storage, &staker_addr_raw, asset_key, env.block.height)?;
+
+    // the position of the deprecated staking token is not counted in the pool
+    let should_migrate = pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, asset_key, &staker_addr_raw);
+
+    // the unbonding period is skipped so that stakers can always exit during the emergency,
+    // the locks not expired yet pay the early unlock penalty
+    let lock_config = read_lock_config(deps.storage)?;
+    let penalty_amount: Uint128 = read_locks(deps.storage, &staker_addr_raw, asset_key)?
+        .iter()
+        .filter(|lock| lock.unlock_time > env.block.time.seconds())
+        .map(|lock| lock.amount * lock_config.early_unlock_penalty)
+        .sum();
+    let unbond_amount = reward_info.bond_amount.checked_sub(penalty_amount)?;
+
+    let staking_token = match &pool_info.migration_params {
+        Some(params) if should_migrate => {
+            store_is_migrated(deps.storage, asset_key, &staker_addr_raw)?;
+            params.deprecated_staking_token.clone()
+        }
+        _ => {
+            pool_info.total_bond_amount = pool_info
+                .total_bond_amount
+                .saturating_sub(reward_info.bond_amount);
+            pool_info.total_boost_amount = pool_info
+                .total_boost_amount
+                .saturating_sub(reward_info.boost_amount);
+            pool_info.staking_token.clone()
+        }
+    };
+
+    // rewards are not computed so that a broken reward state can not block the exit,
+    // the settled pending reward goes back to the pool and the rest is forfeited
+    let forfeited_reward = reward_info.pending_reward;
+    pool_info.pending_reward += forfeited_reward;
+    store_pool_info(deps.storage, asset_key, &pool_info)?;
+
+    rewards_store(deps.storage, &staker_addr_raw).remove(asset_key);
+    locks_store(deps.storage, &staker_addr_raw).remove(asset_key);
+    campaign_rewards_store(deps.storage, &staker_addr_raw).remove(asset_key);
+    stakers_store(deps.storage, asset_key).remove(&staker_addr_raw);
+
+    let staking_token_addr = deps.api.addr_humanize(&staking_token)?;
+    let owner_addr = deps.api.addr_humanize(&config.owner)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (recipient, amount) in [(&staker_addr, unbond_amount), (&owner_addr, penalty_amount)] {
+        if !amount.is_zero() {
+            messages.push(
+                WasmMsg::Execute {
+                    contract_addr: staking_token_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        attr("action", "emergency_unbond"),
+        attr("staker_addr", staker_addr.as_str()),
+        attr("asset_info", asset_info.to_string()),
+        attr("amount", unbond_amount.to_string()),
+        attr("penalty_amount", penalty_amount.to_string()),
+        attr("staking_token", staking_token_addr.as_str()),
+        attr("forfeited_reward", forfeited_reward.to_string()),
+    ]))
+}
+
+/// keep the bond amounts before they are changed at this height
+pub fn checkpoint_bond(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    asset_key: &[u8],
+    height: u64,
+) -> StdResult<()> {
+    let bond_amount = rewards_read(storage, staker_addr)
+        .may_load(asset_key)?
+        .map(|reward_info| reward_info.bond_amount)
+        .unwrap_or_default();
+    store_bond_changelog(storage, asset_key, staker_addr, height, bond_amount)?;
+    checkpoint_total_bond(storage, asset_key, height)
+}
+
+pub fn checkpoint_total_bond(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    height: u64,
+) -> StdResult<()> {
+    let pool_info: PoolInfo = read_pool_info(storage, asset_key)?;
+    store_total_bond_changelog(storage, asset_key, height, pool_info.total_bond_amount)
+}
+
+pub fn query_bond_at(
+    deps: Deps,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+    height: u64,
+) -> StdResult<BondAtResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let asset_key = asset_info.to_vec(deps.api)?;
+
+    // not changed since the height, the current value applies
+    let bond_amount = match read_bond_changelog(deps.storage, &asset_key, &staker_addr_raw, height)?
+    {
+        Some(bond_amount) => bond_amount,
+        None => rewards_read(deps.storage, &staker_addr_raw)
+            .may_load(&asset_key)?
+            .map(|reward_info| reward_info.bond_amount)
+            .unwrap_or_default(),
+    };
+
+    Ok(BondAtResponse {
+        staker_addr,
+        asset_info,
+        height,
+        bond_amount,
+    })
+}
+
+pub fn query_total_bond_at(
+    deps: Deps,
+    asset_info: AssetInfo,
+    height: u64,
+) -> StdResult<TotalBondAtResponse> {
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let total_bond_amount = match read_total_bond_changelog(deps.storage, &asset_key, height)? {
+        Some(total_bond_amount) => total_bond_amount,
+        None => read_pool_info(deps.storage, &asset_key)?.total_bond_amount,
+    };
+
+    Ok(TotalBondAtResponse {
+        asset_info,
+        height,
+        total_bond_amount,
+    })
+}
+
+pub fn set_reward_recipient(
+    deps: DepsMut,
+    staker_addr: Addr,
+    recipient: Addr,
+) -> StdResult<Response> {
+    store_reward_recipient(
+        deps.storage,
+        &deps.api.addr_canonicalize(staker_addr.as_str())?,
+        &deps.api.addr_canonicalize(recipient.as_str())?,
+    )?;
+
+    Ok(Response::new().add_attributes([
+        ("action", "set_reward_recipient"),
+        ("staker_addr", staker_addr.as_str()),
+        ("recipient", recipient.as_str()),
+    ]))
+}
+
+pub fn transfer_position(
+    deps: DepsMut,
+    env: Env,
+    staker_addr: Addr,
+    asset_info: AssetInfo,
+    amount: Uint128,
+    to: Addr,
+) -> StdResult<Response> {
+    if amount.is_zero() {
+        return Err(StdError::generic_err("Cannot transfer zero amount"));
+    }
+
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let to_raw: CanonicalAddr = deps.api.addr_canonicalize(to.as_str())?;
+    if staker_addr_raw == to_raw {
+        return Err(StdError::generic_err("Cannot transfer position to self"));
+    }
+
+    // the deprecated lp token is not counted in the pool, it must be unbonded instead
+    let asset_key = asset_info.to_vec(deps.api)?;
+    let pool_info: PoolInfo = read_pool_info(deps.storage, &asset_key)?;
+    if pool_info.migration_params.is_some()
+        && !read_is_migrated(deps.storage, &asset_key, &staker_addr_raw)
+    {
+        return Err(StdError::generic_err(
+            "The LP token for this asset has been deprecated, withdraw all your deprecated tokens to migrate your position",
+        ));
+    }
+
+    // the pool total is unchanged, both positions are settled at the current index
+    checkpoint_bond(deps.storage, &staker_addr_raw, &asset_key, env.block.height)?;
+    checkpoint_bond(deps.storage, &to_raw, &asset_key, env.block.height)?;
+    let (_, reward_assets, _) = _decrease_bond_amount(
+        deps.storage,
+        deps.api,
+        &staker_addr_raw,
+        &asset_info,
+        amount,
+        env.block.time.seconds(),
+    )?;
+    _increase_bond_amount(
+        deps.storage,
+        deps.api,
+        &to_raw,
+        &asset_info,
+        amount,
+        env.block.time.seconds(),
+    )?;
+
+    // the rewards of a closed position are withdrawn
+    let reward_recipient = deps
+        .api
+        .addr_humanize(&read_reward_recipient(deps.storage, &staker_addr_raw)?)?;
+    let messages = reward_assets
+        .into_iter()
+        .map(|ra| ra.into_msg(None, &deps.querier, reward_recipient.clone()))
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "transfer_position"),
+        ("staker_addr", staker_addr.as_str()),
+        ("asset_info", &asset_info.to_string()),
+        ("amount", &amount.to_string()),
+        ("to", to.as_str()),
+    ]))
+}
+
+pub fn query_reward_recipient(deps: Deps, staker_addr: Addr) -> StdResult<RewardRecipientResponse> {
+    let staker_addr_raw = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    Ok(RewardRecipientResponse {
+        recipient: deps
+            .api
+            .addr_humanize(&read_reward_recipient(deps.storage, &staker_addr_raw)?)?,
+        staker_addr,
+    })
+}
+
+pub fn claim_unbonded(deps: DepsMut, env: Env, staker_addr: Addr) -> StdResult<Response> {
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let (matured_claims, claims): (Vec<Claim>, Vec<Claim>) =
+        read_claims(deps.storage, &staker_addr_raw)?
+            .into_iter()
+            .partition(|claim| claim.release_at <= env.block.time.seconds());
+
+    if matured_claims.is_empty() {
+        return Err(StdError::generic_err("No unbonded amount to claim"));
+    }
+    store_claims(deps.storage, &staker_addr_raw, claims)?;
+
+    // one transfer for each staking token
+    let mut amounts: Vec<(CanonicalAddr, Uint128)> = vec![];
+    for claim in matured_claims {
+        match amounts
+            .iter_mut()
+            .find(|(staking_token, _)| staking_token.eq(&claim.staking_token))
+        {
+            Some((_, amount)) => *amount += claim.amount,
+            None => amounts.push((claim.staking_token, claim.amount)),
+        }
+    }
+
+    let messages = amounts
+        .iter()
+        .map(|(staking_token, amount)| {
+            Ok(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(staking_token)?.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: staker_addr.to_string(),
+                    amount: *amount,
+                })?,
+                funds: vec![],
+            }
+            .into())
+        })
+        .collect::<StdResult<Vec<CosmosMsg>>>()?;
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        ("action", "claim_unbonded"),
+        ("staker_addr", staker_addr.as_str()),
+        (
+            "amount",
+            &amounts
+                .iter()
+                .map(|(_, amount)| *amount)
+                .sum::<Uint128>()
+                .to_string(),
+        ),
+    ]))
+}
+
+pub fn query_claims(deps: Deps, staker_addr: Addr) -> StdResult<ClaimsResponse> {
+    let staker_addr_raw: CanonicalAddr = deps.api.addr_canonicalize(staker_addr.as_str())?;
+    let claims = read_claims(deps.storage, &staker_addr_raw)?
+        .into_iter()
+        .map(|claim| {
+            Ok(ClaimResponse {
+                asset_info: claim.asset_info.to_normal(deps.api)?,
+                staking_token: deps.api.addr_humanize(&claim.staking_token)?,
+                amount: claim.amount,
+                release_at: claim.release_at,
+            })
+        })
+        .collect::<StdResult<Vec<ClaimResponse>>>()?;
+
+    Ok(ClaimsResponse { claims })
+}
+
 pub fn update_list_stakers(
     deps: DepsMut,
     _env: Env,
@@ -113,47 +478,31 @@ pub fn auto_stake(
     let config: Config = read_config(deps.storage)?;
     let factory_addr = deps.api.addr_humanize(&config.factory_addr)?;
 
-    let mut native_asset_op: Option<Asset> = None;
-    let mut token_info_op: Option<(Addr, Uint128)> = None;
-    for asset in assets.iter() {
-        match asset.info.clone() {
-            AssetInfo::NativeToken { .. } => {
-                asset.assert_sent_native_token_balance(&info)?;
-                native_asset_op = Some(asset.clone())
-            }
-            AssetInfo::Token { contract_addr } => {
-                token_info_op = Some((contract_addr, asset.amount))
-            }
-        }
-    }
-
-    // will fail if one of them is missing
-    let native_asset: Asset = match native_asset_op {
-        Some(v) => v,
-        None => return Err(StdError::generic_err("Missing native asset")),
-    };
-    let (token_addr, token_amount) = match token_info_op {
-        Some(v) => v,
-        None => return Err(StdError::generic_err("Missing token asset")),
-    };
-
     // query pair info to obtain pair contract address
     let asset_infos: [AssetInfo; 2] = [assets[0].info.clone(), assets[1].info.clone()];
     let oraiswap_pair: PairInfo = query_pair_info(&deps.querier, factory_addr, &asset_infos)?;
 
-    // assert the token and lp token match with pool info
-    let pool_info: PoolInfo = read_pool_info(
-        deps.storage,
-        &deps.api.addr_canonicalize(token_addr.as_str())?,
-    )?;
-
-    if pool_info.staking_token
-        != deps
-            .api
-            .addr_canonicalize(oraiswap_pair.liquidity_token.as_str())?
-    {
-        return Err(StdError::generic_err("Invalid staking token"));
-    }
+    // the pool can be registered with one of the pair assets or the lp token,
+    // find the one staking the pair lp token
+    let staking_token = deps
+        .api
+        .addr_canonicalize(oraiswap_pair.liquidity_token.as_str())?;
+    let asset_info = [
+        asset_infos[0].clone(),
+        asset_infos[1].clone(),
+        AssetInfo::Token {
+            contract_addr: oraiswap_pair.liquidity_token.clone(),
+        },
+    ]
+    .into_iter()
+    .find(|asset_info| {
+        asset_info
+            .to_vec(deps.api)
+            .and_then(|asset_key| read_pool_info(deps.storage, &asset_key))
+            .map(|pool_info: PoolInfo| pool_info.staking_token == staking_token)
+            .unwrap_or(false)
+    })
+    .ok_or_else(|| StdError::generic_err("Invalid staking token"))?;
 
     // get current lp token amount to later compute the recived amount
     let prev_staking_token_amount = query_token_balance(
@@ -164,74 +513,97 @@ pub fn auto_stake(
 
     let oracle_contract = OracleContract(oraiswap_pair.oracle_addr);
 
-    // compute tax
-    let tax_amount: Uint128 = native_asset.compute_tax(&oracle_contract, &deps.querier)?;
-
-    // 1. Transfer token asset to staking contract
-    // 2. Increase allowance of token for pair contract
-    // 3. Provide liquidity
+    // 1. Transfer token assets to staking contract
+    // 2. Increase allowance of tokens for pair contract
+    // 3. Provide liquidity, native assets net of tax
     // 4. Execute staking hook, will stake in the name of the sender
-    Ok(Response::new()
-        .add_messages(vec![
-            WasmMsg::Execute {
-                contract_addr: token_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                    owner: info.sender.to_string(),
-                    recipient: env.contract.address.to_string(),
-                    amount: token_amount,
-                })?,
-                funds: vec![],
-            },
-            WasmMsg::Execute {
-                contract_addr: token_addr.to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
-                    spender: oraiswap_pair.contract_addr.to_string(),
-                    amount: token_amount,
-                    expires: None,
-                })?,
-                funds: vec![],
-            },
-            WasmMsg::Execute {
-                contract_addr: oraiswap_pair.contract_addr.to_string(),
-                msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
-                    assets: [
-                        Asset {
-                            amount: native_asset.amount.checked_sub(tax_amount)?,
-                            info: native_asset.info.clone(),
-                        },
-                        Asset {
-                            amount: token_amount,
-                            info: AssetInfo::Token {
-                                contract_addr: token_addr.clone(),
-                            },
-                        },
-                    ],
-                    slippage_tolerance,
-                    receiver: None,
-                })?,
-                funds: vec![Coin {
-                    denom: native_asset.info.to_string(),
-                    amount: native_asset.amount.checked_sub(tax_amount)?,
-                }],
-            },
-            WasmMsg::Execute {
-                contract_addr: env.contract.address.to_string(),
-                msg: to_binary(&ExecuteMsg::AutoStakeHook {
-                    asset_info: AssetInfo::Token {
-                        contract_addr: token_addr.clone(),
-                    },
-                    staking_token: oraiswap_pair.liquidity_token,
-                    staker_addr: info.sender,
-                    prev_staking_token_amount,
-                })?,
-                funds: vec![],
-            },
-        ])
-        .add_attributes([
-            ("action", "auto_stake"),
-            ("asset_token", &token_addr.to_string()),
-            ("tax_amount", &tax_amount.to_string()),
-        ]))
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    let mut tax_amounts: Vec<String> = vec![];
+    let mut provide_assets = assets;
+    for asset in provide_assets.iter_mut() {
+        match &asset.info {
+            AssetInfo::NativeToken { denom } => {
+                asset.assert_sent_native_token_balance(&info)?;
+
+                // compute tax
+                let tax_amount = asset.compute_tax(
+                    &oracle_contract,
+                    &deps.querier,
+                    Some(&oraiswap_pair.contract_addr),
+                )?;
+                asset.amount = asset.amount.checked_sub(tax_amount)?;
+                funds.push(Coin {
+                    denom: denom.to_string(),
+                    amount: asset.amount,
+                });
+                tax_amounts.push(format!("{}{}", tax_amount, denom));
+            }
+            AssetInfo::Token { contract_addr } => {
+                messages.push(
+                    WasmMsg::Execute {
+                        contract_addr: contract_addr.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: asset.amount,
+                        })?,
+                        funds: vec![],
+                    }
+                    .into(),
+                );
+                messages.push(
+                    WasmMsg::Execute {
+                        contract_addr: contract_addr.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                            spender: oraiswap_pair.contract_addr.to_string(),
+                            amount: asset.amount,
+                            expires: None,
+                        })?,
+                        funds: vec![],
+                    }
+                    .into(),
+                );
+            }
+        }
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: oraiswap_pair.contract_addr.to_string(),
+            msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                assets: provide_assets,
+                slippage_tolerance,
+                receiver: None,
+            })?,
+            funds,
+        }
+        .into(),
+    );
+    messages.push(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AutoStakeHook {
+                asset_info: asset_info.clone(),
+                staking_token: oraiswap_pair.liquidity_token,
+                staker_addr: info.sender,
+                prev_staking_token_amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    );
+
+    let mut response = Response::new().add_messages(messages).add_attributes([
+        ("action", "auto_stake"),
+        ("asset_info", &asset_info.to_string()),
+    ]);
+    if !tax_amounts.is_empty() {
+        response = response.add_attribute("tax_amount", tax_amounts.join(","));
+    }
+
+    Ok(response)
 }
 
 pub fn auto_stake_hook(
@@ -250,10 +622,10 @@ pub fn auto_stake_hook(
 
     // stake all lp tokens received, compare with staking token amount before liquidity provision was executed
     let current_staking_token_amount =
-        query_token_balance(&deps.querier, staking_token, env.contract.address)?;
+        query_token_balance(&deps.querier, staking_token, env.contract.address.clone())?;
     let amount_to_stake = current_staking_token_amount.checked_sub(prev_staking_token_amount)?;
 
-    bond(deps, staker_addr, asset_info, amount_to_stake)
+    bond(deps, env, staker_addr, asset_info, amount_to_stake)
 }
 
 fn _increase_bond_amount(
@@ -262,6 +634,7 @@ fn _increase_bond_amount(
     staker_addr: &CanonicalAddr,
     asset_info: &AssetInfo,
     amount: Uint128,
+    now: u64,
 ) -> StdResult<()> {
     let asset_key = &asset_info.to_vec(api)?;
     let mut pool_info: PoolInfo = read_pool_info(storage, asset_key)?;
@@ -273,6 +646,7 @@ fn _increase_bond_amount(
             bond_amount: Uint128::zero(),
             pending_reward: Uint128::zero(),
             pending_withdraw: vec![],
+            boost_amount: Uint128::zero(),
         });
 
     // check if the position should be migrated
@@ -289,6 +663,14 @@ fn _increase_bond_amount(
 
     // Withdraw reward to pending reward; before changing share
     before_share_change(pool_info.reward_index, &mut reward_info)?;
+    settle_campaign_rewards(
+        storage,
+        staker_addr,
+        asset_key,
+        reward_info.effective_bond_amount(),
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
 
     // Increase total bond amount
     pool_info.total_bond_amount += amount;
@@ -313,7 +695,8 @@ fn _decrease_bond_amount(
     staker_addr: &CanonicalAddr,
     asset_info: &AssetInfo,
     amount: Uint128,
-) -> StdResult<(CanonicalAddr, Vec<Asset>)> {
+    now: u64,
+) -> StdResult<(CanonicalAddr, Vec<Asset>, u64)> {
     let asset_key = &asset_info.to_vec(api)?;
     let mut pool_info: PoolInfo = read_pool_info(storage, asset_key)?;
     let mut reward_info: RewardInfo = rewards_read(storage, staker_addr).load(asset_key)?;
@@ -337,6 +720,33 @@ fn _decrease_bond_amount(
 
     // Distribute reward to pending reward; before changing share
     before_share_change(pool_index, &mut reward_info)?;
+    // positions of the deprecated staking token are not counted in the pool
+    settle_campaign_rewards(
+        storage,
+        staker_addr,
+        asset_key,
+        if should_migrate {
+            Uint128::zero()
+        } else {
+            reward_info.effective_bond_amount()
+        },
+        pool_info.total_effective_bond_amount(),
+        now,
+    )?;
+
+    // locked amount can not be unbonded until its unlock time
+    let locked_amount = release_expired_locks(
+        storage,
+        staker_addr,
+        asset_key,
+        now,
+        &mut reward_info,
+        &mut pool_info,
+        !should_migrate,
+    )?;
+    if reward_info.bond_amount.checked_sub(locked_amount)? < amount {
+        return Err(StdError::generic_err("Cannot unbond locked amount"));
+    }
 
     // Decrease total bond amount
     if !should_migrate {
@@ -353,9 +763,16 @@ fn _decrease_bond_amount(
 
     if reward_info.pending_reward.is_zero() && reward_info.bond_amount.is_zero() {
         // if pending_withdraw is not empty, then return reward_assets to withdraw money
+        // campaign rewards are withdrawn with the position
         reward_assets = reward_info
             .pending_withdraw
             .into_iter()
+            .chain(withdraw_campaign_rewards(
+                storage,
+                staker_addr,
+                asset_key,
+                true,
+            )?)
             .map(|ra| Ok(ra.to_normal(api)?))
             .collect::<StdResult<Vec<Asset>>>()?;
 
@@ -369,5 +786,5 @@ fn _decrease_bond_amount(
     // Update pool info
     store_pool_info(storage, asset_key, &pool_info)?;
 
-    Ok((staking_token, reward_assets))
+    Ok((staking_token, reward_assets, pool_info.unbonding_period))
 }
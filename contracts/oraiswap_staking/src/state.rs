@@ -1,7 +1,8 @@
 use cosmwasm_schema::cw_serde;
-use oraiswap::asset::AssetRaw;
+use oraiswap::asset::{AssetInfoRaw, AssetRaw};
+use oraiswap::staking::LockTier;
 
-use cosmwasm_std::{CanonicalAddr, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
 
 pub static KEY_CONFIG: &[u8] = b"config_v2";
@@ -10,6 +11,19 @@ pub static PREFIX_REWARD: &[u8] = b"reward_v2";
 static PREFIX_STAKER: &[u8] = b"staker";
 static PREFIX_IS_MIGRATED: &[u8] = b"is_migrated";
 static PREFIX_REWARDS_PER_SEC: &[u8] = b"rewards_per_sec";
+static PREFIX_LOCK: &[u8] = b"lock";
+static KEY_LOCK_CONFIG: &[u8] = b"lock_config";
+static KEY_LAST_LOCK_ID: &[u8] = b"last_lock_id";
+static PREFIX_CLAIM: &[u8] = b"claim";
+static PREFIX_REWARD_RECIPIENT: &[u8] = b"reward_recipient";
+static PREFIX_BOND_CHANGELOG: &[u8] = b"bond_changelog";
+static PREFIX_TOTAL_BOND_CHANGELOG: &[u8] = b"total_bond_changelog";
+static PREFIX_CAMPAIGN: &[u8] = b"campaign";
+static PREFIX_POOL_CAMPAIGNS: &[u8] = b"pool_campaigns";
+static PREFIX_CAMPAIGN_REWARD: &[u8] = b"campaign_reward";
+static KEY_LAST_CAMPAIGN_ID: &[u8] = b"last_campaign_id";
+static PREFIX_CAMPAIGN_REWARD_ASSET: &[u8] = b"campaign_reward_asset";
+static PREFIX_CLOSED_REWARD_INDEX: &[u8] = b"closed_reward_index";
 
 #[cw_serde]
 pub struct Config {
@@ -18,6 +32,12 @@ pub struct Config {
     pub oracle_addr: CanonicalAddr,
     pub factory_addr: CanonicalAddr,
     pub base_denom: String,
+    // router used to swap the rewards when compounding
+    #[serde(default)]
+    pub router_addr: Option<CanonicalAddr>,
+    // stakers can emergency unbond
+    #[serde(default)]
+    pub emergency: bool,
 }
 
 pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
@@ -35,6 +55,19 @@ pub struct PoolInfo {
     pub total_bond_amount: Uint128,
     pub reward_index: Decimal,
     pub migration_params: Option<MigrationParams>,
+    // extra reward weight of all locked bonds
+    #[serde(default)]
+    pub total_boost_amount: Uint128,
+    // seconds the unbonded amount waits before it can be claimed
+    #[serde(default)]
+    pub unbonding_period: u64,
+}
+
+impl PoolInfo {
+    /// total reward weight, bonded amount plus lock boosts
+    pub fn total_effective_bond_amount(&self) -> Uint128 {
+        self.total_bond_amount + self.total_boost_amount
+    }
 }
 
 #[cw_serde]
@@ -63,6 +96,16 @@ pub struct RewardInfo {
     pub pending_reward: Uint128,
     // this is updated by the owner of this contract, when changing the reward_per_sec
     pub pending_withdraw: Vec<AssetRaw>,
+    // extra reward weight of the locked bonds
+    #[serde(default)]
+    pub boost_amount: Uint128,
+}
+
+impl RewardInfo {
+    /// reward weight of the position, bonded amount plus lock boosts
+    pub fn effective_bond_amount(&self) -> Uint128 {
+        self.bond_amount + self.boost_amount
+    }
 }
 
 /// returns a bucket with all rewards owned by this owner (query it by owner)
@@ -121,3 +164,350 @@ pub fn read_rewards_per_sec(storage: &dyn Storage, asset_key: &[u8]) -> StdResul
         ReadonlyBucket::new(storage, PREFIX_REWARDS_PER_SEC);
     weight_bucket.load(asset_key)
 }
+
+#[cw_serde]
+#[derive(Default)]
+pub struct LockConfig {
+    pub tiers: Vec<LockTier>,
+    // share of the locked amount forfeited to the owner when unlocking early
+    pub early_unlock_penalty: Decimal,
+}
+
+pub fn store_lock_config(storage: &mut dyn Storage, lock_config: &LockConfig) -> StdResult<()> {
+    singleton(storage, KEY_LOCK_CONFIG).save(lock_config)
+}
+
+pub fn read_lock_config(storage: &dyn Storage) -> StdResult<LockConfig> {
+    Ok(singleton_read(storage, KEY_LOCK_CONFIG)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+#[cw_serde]
+pub struct Lock {
+    pub lock_id: u64,
+    pub amount: Uint128,
+    pub boost_amount: Uint128,
+    pub unlock_time: u64,
+}
+
+pub fn next_lock_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let lock_id = singleton_read(storage, KEY_LAST_LOCK_ID)
+        .may_load()?
+        .unwrap_or(0u64)
+        + 1;
+    singleton(storage, KEY_LAST_LOCK_ID).save(&lock_id)?;
+    Ok(lock_id)
+}
+
+/// returns a bucket with all locks of this staker, keyed by asset key
+pub fn locks_store<'a>(
+    storage: &'a mut dyn Storage,
+    owner: &CanonicalAddr,
+) -> Bucket<'a, Vec<Lock>> {
+    Bucket::multilevel(storage, &[PREFIX_LOCK, owner.as_slice()])
+}
+
+pub fn read_locks(
+    storage: &dyn Storage,
+    owner: &CanonicalAddr,
+    asset_key: &[u8],
+) -> StdResult<Vec<Lock>> {
+    Ok(
+        ReadonlyBucket::multilevel(storage, &[PREFIX_LOCK, owner.as_slice()])
+            .may_load(asset_key)?
+            .unwrap_or_default(),
+    )
+}
+
+#[cw_serde]
+pub struct Claim {
+    pub asset_info: AssetInfoRaw,
+    pub staking_token: CanonicalAddr,
+    pub amount: Uint128,
+    pub release_at: u64,
+}
+
+pub fn store_claims(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    claims: Vec<Claim>,
+) -> StdResult<()> {
+    let mut claims_bucket: Bucket<Vec<Claim>> = Bucket::new(storage, PREFIX_CLAIM);
+    if claims.is_empty() {
+        claims_bucket.remove(staker_addr);
+        Ok(())
+    } else {
+        claims_bucket.save(staker_addr, &claims)
+    }
+}
+
+pub fn read_claims(storage: &dyn Storage, staker_addr: &CanonicalAddr) -> StdResult<Vec<Claim>> {
+    let claims_bucket: ReadonlyBucket<Vec<Claim>> = ReadonlyBucket::new(storage, PREFIX_CLAIM);
+    Ok(claims_bucket.may_load(staker_addr)?.unwrap_or_default())
+}
+
+/// like cw-storage-plus SnapshotMap, the changelog keeps the value before the first change at a height
+pub fn store_bond_changelog(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    staker_addr: &CanonicalAddr,
+    height: u64,
+    bond_amount: Uint128,
+) -> StdResult<()> {
+    let mut changelog: Bucket<Uint128> = Bucket::multilevel(
+        storage,
+        &[PREFIX_BOND_CHANGELOG, asset_key, staker_addr.as_slice()],
+    );
+    if changelog.may_load(&height.to_be_bytes())?.is_none() {
+        changelog.save(&height.to_be_bytes(), &bond_amount)?;
+    }
+    Ok(())
+}
+
+/// value at the start of the height, none if it was not changed since
+pub fn read_bond_changelog(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    staker_addr: &CanonicalAddr,
+    height: u64,
+) -> StdResult<Option<Uint128>> {
+    let changelog: ReadonlyBucket<Uint128> = ReadonlyBucket::multilevel(
+        storage,
+        &[PREFIX_BOND_CHANGELOG, asset_key, staker_addr.as_slice()],
+    );
+    let bond_amount = changelog
+        .range(Some(&height.to_be_bytes()), None, Order::Ascending)
+        .next()
+        .map(|item| item.map(|(_, bond_amount)| bond_amount))
+        .transpose();
+    bond_amount
+}
+
+pub fn store_total_bond_changelog(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    height: u64,
+    total_bond_amount: Uint128,
+) -> StdResult<()> {
+    let mut changelog: Bucket<Uint128> =
+        Bucket::multilevel(storage, &[PREFIX_TOTAL_BOND_CHANGELOG, asset_key]);
+    if changelog.may_load(&height.to_be_bytes())?.is_none() {
+        changelog.save(&height.to_be_bytes(), &total_bond_amount)?;
+    }
+    Ok(())
+}
+
+pub fn read_total_bond_changelog(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    height: u64,
+) -> StdResult<Option<Uint128>> {
+    let changelog: ReadonlyBucket<Uint128> =
+        ReadonlyBucket::multilevel(storage, &[PREFIX_TOTAL_BOND_CHANGELOG, asset_key]);
+    let total_bond_amount = changelog
+        .range(Some(&height.to_be_bytes()), None, Order::Ascending)
+        .next()
+        .map(|item| item.map(|(_, total_bond_amount)| total_bond_amount))
+        .transpose();
+    total_bond_amount
+}
+
+/// remove the recipient when the rewards go back to the staker
+pub fn store_reward_recipient(
+    storage: &mut dyn Storage,
+    staker_addr: &CanonicalAddr,
+    recipient: &CanonicalAddr,
+) -> StdResult<()> {
+    let mut recipient_bucket: Bucket<CanonicalAddr> = Bucket::new(storage, PREFIX_REWARD_RECIPIENT);
+    if recipient == staker_addr {
+        recipient_bucket.remove(staker_addr);
+        Ok(())
+    } else {
+        recipient_bucket.save(staker_addr, recipient)
+    }
+}
+
+/// the address receiving the staker rewards, the staker itself by default
+pub fn read_reward_recipient(
+    storage: &dyn Storage,
+    staker_addr: &CanonicalAddr,
+) -> StdResult<CanonicalAddr> {
+    let recipient_bucket: ReadonlyBucket<CanonicalAddr> =
+        ReadonlyBucket::new(storage, PREFIX_REWARD_RECIPIENT);
+    Ok(recipient_bucket
+        .may_load(staker_addr)?
+        .unwrap_or_else(|| staker_addr.clone()))
+}
+
+#[cw_serde]
+pub struct Campaign {
+    pub campaign_id: u64,
+    pub asset_info: AssetInfoRaw,
+    pub funder: CanonicalAddr,
+    pub reward: AssetRaw,
+    pub start_time: u64,
+    pub end_time: u64,
+    // streamed while nothing was bonded, refundable to the funder after the end
+    pub unused_amount: Uint128,
+}
+
+impl Campaign {
+    /// reward amount streamed from the start time until the given time
+    pub fn streamed_amount(&self, time: u64) -> Uint128 {
+        let time = time.clamp(self.start_time, self.end_time);
+        self.reward
+            .amount
+            .multiply_ratio(time - self.start_time, self.end_time - self.start_time)
+    }
+}
+
+pub fn read_last_campaign_id(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_LAST_CAMPAIGN_ID)
+        .may_load()?
+        .unwrap_or(0u64))
+}
+
+pub fn next_campaign_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let campaign_id = read_last_campaign_id(storage)? + 1;
+    singleton(storage, KEY_LAST_CAMPAIGN_ID).save(&campaign_id)?;
+    Ok(campaign_id)
+}
+
+pub fn store_campaign(storage: &mut dyn Storage, campaign: &Campaign) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_CAMPAIGN).save(&campaign.campaign_id.to_be_bytes(), campaign)
+}
+
+pub fn read_campaign(storage: &dyn Storage, campaign_id: u64) -> StdResult<Campaign> {
+    ReadonlyBucket::new(storage, PREFIX_CAMPAIGN).load(&campaign_id.to_be_bytes())
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct PoolCampaigns {
+    pub last_update_time: u64,
+    // campaigns not ended at the last update
+    pub campaign_ids: Vec<u64>,
+    // one reward index for each campaign reward asset of the pool
+    pub reward_indexes: Vec<CampaignRewardIndex>,
+}
+
+#[cw_serde]
+pub struct CampaignRewardIndex {
+    pub info: AssetInfoRaw,
+    pub index: Decimal,
+    // campaign that opened the index, the reward asset gets a new index once all its campaigns ended
+    pub index_id: u64,
+}
+
+pub fn store_pool_campaigns(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    pool_campaigns: &PoolCampaigns,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_POOL_CAMPAIGNS).save(asset_key, pool_campaigns)
+}
+
+pub fn read_pool_campaigns(storage: &dyn Storage, asset_key: &[u8]) -> StdResult<PoolCampaigns> {
+    Ok(ReadonlyBucket::new(storage, PREFIX_POOL_CAMPAIGNS)
+        .may_load(asset_key)?
+        .unwrap_or_default())
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct CampaignRewards {
+    // last campaign id when settled, the indexes opened after it are new to the staker
+    pub last_campaign_id: u64,
+    pub rewards: Vec<CampaignRewardInfo>,
+}
+
+#[cw_serde]
+pub struct CampaignRewardInfo {
+    pub info: AssetInfoRaw,
+    pub index: Decimal,
+    pub pending_reward: Uint128,
+    pub index_id: u64,
+}
+
+/// returns a bucket with the campaign rewards of this staker, keyed by asset key
+pub fn campaign_rewards_store<'a>(
+    storage: &'a mut dyn Storage,
+    owner: &CanonicalAddr,
+) -> Bucket<'a, CampaignRewards> {
+    Bucket::multilevel(storage, &[PREFIX_CAMPAIGN_REWARD, owner.as_slice()])
+}
+
+pub fn read_campaign_rewards(
+    storage: &dyn Storage,
+    owner: &CanonicalAddr,
+    asset_key: &[u8],
+) -> StdResult<CampaignRewards> {
+    Ok(
+        ReadonlyBucket::multilevel(storage, &[PREFIX_CAMPAIGN_REWARD, owner.as_slice()])
+            .may_load(asset_key)?
+            .unwrap_or_default(),
+    )
+}
+
+/// reward index dropped from the pool at its final value, for the stakers not settled yet
+pub fn store_closed_reward_index(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    reward_index: &CampaignRewardIndex,
+) -> StdResult<()> {
+    Bucket::multilevel(storage, &[PREFIX_CLOSED_REWARD_INDEX, asset_key])
+        .save(&reward_index.index_id.to_be_bytes(), reward_index)
+}
+
+pub fn read_closed_reward_index(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    index_id: u64,
+) -> StdResult<CampaignRewardIndex> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_CLOSED_REWARD_INDEX, asset_key])
+        .load(&index_id.to_be_bytes())
+}
+
+/// reward indexes of the pool closed with an id above the given one
+pub fn read_closed_reward_indexes(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+    start_after: u64,
+) -> StdResult<Vec<CampaignRewardIndex>> {
+    ReadonlyBucket::multilevel(storage, &[PREFIX_CLOSED_REWARD_INDEX, asset_key])
+        .range(
+            Some(&(start_after + 1).to_be_bytes()),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, reward_index)| reward_index))
+        .collect()
+}
+
+/// campaign reward asset allowed by the owner, the amount is the minimum reward of a campaign
+pub fn store_campaign_reward_asset(
+    storage: &mut dyn Storage,
+    asset_key: &[u8],
+    minimum_reward: &AssetRaw,
+) -> StdResult<()> {
+    Bucket::new(storage, PREFIX_CAMPAIGN_REWARD_ASSET).save(asset_key, minimum_reward)
+}
+
+pub fn remove_campaign_reward_asset(storage: &mut dyn Storage, asset_key: &[u8]) {
+    Bucket::<AssetRaw>::new(storage, PREFIX_CAMPAIGN_REWARD_ASSET).remove(asset_key)
+}
+
+pub fn read_campaign_reward_asset(
+    storage: &dyn Storage,
+    asset_key: &[u8],
+) -> StdResult<Option<AssetRaw>> {
+    ReadonlyBucket::new(storage, PREFIX_CAMPAIGN_REWARD_ASSET).may_load(asset_key)
+}
+
+pub fn read_campaign_reward_assets(storage: &dyn Storage) -> StdResult<Vec<AssetRaw>> {
+    ReadonlyBucket::new(storage, PREFIX_CAMPAIGN_REWARD_ASSET)
+        .range(None, None, Order::Ascending)
+        .map(|item| item.map(|(_, minimum_reward)| minimum_reward))
+        .collect()
+}
@@ -4,10 +4,11 @@ use cosmwasm_std::testing::{
 };
 use cosmwasm_std::{attr, coin, from_binary, to_binary, Addr, Decimal, Order, StdError, Uint128};
 use cw20::Cw20ReceiveMsg;
+use oraiswap::admin::{PendingAdminResponse, TimelockResponse};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::staking::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg,
-    RewardInfoResponse,
+    RewardInfoResponse, RewardsPerSecResponse,
 };
 
 #[test]
@@ -38,6 +39,8 @@ fn proper_initialization() {
             oracle_addr: Addr::unchecked("oracle"),
             factory_addr: Addr::unchecked("factory"),
             base_denom: ORAI_DENOM.to_string(),
+            router_addr: None,
+            emergency: false,
         },
         config
     );
@@ -64,11 +67,19 @@ fn update_config() {
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some(Addr::unchecked("owner2")),
         rewarder: None,
+        router: Some(Addr::unchecked("router")),
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    // the proposed owner takes over once it accepts
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::PendingAdmin {}).unwrap();
+    let pending_admin: PendingAdminResponse = from_binary(&res).unwrap();
+    assert_eq!(pending_admin.pending_admin, Some(Addr::unchecked("owner2")));
+    let info = mock_info("owner2", &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptAdmin {}).unwrap();
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config: ConfigResponse = from_binary(&res).unwrap();
@@ -79,6 +90,8 @@ fn update_config() {
             oracle_addr: Addr::unchecked("oracle"),
             factory_addr: Addr::unchecked("factory"),
             base_denom: ORAI_DENOM.to_string(),
+            router_addr: Some(Addr::unchecked("router")),
+            emergency: false,
         },
         config
     );
@@ -88,6 +101,7 @@ fn update_config() {
     let msg = ExecuteMsg::UpdateConfig {
         rewarder: None,
         owner: None,
+        router: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -97,6 +111,98 @@ fn update_config() {
     }
 }
 
+#[test]
+fn timelocked_rewards_per_sec() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("reward"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let info = mock_info("addr", &[]);
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateTimelock { delay: 100 };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // the new rate is queued
+    let msg = ExecuteMsg::UpdateRewardsPerSec {
+        asset_info: asset_info.clone(),
+        assets: vec![Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: 100u128.into(),
+        }],
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "queue_change"),
+            attr("change_id", "1"),
+            attr("eta", (mock_env().block.time.seconds() + 100).to_string()),
+        ]
+    );
+
+    let query_msg = QueryMsg::RewardsPerSec { asset_info };
+    query(deps.as_ref(), mock_env(), query_msg.clone()).unwrap_err();
+
+    let msg = ExecuteMsg::ExecuteTimelocked { id: 1 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    )
+    .unwrap_err();
+    execute(deps.as_mut(), env, mock_info("owner", &[]), msg).unwrap();
+    let res: RewardsPerSecResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+    assert_eq!(res.assets.len(), 1);
+
+    // cancelled changes are never applied
+    let msg = ExecuteMsg::UpdateTimelock { delay: 0 };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let msg = ExecuteMsg::CancelTimelocked { id: 2 };
+    execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Timelock {
+            start_after: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let timelock: TimelockResponse = from_binary(&res).unwrap();
+    assert_eq!(timelock.delay, 100);
+    assert_eq!(timelock.changes, vec![]);
+}
+
 #[test]
 fn test_register() {
     let mut deps = mock_dependencies();
@@ -163,6 +269,7 @@ fn test_register() {
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
             migration_index_snapshot: None,
+            unbonding_period: 0,
         }
     );
 }
@@ -92,6 +92,7 @@ fn test_migration() {
             pending_reward: Uint128::from(500u64),
             native_token: false,
             pending_withdraw: vec![],
+            boost_amount: Uint128::zero(),
         }
     );
 }
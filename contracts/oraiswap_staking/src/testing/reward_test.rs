@@ -1,13 +1,16 @@
 use crate::contract::{execute, instantiate, query};
 use crate::state::{read_pool_info, rewards_read, store_pool_info, PoolInfo, RewardInfo};
 use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
-use cosmwasm_std::{coin, from_binary, to_binary, Addr, Api, Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, Api, BankMsg, Decimal, SubMsg, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
 use oraiswap::staking::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
-    RewardInfoResponseItem,
+    CampaignResponse, CampaignRewardAssetsResponse, CampaignRewardsResponse, CampaignsResponse,
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolAprResponse, PoolInfoResponse, QueryMsg,
+    RewardAprItem, RewardInfoResponse, RewardInfoResponseItem,
 };
 use oraiswap::testing::{MockApp, ATOM_DENOM};
 
@@ -378,6 +381,7 @@ fn test_before_share_changes() {
             index: Decimal::zero(),
             native_token: false,
             pending_withdraw: vec![],
+            boost_amount: Uint128::zero(),
         },
         reward_info
     );
@@ -405,6 +409,7 @@ fn test_before_share_changes() {
             index: Decimal::from_ratio(100u128, 100u128),
             native_token: false,
             pending_withdraw: vec![],
+            boost_amount: Uint128::zero(),
         },
         reward_info
     );
@@ -440,6 +445,7 @@ fn test_before_share_changes() {
             index: Decimal::from_ratio(150u128, 100u128),
             native_token: false,
             pending_withdraw: vec![],
+            boost_amount: Uint128::zero(),
         },
         reward_info
     );
@@ -1013,3 +1019,562 @@ fn test_update_rewards_per_sec_with_multiple_bond() {
         }
     );
 }
+
+#[test]
+fn test_campaign() {
+    let mut deps = mock_dependencies_with_balance(&[coin(10000000000u128, ORAI_DENOM)]);
+    let env_at = |seconds: u64| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env
+    };
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let empty_asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset2"),
+    };
+    for (asset_info, staking_token) in [(&asset_info, "staking"), (&empty_asset_info, "staking2")] {
+        let msg = ExecuteMsg::RegisterAsset {
+            asset_info: asset_info.clone(),
+            staking_token: Addr::unchecked(staking_token),
+        };
+        let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    }
+
+    let bond_msg = |staker: &str| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: asset_info.clone(),
+            })
+            .unwrap(),
+        })
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env_at(0),
+        mock_info("staking", &[]),
+        bond_msg("addr"),
+    )
+    .unwrap();
+
+    // only the owner allows campaign reward assets
+    let allow_msg =
+        |reward_info: AssetInfo, minimum_reward: u128| ExecuteMsg::UpdateCampaignRewardAsset {
+            reward_info,
+            minimum_reward: Some(Uint128::from(minimum_reward)),
+        };
+    let orai_info = AssetInfo::NativeToken {
+        denom: ORAI_DENOM.to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        allow_msg(orai_info.clone(), 1000),
+    );
+    assert!(res.is_err());
+    for (reward_info, minimum_reward) in [
+        (orai_info.clone(), 1000u128),
+        (
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("reward"),
+            },
+            500u128,
+        ),
+    ] {
+        let _res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            allow_msg(reward_info, minimum_reward),
+        )
+        .unwrap();
+    }
+    let res: CampaignRewardAssetsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::CampaignRewardAssets {}).unwrap())
+            .unwrap();
+    assert_eq!(res.reward_assets.len(), 2);
+
+    // the reward is below the minimum
+    let start_time = mock_env().block.time.seconds() + 100;
+    let msg = ExecuteMsg::CreateCampaign {
+        asset_info: asset_info.clone(),
+        reward: Asset {
+            info: orai_info.clone(),
+            amount: Uint128::from(999u128),
+        },
+        start_time,
+        end_time: start_time + 1000,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env_at(0),
+        mock_info("partner", &[coin(999u128, ORAI_DENOM)]),
+        msg,
+    );
+    assert!(res.is_err());
+
+    // the reward asset is not allowed
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "partner".to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::CreateCampaign {
+            asset_info: asset_info.clone(),
+            start_time,
+            end_time: start_time + 1000,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), env_at(0), mock_info("spam", &[]), msg);
+    assert!(res.is_err());
+
+    // stream 1000 orai from 100s to 1100s
+    let reward = Asset {
+        info: AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        amount: Uint128::from(1000u128),
+    };
+    let msg = ExecuteMsg::CreateCampaign {
+        asset_info: asset_info.clone(),
+        reward: reward.clone(),
+        start_time,
+        end_time: start_time + 1000,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env_at(0),
+        mock_info("partner", &[]),
+        msg.clone(),
+    );
+    assert!(res.is_err());
+    let _res = execute(
+        deps.as_mut(),
+        env_at(0),
+        mock_info("partner", &[coin(1000u128, ORAI_DENOM)]),
+        msg,
+    )
+    .unwrap();
+
+    // stream 500 token on the pool without bond, all is unused
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "partner".to_string(),
+        amount: Uint128::from(500u128),
+        msg: to_binary(&Cw20HookMsg::CreateCampaign {
+            asset_info: empty_asset_info.clone(),
+            start_time,
+            end_time: start_time + 1000,
+        })
+        .unwrap(),
+    });
+    let _res = execute(deps.as_mut(), env_at(0), mock_info("reward", &[]), msg).unwrap();
+
+    let res: CampaignsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Campaigns {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.campaigns,
+        vec![CampaignResponse {
+            campaign_id: 1,
+            asset_info: asset_info.clone(),
+            funder: Addr::unchecked("partner"),
+            reward: reward.clone(),
+            start_time,
+            end_time: start_time + 1000,
+            unused_amount: Uint128::zero(),
+        }]
+    );
+
+    // addr earns alone for 500s, then shares the rest with addr2
+    let _res = execute(
+        deps.as_mut(),
+        env_at(600),
+        mock_info("staking", &[]),
+        bond_msg("addr2"),
+    )
+    .unwrap();
+
+    for (staker, amount) in [("addr", 750u128), ("addr2", 250u128)] {
+        let res: CampaignRewardsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env_at(2000),
+                QueryMsg::CampaignRewards {
+                    staker_addr: Addr::unchecked(staker),
+                    asset_info: asset_info.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.pending_rewards,
+            vec![Asset {
+                info: reward.info.clone(),
+                amount: Uint128::from(amount),
+            }]
+        );
+    }
+
+    let msg = ExecuteMsg::Withdraw {
+        asset_info: Some(asset_info.clone()),
+    };
+    let res = execute(deps.as_mut(), env_at(2000), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr".to_string(),
+            amount: vec![coin(750u128, ORAI_DENOM)],
+        })]
+    );
+
+    // only the funder can refund after the end
+    let msg = ExecuteMsg::RefundCampaign { campaign_id: 2 };
+    let res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        mock_info("addr", &[]),
+        msg.clone(),
+    );
+    assert!(res.is_err());
+    let res = execute(
+        deps.as_mut(),
+        env_at(600),
+        mock_info("partner", &[]),
+        msg.clone(),
+    );
+    assert!(res.is_err());
+    let res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        mock_info("partner", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "reward".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "partner".to_string(),
+                amount: Uint128::from(500u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    let res = execute(deps.as_mut(), env_at(2000), mock_info("partner", &[]), msg);
+    assert!(res.is_err());
+
+    // the fully used campaign has nothing to refund
+    let msg = ExecuteMsg::RefundCampaign { campaign_id: 1 };
+    let res = execute(deps.as_mut(), env_at(2000), mock_info("partner", &[]), msg);
+    assert!(res.is_err());
+
+    // the ended campaign and its reward index are dropped
+    let res: CampaignsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env_at(2000),
+            QueryMsg::Campaigns {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.campaigns.is_empty());
+
+    // addr3 bonds after the close, it earns nothing of the closed index
+    let _res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        mock_info("staking", &[]),
+        bond_msg("addr3"),
+    )
+    .unwrap();
+
+    // a new campaign opens a new index, addr2 keeps the reward of the closed one
+    let start_time = mock_env().block.time.seconds() + 2100;
+    let msg = ExecuteMsg::CreateCampaign {
+        asset_info: asset_info.clone(),
+        reward: reward.clone(),
+        start_time,
+        end_time: start_time + 1000,
+    };
+    let _res = execute(
+        deps.as_mut(),
+        env_at(2000),
+        mock_info("partner", &[coin(1000u128, ORAI_DENOM)]),
+        msg,
+    )
+    .unwrap();
+
+    for (staker, amount) in [("addr", 333u128), ("addr2", 583u128), ("addr3", 333u128)] {
+        let res: CampaignRewardsResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env_at(3100),
+                QueryMsg::CampaignRewards {
+                    staker_addr: Addr::unchecked(staker),
+                    asset_info: asset_info.clone(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.pending_rewards,
+            vec![Asset {
+                info: reward.info.clone(),
+                amount: Uint128::from(amount),
+            }]
+        );
+    }
+
+    let msg = ExecuteMsg::Withdraw {
+        asset_info: Some(asset_info.clone()),
+    };
+    let res = execute(deps.as_mut(), env_at(3100), mock_info("addr2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "addr2".to_string(),
+            amount: vec![coin(583u128, ORAI_DENOM)],
+        })]
+    );
+}
+
+#[test]
+fn test_pool_apr() {
+    let mut app = MockApp::new(&[(&"addr".to_string(), &[coin(10000000000u128, ORAI_DENOM)])]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let asset_addr = app.create_token("asset");
+    let reward_addr = app.create_token("reward");
+    app.set_token_balances(&[
+        (
+            &"reward".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+        (
+            &"asset".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+    ]);
+
+    // asset is worth 1 orai, reward is worth 0.5 orai
+    let mut liquidity_tokens = vec![];
+    for (token_addr, token_amount) in [
+        (asset_addr.clone(), 1000000u128),
+        (reward_addr.clone(), 2000000u128),
+    ] {
+        let pair_addr = app
+            .create_pair([
+                AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: token_addr.clone(),
+                },
+            ])
+            .unwrap();
+        app.execute(
+            Addr::unchecked("addr"),
+            token_addr.clone(),
+            &Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair_addr.to_string(),
+                amount: Uint128::from(token_amount),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute(
+            Addr::unchecked("addr"),
+            pair_addr.clone(),
+            &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ORAI_DENOM.to_string(),
+                        },
+                        amount: Uint128::from(1000000u128),
+                    },
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: token_addr.clone(),
+                        },
+                        amount: Uint128::from(token_amount),
+                    },
+                ],
+                slippage_tolerance: None,
+                receiver: None,
+            },
+            &[coin(1000000u128, ORAI_DENOM)],
+        )
+        .unwrap();
+        let res: oraiswap::pair::PairResponse = app
+            .query(pair_addr, &oraiswap::pair::QueryMsg::Pair {})
+            .unwrap();
+        liquidity_tokens.push(res.info.liquidity_token);
+    }
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let staking_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr"),
+            &InstantiateMsg {
+                owner: Some(Addr::unchecked("owner")),
+                rewarder: Addr::unchecked("rewarder"),
+                minter: Some(Addr::unchecked("mint")),
+                oracle_addr: app.oracle_addr.clone(),
+                factory_addr: app.factory_addr.clone(),
+                base_denom: None,
+            },
+            &[],
+            "staking",
+        )
+        .unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: asset_addr.clone(),
+    };
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::RegisterAsset {
+            asset_info: asset_info.clone(),
+            staking_token: liquidity_tokens[0].clone(),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateRewardsPerSec {
+            asset_info: asset_info.clone(),
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: reward_addr.clone(),
+                    },
+                    amount: Uint128::from(2u128),
+                },
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // nothing bonded yet
+    let res: PoolAprResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::PoolApr {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.tvl, Uint128::zero());
+    assert_eq!(res.apr, Decimal::zero());
+
+    // bond 1000 of the 1000000 lp tokens, worth 2000 orai
+    app.execute(
+        Addr::unchecked("addr"),
+        liquidity_tokens[0].clone(),
+        &Cw20ExecuteMsg::Send {
+            contract: staking_addr.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: asset_info.clone(),
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res: PoolAprResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::PoolApr {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        PoolAprResponse {
+            asset_info,
+            base_denom: ORAI_DENOM.to_string(),
+            total_bond_amount: Uint128::from(1000u128),
+            tvl: Uint128::from(2000u128),
+            annual_reward_value: Uint128::from(63072000u128),
+            apr: Decimal::from_ratio(31536u128, 1u128),
+            rewards: vec![
+                RewardAprItem {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    reward_per_sec: Uint128::from(1u128),
+                    price: Decimal::one(),
+                    annual_reward_value: Uint128::from(31536000u128),
+                    apr: Decimal::from_ratio(15768u128, 1u128),
+                },
+                RewardAprItem {
+                    info: AssetInfo::Token {
+                        contract_addr: reward_addr,
+                    },
+                    reward_per_sec: Uint128::from(2u128),
+                    price: Decimal::from_ratio(1u128, 2u128),
+                    annual_reward_value: Uint128::from(31536000u128),
+                    apr: Decimal::from_ratio(15768u128, 1u128),
+                },
+            ],
+        }
+    );
+}
@@ -1,18 +1,20 @@
 use crate::contract::{execute, instantiate, query};
+use crate::state::{rewards_read, rewards_store};
 use cosmwasm_std::testing::{
     mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
 };
 use cosmwasm_std::{
-    attr, coin, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, StdError, SubMsg,
-    Uint128, WasmMsg,
+    attr, coin, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, StdError,
+    SubMsg, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use oraiswap::asset::{Asset, AssetInfo, ORAI_DENOM};
 use oraiswap::create_entry_points_testing;
 use oraiswap::pair::PairResponse;
 use oraiswap::staking::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolInfoResponse, QueryMsg, RewardInfoResponse,
-    RewardInfoResponseItem,
+    BondAtResponse, Claim, ClaimsResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, LockResponse,
+    LockTier, LocksResponse, PoolInfoResponse, QueryMsg, RewardInfoResponse,
+    RewardInfoResponseItem, RewardRecipientResponse, TotalBondAtResponse,
 };
 use oraiswap::testing::{AttributeUtil, MockApp, ATOM_DENOM};
 
@@ -107,6 +109,7 @@ fn test_bond_tokens() {
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
             migration_index_snapshot: None,
+            unbonding_period: 0,
         }
     );
 
@@ -147,6 +150,7 @@ fn test_bond_tokens() {
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
             migration_index_snapshot: None,
+            unbonding_period: 0,
         }
     );
 
@@ -344,6 +348,7 @@ fn test_unbond() {
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
             migration_index_snapshot: None,
+            unbonding_period: 0,
         }
     );
 
@@ -653,6 +658,1515 @@ fn test_auto_stake() {
             pending_reward: Uint128::zero(),
             migration_deprecated_staking_token: None,
             migration_index_snapshot: None,
+            unbonding_period: 0,
         }
     );
 }
+
+#[test]
+fn test_auto_stake_any_pair() {
+    let mut app = MockApp::new(&[(
+        &"addr".to_string(),
+        &[
+            coin(10000000000u128, ORAI_DENOM),
+            coin(10000000000u128, ATOM_DENOM),
+        ],
+    )]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    app.set_tax(
+        Decimal::zero(),
+        &[(&ATOM_DENOM.to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let asset_addr = app.create_token("asset");
+    let reward_addr = app.create_token("reward");
+    app.set_token_balances(&[
+        (
+            &"reward".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+        (
+            &"asset".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+    ]);
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let staking_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr"),
+            &InstantiateMsg {
+                owner: Some(Addr::unchecked("owner")),
+                rewarder: Addr::unchecked("rewarder"),
+                minter: Some(Addr::unchecked("mint")),
+                oracle_addr: app.oracle_addr.clone(),
+                factory_addr: app.factory_addr.clone(),
+                base_denom: None,
+            },
+            &[],
+            "staking",
+        )
+        .unwrap();
+
+    // native/native pool registered with the atom denom, cw20/cw20 pool with its lp token
+    let native_infos = [
+        AssetInfo::NativeToken {
+            denom: ORAI_DENOM.to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: ATOM_DENOM.to_string(),
+        },
+    ];
+    let token_infos = [
+        AssetInfo::Token {
+            contract_addr: asset_addr.clone(),
+        },
+        AssetInfo::Token {
+            contract_addr: reward_addr.clone(),
+        },
+    ];
+    for asset_infos in [native_infos.clone(), token_infos.clone()] {
+        let pair_addr = app.create_pair(asset_infos.clone()).unwrap();
+        let PairResponse { info: pair_info } = app
+            .query(pair_addr, &oraiswap::pair::QueryMsg::Pair {})
+            .unwrap();
+        let pool_asset_info = if asset_infos[1].is_native_token() {
+            asset_infos[1].clone()
+        } else {
+            AssetInfo::Token {
+                contract_addr: pair_info.liquidity_token.clone(),
+            }
+        };
+        app.execute(
+            Addr::unchecked("owner"),
+            staking_addr.clone(),
+            &ExecuteMsg::RegisterAsset {
+                asset_info: pool_asset_info,
+                staking_token: pair_info.liquidity_token,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    for token_addr in [asset_addr.clone(), reward_addr.clone()] {
+        app.execute(
+            Addr::unchecked("addr"),
+            token_addr,
+            &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+                spender: staking_addr.to_string(),
+                amount: Uint128::from(100u128),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    let msg = ExecuteMsg::AutoStake {
+        assets: native_infos.clone().map(|info| Asset {
+            info,
+            amount: Uint128::from(100u128),
+        }),
+        slippage_tolerance: None,
+    };
+
+    // missing atom funds
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &msg,
+        &[coin(100u128, ORAI_DENOM)],
+    );
+    app.assert_fail(res);
+
+    let _res = app
+        .execute(
+            Addr::unchecked("addr"),
+            staking_addr.clone(),
+            &msg,
+            &[coin(100u128, ATOM_DENOM), coin(100u128, ORAI_DENOM)],
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::AutoStake {
+        assets: token_infos.clone().map(|info| Asset {
+            info,
+            amount: Uint128::from(100u128),
+        }),
+        slippage_tolerance: None,
+    };
+    let _res = app
+        .execute(Addr::unchecked("addr"), staking_addr.clone(), &msg, &[])
+        .unwrap();
+
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                asset_info: None,
+            },
+        )
+        .unwrap();
+    // the atom deposit is net of tax
+    let mut bond_amounts = res
+        .reward_infos
+        .into_iter()
+        .map(|reward_info| (reward_info.asset_info, reward_info.bond_amount))
+        .collect::<Vec<_>>();
+    bond_amounts.sort_by_key(|(_, bond_amount)| *bond_amount);
+    assert_eq!(
+        bond_amounts,
+        vec![
+            (native_infos[1].clone(), Uint128::from(99u128)),
+            (
+                AssetInfo::Token {
+                    contract_addr: app.query_pair(token_infos).unwrap().liquidity_token
+                },
+                Uint128::from(100u128)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_compound() {
+    let mut app = MockApp::new(&[(&"addr".to_string(), &[coin(10000000000u128, ORAI_DENOM)])]);
+
+    app.set_oracle_contract(Box::new(create_entry_points_testing!(oraiswap_oracle)));
+
+    app.set_token_contract(Box::new(create_entry_points_testing!(oraiswap_token)));
+
+    app.set_factory_and_pair_contract(
+        Box::new(
+            create_entry_points_testing!(oraiswap_factory)
+                .with_reply(oraiswap_factory::contract::reply),
+        ),
+        Box::new(
+            create_entry_points_testing!(oraiswap_pair).with_reply(oraiswap_pair::contract::reply),
+        ),
+    );
+
+    let asset_addr = app.create_token("asset");
+    let reward_addr = app.create_token("reward");
+    app.set_token_balances(&[
+        (
+            &"reward".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+        (
+            &"asset".to_string(),
+            &[(&"addr".to_string(), &Uint128::from(10000000000u128))],
+        ),
+    ]);
+
+    // the pool pair and a pair to swap the reward token
+    let mut pair_infos = vec![];
+    for token_addr in [asset_addr.clone(), reward_addr.clone()] {
+        let pair_addr = app
+            .create_pair([
+                AssetInfo::NativeToken {
+                    denom: ORAI_DENOM.to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: token_addr.clone(),
+                },
+            ])
+            .unwrap();
+        let PairResponse { info: pair_info } = app
+            .query(pair_addr.clone(), &oraiswap::pair::QueryMsg::Pair {})
+            .unwrap();
+
+        app.execute(
+            Addr::unchecked("addr"),
+            token_addr.clone(),
+            &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+                spender: pair_addr.to_string(),
+                amount: Uint128::from(1000000u128),
+                expires: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute(
+            Addr::unchecked("addr"),
+            pair_addr.clone(),
+            &oraiswap::pair::ExecuteMsg::ProvideLiquidity {
+                assets: [
+                    Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: ORAI_DENOM.to_string(),
+                        },
+                        amount: Uint128::from(1000000u128),
+                    },
+                    Asset {
+                        info: AssetInfo::Token {
+                            contract_addr: token_addr.clone(),
+                        },
+                        amount: Uint128::from(1000000u128),
+                    },
+                ],
+                slippage_tolerance: None,
+                receiver: None,
+            },
+            &[coin(1000000u128, ORAI_DENOM)],
+        )
+        .unwrap();
+        pair_infos.push(pair_info);
+    }
+    let pair_info = pair_infos.remove(0);
+
+    let router_code_id = app.upload(Box::new(create_entry_points_testing!(oraiswap_router)));
+    let router_addr = app
+        .instantiate(
+            router_code_id,
+            Addr::unchecked("addr"),
+            &oraiswap::router::InstantiateMsg {
+                factory_addr: app.factory_addr.clone(),
+                factory_addr_v2: app.factory_addr.clone(),
+            },
+            &[],
+            "router",
+        )
+        .unwrap();
+
+    let code_id = app.upload(Box::new(create_entry_points_testing!(crate)));
+    let staking_addr = app
+        .instantiate(
+            code_id,
+            Addr::unchecked("addr"),
+            &InstantiateMsg {
+                owner: Some(Addr::unchecked("owner")),
+                rewarder: Addr::unchecked("rewarder"),
+                minter: Some(Addr::unchecked("mint")),
+                oracle_addr: app.oracle_addr.clone(),
+                factory_addr: app.factory_addr.clone(),
+                base_denom: None,
+            },
+            &[],
+            "staking",
+        )
+        .unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: asset_addr.clone(),
+    };
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::RegisterAsset {
+            asset_info: asset_info.clone(),
+            staking_token: pair_info.liquidity_token.clone(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // rewards are shared between orai, the pool token and the reward token
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateRewardsPerSec {
+            asset_info: asset_info.clone(),
+            assets: vec![
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+                Asset {
+                    info: asset_info.clone(),
+                    amount: Uint128::from(100u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: reward_addr.clone(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // bond lp tokens
+    app.execute(
+        Addr::unchecked("addr"),
+        pair_info.liquidity_token.clone(),
+        &cw20::Cw20ExecuteMsg::Send {
+            contract: staking_addr.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: asset_info.clone(),
+            })
+            .unwrap(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // fund the staking contract then deposit the rewards
+    app.set_balances_from(
+        Addr::unchecked("addr"),
+        &[(
+            &ORAI_DENOM.to_string(),
+            &[(&staking_addr.to_string(), &Uint128::from(1000u128))],
+        )],
+    );
+    for token_addr in [asset_addr.clone(), reward_addr.clone()] {
+        app.execute(
+            Addr::unchecked("addr"),
+            token_addr,
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: staking_addr.to_string(),
+                amount: Uint128::from(1000u128),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+    app.execute(
+        Addr::unchecked("rewarder"),
+        staking_addr.clone(),
+        &ExecuteMsg::DepositReward {
+            rewards: vec![Asset {
+                info: asset_info.clone(),
+                amount: Uint128::from(3000u128),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let compound_msg = ExecuteMsg::Compound {
+        asset_info: asset_info.clone(),
+        slippage_tolerance: Some(Decimal::percent(1)),
+        reward_minimum_receives: vec![Asset {
+            info: AssetInfo::Token {
+                contract_addr: reward_addr.clone(),
+            },
+            amount: Uint128::from(1u128),
+        }],
+    };
+
+    // router is not set
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    );
+    app.assert_fail(res);
+
+    app.execute(
+        Addr::unchecked("owner"),
+        staking_addr.clone(),
+        &ExecuteMsg::UpdateConfig {
+            rewarder: None,
+            owner: None,
+            router: Some(router_addr.clone()),
+        },
+        &[],
+    )
+    .unwrap();
+
+    // only the contract can execute the hook
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::CompoundHook {
+            asset_info: asset_info.clone(),
+            staker_addr: Addr::unchecked("addr"),
+            assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: ORAI_DENOM.to_string(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+                Asset {
+                    info: asset_info.clone(),
+                    amount: Uint128::from(1000u128),
+                },
+            ],
+            prev_balances: [Uint128::zero(), Uint128::zero()],
+            slippage_tolerance: None,
+            balanced: true,
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // the reward swap needs a minimum receive
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::Compound {
+            asset_info: asset_info.clone(),
+            slippage_tolerance: None,
+            reward_minimum_receives: vec![],
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // reward swap can not receive enough
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &ExecuteMsg::Compound {
+            asset_info: asset_info.clone(),
+            slippage_tolerance: None,
+            reward_minimum_receives: vec![Asset {
+                info: AssetInfo::Token {
+                    contract_addr: reward_addr.clone(),
+                },
+                amount: Uint128::from(1000u128),
+            }],
+        },
+        &[],
+    );
+    app.assert_fail(res);
+
+    // balancing swap needs a slippage tolerance, it can not receive the pool price return
+    for slippage_tolerance in [None, Some(Decimal::zero())] {
+        let res = app.execute(
+            Addr::unchecked("addr"),
+            staking_addr.clone(),
+            &ExecuteMsg::Compound {
+                asset_info: asset_info.clone(),
+                slippage_tolerance,
+                reward_minimum_receives: vec![Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: reward_addr.clone(),
+                    },
+                    amount: Uint128::from(1u128),
+                }],
+            },
+            &[],
+        );
+        app.assert_fail(res);
+    }
+
+    app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    )
+    .unwrap();
+
+    // rewards are bonded as lp tokens, nothing is left to withdraw
+    let res: RewardInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr"),
+                asset_info: Some(asset_info.clone()),
+            },
+        )
+        .unwrap();
+    let reward_info = &res.reward_infos[0];
+    assert!(reward_info.pending_withdraw.is_empty());
+    assert!(reward_info.bond_amount > Uint128::from(2400u128));
+
+    let pool_info: PoolInfoResponse = app
+        .query(
+            staking_addr.clone(),
+            &QueryMsg::PoolInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(pool_info.total_bond_amount, reward_info.bond_amount);
+
+    // all the rewards were compounded
+    assert!(
+        app.query_balance(staking_addr.clone(), ORAI_DENOM.to_string())
+            .unwrap()
+            < Uint128::from(5u128)
+    );
+
+    // no reward to compound
+    let res = app.execute(
+        Addr::unchecked("addr"),
+        staking_addr.clone(),
+        &compound_msg,
+        &[],
+    );
+    app.assert_fail(res);
+}
+
+#[test]
+fn test_lock_boost() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // one week lock boosts 1.5x, one year lock boosts 3x, early unlock loses 10%
+    let msg = ExecuteMsg::UpdateLockTiers {
+        tiers: vec![
+            LockTier {
+                duration: 604800,
+                boost: Decimal::from_ratio(3u128, 2u128),
+            },
+            LockTier {
+                duration: 31536000,
+                boost: Decimal::from_ratio(3u128, 1u128),
+            },
+        ],
+        early_unlock_penalty: Decimal::percent(10),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    // bond 100 tokens from both accounts
+    for staker in ["addr", "addr2"] {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: asset_info.clone(),
+            })
+            .unwrap(),
+        });
+        let info = mock_info("staking", &[]);
+        let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    // duration must be one of the tiers
+    let msg = ExecuteMsg::Lock {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(100u128),
+        duration: 86400,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Invalid lock duration"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // lock all bond of addr for one year
+    let msg = ExecuteMsg::Lock {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(100u128),
+        duration: 31536000,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "lock"),
+            attr("staker_addr", "addr"),
+            attr("asset_info", "asset"),
+            attr("lock_id", "1"),
+            attr("amount", "100"),
+            attr("boost_amount", "200"),
+            attr(
+                "unlock_time",
+                (mock_env().block.time.seconds() + 31536000).to_string()
+            ),
+        ]
+    );
+
+    // 400 rewards are shared by the effective bonds 300 and 100
+    let msg = ExecuteMsg::DepositReward {
+        rewards: vec![Asset {
+            info: asset_info.clone(),
+            amount: Uint128::from(400u128),
+        }],
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("rewarder", &[]), msg).unwrap();
+
+    for (staker, pending_reward) in [("addr", 300u128), ("addr2", 100u128)] {
+        let res: RewardInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RewardInfo {
+                    staker_addr: Addr::unchecked(staker),
+                    asset_info: Some(asset_info.clone()),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.reward_infos[0].pending_reward,
+            Uint128::from(pending_reward)
+        );
+    }
+
+    let res: LocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Locks {
+                staker_addr: Addr::unchecked("addr"),
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        LocksResponse {
+            staker_addr: Addr::unchecked("addr"),
+            asset_info: asset_info.clone(),
+            bond_amount: Uint128::from(100u128),
+            effective_bond_amount: Uint128::from(300u128),
+            locks: vec![LockResponse {
+                lock_id: 1,
+                amount: Uint128::from(100u128),
+                boost_amount: Uint128::from(200u128),
+                unlock_time: mock_env().block.time.seconds() + 31536000,
+            }],
+        }
+    );
+
+    // locked amount can not be unbonded
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(1u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Cannot unbond locked amount"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // unlock early, 10 tokens are forfeited to the owner
+    let msg = ExecuteMsg::Unlock {
+        asset_info: asset_info.clone(),
+        lock_id: 1,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "owner".to_string(),
+                amount: Uint128::from(10u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(190u128));
+
+    // lock for one week, after a week anyone can release the lock
+    let msg = ExecuteMsg::Lock {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(90u128),
+        duration: 604800,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::ReleaseExpiredLocks {
+        staker_addr: Addr::unchecked("addr"),
+        asset_info: asset_info.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No expired lock"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(604800);
+    let _res = execute(deps.as_mut(), env.clone(), mock_info("keeper", &[]), msg).unwrap();
+
+    let res: LocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Locks {
+                staker_addr: Addr::unchecked("addr"),
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.effective_bond_amount, Uint128::from(90u128));
+    assert_eq!(res.locks, vec![]);
+
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(90u128),
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr", &[]), msg).unwrap();
+
+    let res: LocksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Locks {
+                staker_addr: Addr::unchecked("addr"),
+                asset_info,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.bond_amount, Uint128::zero());
+    assert_eq!(res.locks, vec![]);
+}
+
+#[test]
+fn test_unbond_with_unbonding_period() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // one day unbonding period
+    let msg = ExecuteMsg::UpdateUnbondingPeriod {
+        asset_info: asset_info.clone(),
+        unbonding_period: 86400,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            asset_info: asset_info.clone(),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // unbond twice, nothing is transferred yet
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(60u128),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![]);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(3600);
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(40u128),
+    };
+    let _res = execute(deps.as_mut(), env, mock_info("addr", &[]), msg).unwrap();
+
+    // unbonded amount does not earn rewards anymore
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::zero());
+    assert_eq!(pool_info.unbonding_period, 86400);
+
+    let res: ClaimsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Claims {
+                staker_addr: Addr::unchecked("addr"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.claims,
+        vec![
+            Claim {
+                asset_info: asset_info.clone(),
+                staking_token: Addr::unchecked("staking"),
+                amount: Uint128::from(60u128),
+                release_at: mock_env().block.time.seconds() + 86400,
+            },
+            Claim {
+                asset_info: asset_info.clone(),
+                staking_token: Addr::unchecked("staking"),
+                amount: Uint128::from(40u128),
+                release_at: mock_env().block.time.seconds() + 90000,
+            },
+        ]
+    );
+
+    // nothing matured yet
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr", &[]),
+        ExecuteMsg::ClaimUnbonded {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No unbonded amount to claim"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // the first claim is released after one day
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr", &[]),
+        ExecuteMsg::ClaimUnbonded {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr".to_string(),
+                amount: Uint128::from(60u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    let res: ClaimsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Claims {
+                staker_addr: Addr::unchecked("addr"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.claims.len(), 1);
+    assert_eq!(res.claims[0].amount, Uint128::from(40u128));
+}
+
+#[test]
+fn test_transfer_position_and_reward_recipient() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateRewardsPerSec {
+        asset_info: asset_info.clone(),
+        assets: vec![Asset {
+            info: AssetInfo::NativeToken {
+                denom: ORAI_DENOM.to_string(),
+            },
+            amount: Uint128::from(100u128),
+        }],
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // bond 100 tokens
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Bond {
+            asset_info: asset_info.clone(),
+        })
+        .unwrap(),
+    });
+    let info = mock_info("staking", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let deposit_msg = ExecuteMsg::DepositReward {
+        rewards: vec![Asset {
+            info: asset_info.clone(),
+            amount: Uint128::from(100u128),
+        }],
+    };
+    let info = mock_info("rewarder", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_msg.clone()).unwrap();
+
+    // transfer more than bond amount
+    let msg = ExecuteMsg::TransferPosition {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(101u128),
+        to: Addr::unchecked("addr2"),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot unbond more than bond amount")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // transfer to self
+    let msg = ExecuteMsg::TransferPosition {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(40u128),
+        to: Addr::unchecked("addr"),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Cannot transfer position to self")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = ExecuteMsg::TransferPosition {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(40u128),
+        to: Addr::unchecked("addr2"),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+
+    // the accrued reward stays with the sender
+    for (staker, bond_amount, pending_reward) in [("addr", 60u128, 100u128), ("addr2", 40, 0)] {
+        let res: RewardInfoResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::RewardInfo {
+                    staker_addr: Addr::unchecked(staker),
+                    asset_info: Some(asset_info.clone()),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.reward_infos[0].bond_amount, Uint128::from(bond_amount));
+        assert_eq!(
+            res.reward_infos[0].pending_reward,
+            Uint128::from(pending_reward)
+        );
+    }
+
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+
+    // new rewards are shared by the transferred bond
+    let info = mock_info("rewarder", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_msg).unwrap();
+
+    let msg = ExecuteMsg::SetRewardRecipient {
+        recipient: Addr::unchecked("custody"),
+    };
+    let info = mock_info("addr", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res: RewardRecipientResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardRecipient {
+                staker_addr: Addr::unchecked("addr"),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.recipient, Addr::unchecked("custody"));
+
+    let msg = ExecuteMsg::Withdraw {
+        asset_info: Some(asset_info.clone()),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "custody".to_string(),
+            amount: vec![coin(160u128, ORAI_DENOM)],
+        }))]
+    );
+
+    // the staker itself resets the recipient
+    let msg = ExecuteMsg::SetRewardRecipient {
+        recipient: Addr::unchecked("addr"),
+    };
+    let info = mock_info("addr", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Withdraw {
+        asset_info: Some(asset_info),
+    };
+    let info = mock_info("addr2", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr2".to_string(),
+            amount: vec![coin(40u128, ORAI_DENOM)],
+        }))]
+    );
+}
+
+#[test]
+fn test_emergency_unbond() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // the unbonding period is skipped
+    let msg = ExecuteMsg::UpdateUnbondingPeriod {
+        asset_info: asset_info.clone(),
+        unbonding_period: 100,
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let bond = |deps: DepsMut, staker: &str| {
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset"),
+                },
+            })
+            .unwrap(),
+        });
+        let info = mock_info("staking", &[]);
+        execute(deps, mock_env(), info, msg).unwrap();
+    };
+    bond(deps.as_mut(), "addr");
+    bond(deps.as_mut(), "addr2");
+
+    let deposit_msg = ExecuteMsg::DepositReward {
+        rewards: vec![Asset {
+            info: asset_info.clone(),
+            amount: Uint128::from(100u128),
+        }],
+    };
+    let info = mock_info("rewarder", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_msg.clone()).unwrap();
+
+    // settle the reward of addr to its pending reward
+    bond(deps.as_mut(), "addr");
+
+    // half of the bond of addr is locked, early unlock loses 10%
+    let msg = ExecuteMsg::UpdateLockTiers {
+        tiers: vec![LockTier {
+            duration: 604800,
+            boost: Decimal::from_ratio(3u128, 2u128),
+        }],
+        early_unlock_penalty: Decimal::percent(10),
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+    let msg = ExecuteMsg::Lock {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(100u128),
+        duration: 604800,
+    };
+    let _res = execute(deps.as_mut(), mock_env(), mock_info("addr", &[]), msg).unwrap();
+
+    let msg = ExecuteMsg::EmergencyUnbond {
+        asset_info: asset_info.clone(),
+    };
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "Emergency unbond is not enabled")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    let info = mock_info("addr", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SetEmergency { emergency: true },
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "unauthorized"),
+        _ => panic!("Must return unauthorized error"),
+    }
+
+    let info = mock_info("owner", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SetEmergency { emergency: true },
+    )
+    .unwrap();
+
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "staking".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "addr".to_string(),
+                    amount: Uint128::from(190u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "staking".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "owner".to_string(),
+                    amount: Uint128::from(10u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "emergency_unbond"),
+            attr("staker_addr", "addr"),
+            attr("asset_info", "asset"),
+            attr("amount", "190"),
+            attr("penalty_amount", "10"),
+            attr("staking_token", "staking"),
+            attr("forfeited_reward", "50"),
+        ]
+    );
+
+    // the position is closed
+    let info = mock_info("addr", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "No bond amount to unbond"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let pool_info: PoolInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::PoolInfo {
+                asset_info: asset_info.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pool_info.total_bond_amount, Uint128::from(100u128));
+    assert_eq!(pool_info.pending_reward, Uint128::from(50u128));
+
+    // the forfeited reward is shared with the next deposit
+    let info = mock_info("rewarder", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, deposit_msg).unwrap();
+
+    let res: RewardInfoResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RewardInfo {
+                staker_addr: Addr::unchecked("addr2"),
+                asset_info: Some(asset_info.clone()),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.reward_infos[0].pending_reward, Uint128::from(200u128));
+
+    // a reward state that can not be settled does not block the exit
+    let staker_addr_raw = deps.as_ref().api.addr_canonicalize("addr2").unwrap();
+    let asset_key = asset_info.to_vec(deps.as_ref().api).unwrap();
+    let mut reward_info = rewards_read(&deps.storage, &staker_addr_raw)
+        .load(&asset_key)
+        .unwrap();
+    reward_info.index = Decimal::MAX;
+    rewards_store(&mut deps.storage, &staker_addr_raw)
+        .save(&asset_key, &reward_info)
+        .unwrap();
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(100u128),
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), msg).unwrap_err();
+
+    let msg = ExecuteMsg::EmergencyUnbond { asset_info };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr2", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: "staking".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr2".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+}
+
+#[test]
+fn test_bond_snapshots() {
+    let mut deps = mock_dependencies();
+
+    let msg = InstantiateMsg {
+        owner: Some(Addr::unchecked("owner")),
+        rewarder: Addr::unchecked("rewarder"),
+        minter: Some(Addr::unchecked("mint")),
+        oracle_addr: Addr::unchecked("oracle"),
+        factory_addr: Addr::unchecked("factory"),
+        base_denom: None,
+    };
+
+    let info = mock_info("addr", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let asset_info = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset"),
+    };
+    let msg = ExecuteMsg::RegisterAsset {
+        asset_info: asset_info.clone(),
+        staking_token: Addr::unchecked("staking"),
+    };
+    let info = mock_info("owner", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env_at = |height: u64| {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    };
+    let bond_msg = |staker: &str, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Bond {
+                asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset"),
+                },
+            })
+            .unwrap(),
+        })
+    };
+
+    let info = mock_info("staking", &[]);
+    let _res = execute(
+        deps.as_mut(),
+        env_at(10),
+        info.clone(),
+        bond_msg("addr", 100),
+    )
+    .unwrap();
+    let _res = execute(deps.as_mut(), env_at(20), info, bond_msg("addr2", 50)).unwrap();
+
+    let msg = ExecuteMsg::Unbond {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(30u128),
+    };
+    let info = mock_info("addr", &[]);
+    let _res = execute(deps.as_mut(), env_at(30), info, msg).unwrap();
+
+    let msg = ExecuteMsg::TransferPosition {
+        asset_info: asset_info.clone(),
+        amount: Uint128::from(20u128),
+        to: Addr::unchecked("addr2"),
+    };
+    let info = mock_info("addr", &[]);
+    let _res = execute(deps.as_mut(), env_at(40), info, msg).unwrap();
+
+    // the amounts are taken at the start of the height
+    for (staker, height, bond_amount) in [
+        ("addr", 5u64, 0u128),
+        ("addr", 10, 0),
+        ("addr", 11, 100),
+        ("addr", 30, 100),
+        ("addr", 31, 70),
+        ("addr", 41, 50),
+        ("addr", 100, 50),
+        ("addr2", 20, 0),
+        ("addr2", 21, 50),
+        ("addr2", 40, 50),
+        ("addr2", 41, 70),
+    ] {
+        let res: BondAtResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BondAt {
+                    staker_addr: Addr::unchecked(staker),
+                    asset_info: asset_info.clone(),
+                    height,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.bond_amount, Uint128::from(bond_amount));
+    }
+
+    for (height, total_bond_amount) in [(10u64, 0u128), (11, 100), (21, 150), (31, 120), (41, 120)]
+    {
+        let res: TotalBondAtResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::TotalBondAt {
+                    asset_info: asset_info.clone(),
+                    height,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.total_bond_amount, Uint128::from(total_bond_amount));
+    }
+}
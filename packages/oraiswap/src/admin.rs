@@ -0,0 +1,175 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::serde::{de::DeserializeOwned, Serialize};
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Api, Attribute, Binary, CanonicalAddr, Env, Order, StdError,
+    StdResult, Storage,
+};
+use cosmwasm_storage::{singleton, singleton_read, Bucket, ReadonlyBucket};
+
+use crate::querier::calc_range_start;
+
+static KEY_PENDING_ADMIN: &[u8] = b"pending_admin";
+static KEY_TIMELOCK_DELAY: &[u8] = b"timelock_delay";
+static KEY_TIMELOCK_NEXT_ID: &[u8] = b"timelock_next_id";
+static PREFIX_TIMELOCK_CHANGE: &[u8] = b"timelock_change";
+
+// a longer delay could lock the parameters for good
+pub const MAX_TIMELOCK_DELAY: u64 = 30 * 86400;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+#[cw_serde]
+pub struct PendingAdminResponse {
+    pub pending_admin: Option<Addr>,
+}
+
+/// TimelockChange is a queued execute message of the contract, applied once eta is reached
+#[cw_serde]
+pub struct TimelockChange {
+    pub id: u64,
+    pub msg: Binary,
+    pub eta: u64,
+}
+
+impl TimelockChange {
+    pub fn attributes(&self) -> Vec<Attribute> {
+        vec![
+            Attribute::new("action", "queue_change"),
+            Attribute::new("change_id", self.id.to_string()),
+            Attribute::new("eta", self.eta.to_string()),
+        ]
+    }
+}
+
+#[cw_serde]
+pub struct TimelockResponse {
+    pub delay: u64,
+    pub changes: Vec<TimelockChange>,
+}
+
+/// the new admin takes over once it accepts
+pub fn propose_admin(storage: &mut dyn Storage, api: &dyn Api, admin: &Addr) -> StdResult<()> {
+    let admin_raw = api.addr_canonicalize(admin.as_str())?;
+    singleton(storage, KEY_PENDING_ADMIN).save(&admin_raw)
+}
+
+/// returns the admin to store when the sender is the proposed one
+pub fn accept_admin(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    sender: &Addr,
+) -> StdResult<CanonicalAddr> {
+    let pending_admin: CanonicalAddr = singleton_read(storage, KEY_PENDING_ADMIN)
+        .may_load()?
+        .ok_or_else(|| StdError::generic_err("No admin proposed"))?;
+    if pending_admin != api.addr_canonicalize(sender.as_str())? {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    singleton::<CanonicalAddr>(storage, KEY_PENDING_ADMIN).remove();
+    Ok(pending_admin)
+}
+
+pub fn query_pending_admin(
+    storage: &dyn Storage,
+    api: &dyn Api,
+) -> StdResult<PendingAdminResponse> {
+    let pending_admin: Option<CanonicalAddr> =
+        singleton_read(storage, KEY_PENDING_ADMIN).may_load()?;
+
+    Ok(PendingAdminResponse {
+        pending_admin: pending_admin
+            .map(|admin| api.addr_humanize(&admin))
+            .transpose()?,
+    })
+}
+
+pub fn read_timelock_delay(storage: &dyn Storage) -> StdResult<u64> {
+    Ok(singleton_read(storage, KEY_TIMELOCK_DELAY)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_timelock_delay(storage: &mut dyn Storage, delay: u64) -> StdResult<()> {
+    if delay > MAX_TIMELOCK_DELAY {
+        return Err(StdError::generic_err("Timelock delay is too long"));
+    }
+
+    singleton(storage, KEY_TIMELOCK_DELAY).save(&delay)
+}
+
+/// queues the message when a delay is set, otherwise it applies right away
+pub fn queue_change<T: Serialize>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    msg: &T,
+) -> StdResult<Option<TimelockChange>> {
+    let delay = read_timelock_delay(storage)?;
+    if delay == 0 {
+        return Ok(None);
+    }
+
+    let mut next_id = singleton(storage, KEY_TIMELOCK_NEXT_ID);
+    let id: u64 = next_id.may_load()?.unwrap_or_default() + 1;
+    next_id.save(&id)?;
+
+    let change = TimelockChange {
+        id,
+        msg: to_binary(msg)?,
+        eta: env.block.time.seconds() + delay,
+    };
+    Bucket::new(storage, PREFIX_TIMELOCK_CHANGE).save(&id.to_be_bytes(), &change)?;
+
+    Ok(Some(change))
+}
+
+/// removes the change from the queue once its eta is reached
+pub fn take_change<T: DeserializeOwned>(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+) -> StdResult<T> {
+    let change = read_change(storage, id)?;
+    if env.block.time.seconds() < change.eta {
+        return Err(StdError::generic_err(format!(
+            "Timelocked change {} is not ready",
+            id
+        )));
+    }
+
+    Bucket::<TimelockChange>::new(storage, PREFIX_TIMELOCK_CHANGE).remove(&id.to_be_bytes());
+    from_binary(&change.msg)
+}
+
+pub fn cancel_change(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    read_change(storage, id)?;
+    Bucket::<TimelockChange>::new(storage, PREFIX_TIMELOCK_CHANGE).remove(&id.to_be_bytes());
+    Ok(())
+}
+
+pub fn query_timelock(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<TimelockResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after.map(|id| id.to_be_bytes().to_vec()));
+    let changes = ReadonlyBucket::new(storage, PREFIX_TIMELOCK_CHANGE)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, change)| change))
+        .collect::<StdResult<Vec<TimelockChange>>>()?;
+
+    Ok(TimelockResponse {
+        delay: read_timelock_delay(storage)?,
+        changes,
+    })
+}
+
+fn read_change(storage: &dyn Storage, id: u64) -> StdResult<TimelockChange> {
+    ReadonlyBucket::new(storage, PREFIX_TIMELOCK_CHANGE)
+        .may_load(&id.to_be_bytes())?
+        .ok_or_else(|| StdError::generic_err(format!("Timelocked change {} not found", id)))
+}
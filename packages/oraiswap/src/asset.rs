@@ -1,7 +1,7 @@
 use cosmwasm_schema::cw_serde;
 use std::fmt;
 
-use crate::oracle::OracleContract;
+use crate::oracle::{OracleContract, TaxRateForResponse};
 use crate::querier::query_token_balance;
 
 use cosmwasm_std::{
@@ -29,21 +29,33 @@ impl Asset {
         self.info.is_native_token()
     }
 
+    /// tax of sending the asset to recipient, exempt recipients are not taxed
     pub fn compute_tax(
         &self,
         oracle_contract: &OracleContract,
         querier: &QuerierWrapper,
+        recipient: Option<&Addr>,
     ) -> StdResult<Uint128> {
         let amount = self.amount;
         if let AssetInfo::NativeToken { denom } = &self.info {
             if denom == ORAI_DENOM {
                 Ok(Uint128::from(0u64))
             } else {
-                // get oracle params from oracle contract
-                let tax_rate = oracle_contract.query_tax_rate(querier)?.rate;
-                let tax_cap = oracle_contract
-                    .query_tax_cap(querier, denom.to_string())?
-                    .cap;
+                // get oracle params from oracle contract, an oracle not migrated yet
+                // has neither per denom rates nor exemptions
+                let (tax_rate, tax_cap) = match oracle_contract.query_tax_rate_for(
+                    querier,
+                    denom.to_string(),
+                    recipient.cloned(),
+                ) {
+                    Ok(TaxRateForResponse { rate, cap, .. }) => (rate, cap),
+                    Err(_) => (
+                        oracle_contract.query_tax_rate(querier)?.rate,
+                        oracle_contract
+                            .query_tax_cap(querier, denom.to_string())?
+                            .cap,
+                    ),
+                };
 
                 Ok(std::cmp::min(
                     amount.checked_sub(amount * (Decimal::one() / (tax_rate + Decimal::one())))?,
@@ -59,12 +71,17 @@ impl Asset {
         &self,
         oracle_contract: &OracleContract,
         querier: &QuerierWrapper,
+        recipient: Option<&Addr>,
     ) -> StdResult<Coin> {
         let amount = self.amount;
         if let AssetInfo::NativeToken { denom } = &self.info {
             Ok(Coin {
                 denom: denom.to_string(),
-                amount: amount.checked_sub(self.compute_tax(oracle_contract, querier)?)?,
+                amount: amount.checked_sub(self.compute_tax(
+                    oracle_contract,
+                    querier,
+                    recipient,
+                )?)?,
             })
         } else {
             Err(StdError::generic_err("cannot deduct tax from token asset"))
@@ -92,7 +109,7 @@ impl Asset {
             AssetInfo::NativeToken { denom } => {
                 // if there is oracle contract then calculate tax deduction
                 let send_amount = if let Some(oracle_contract) = oracle_contract {
-                    self.deduct_tax(oracle_contract, querier)?
+                    self.deduct_tax(oracle_contract, querier, Some(&recipient))?
                 } else {
                     coin(amount.u128(), denom)
                 };
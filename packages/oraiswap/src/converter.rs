@@ -2,6 +2,7 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 
 use cosmwasm_std::{Addr, Decimal};
 
+use crate::admin::PendingAdminResponse;
 use crate::asset::AssetInfo;
 use cw20::Cw20ReceiveMsg;
 
@@ -29,9 +30,11 @@ pub enum ExecuteMsg {
     ///////////////////
     /// Owner Operations
     ///////////////////
+    // proposes the new owner, who takes over with AcceptAdmin
     UpdateConfig {
         owner: Addr,
     },
+    AcceptAdmin {},
     Convert {},
     UpdatePair {
         from: TokenInfo,
@@ -53,6 +56,8 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
     #[returns(ConvertInfoResponse)]
     ConvertInfo { asset_info: AssetInfo },
 }
@@ -33,6 +33,18 @@ pub enum ContractError {
     #[error("Too small offer amount")]
     TooSmallOfferAmount {},
 
+    #[error("Too small ask amount, must be at least the lot size")]
+    TooSmallAskAmount {},
+
+    #[error("Invalid tick size, tick size multiplied by lot size must be an integer amount")]
+    InvalidTickSize {},
+
+    #[error("Order book is not accepting new orders")]
+    OrderBookPaused {},
+
+    #[error("Order book matching is halted")]
+    OrderBookHalted {},
+
     #[error("Offer pool is zero")]
     OfferPoolIsZero {},
 
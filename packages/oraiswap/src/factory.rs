@@ -1,6 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::Addr;
 
+use crate::admin::PendingAdminResponse;
 use crate::asset::{AssetInfo, PairInfo};
 
 #[cw_serde]
@@ -14,12 +15,13 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// UpdateConfig update relevant code IDs
+    /// UpdateConfig update relevant code IDs, the new owner takes over with AcceptAdmin
     UpdateConfig {
         owner: Option<String>,
         token_code_id: Option<u64>,
         pair_code_id: Option<u64>,
     },
+    AcceptAdmin {},
     /// CreatePair instantiates pair contract
     CreatePair {
         /// Asset infos
@@ -32,6 +34,8 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
     #[returns(PairInfo)]
     Pair { asset_infos: [AssetInfo; 2] },
     #[returns(PairsResponse)]
@@ -39,6 +43,13 @@ pub enum QueryMsg {
         start_after: Option<[AssetInfo; 2]>,
         limit: Option<u32>,
     },
+    /// PairsByAsset pages the pairs trading the asset, ordered by the other asset
+    #[returns(PairsResponse)]
+    PairsByAsset {
+        asset_info: AssetInfo,
+        start_after: Option<AssetInfo>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -0,0 +1,118 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+
+use cosmwasm_std::{Addr, Decimal, Uint128};
+
+use crate::admin::PendingAdminResponse;
+use crate::asset::AssetInfo;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    pub staking_contract: Addr,
+    // pool of the staking contract whose bonded lp gives the voting power
+    pub vote_asset_info: AssetInfo,
+    pub epoch_duration: u64,
+    pub start_time: Option<u64>,
+}
+
+#[cw_serde]
+pub struct GaugeVote {
+    pub asset_info: AssetInfo,
+    pub ratio: Decimal,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    ///////////////////
+    /// Owner Operations
+    ///////////////////
+    // proposes the new owner, who takes over with AcceptAdmin
+    UpdateConfig {
+        owner: Option<Addr>,
+    },
+    AcceptAdmin {},
+    AddGauge {
+        asset_info: AssetInfo,
+    },
+    RemoveGauge {
+        asset_info: AssetInfo,
+    },
+
+    ////////////////////
+    /// User Operations
+    ////////////////////
+    // split the voting power between gauges, the votes of an epoch set the weights of the next one
+    Vote {
+        votes: Vec<GaugeVote>,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    #[returns(ConfigResponse)]
+    Config {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
+    #[returns(GaugesResponse)]
+    Gauges {},
+    // default is the current epoch, the next epoch gives the weights of the ongoing vote
+    #[returns(WeightsResponse)]
+    Weights { epoch: Option<u64> },
+    #[returns(PoolWeightResponse)]
+    PoolWeight {
+        asset_info: AssetInfo,
+        epoch: Option<u64>,
+    },
+    #[returns(VoteResponse)]
+    Vote { voter: Addr, epoch: Option<u64> },
+}
+
+// We define a custom struct for each query response
+#[cw_serde]
+pub struct ConfigResponse {
+    pub owner: Addr,
+    pub staking_contract: Addr,
+    pub vote_asset_info: AssetInfo,
+    pub epoch_duration: u64,
+    pub start_time: u64,
+    pub current_epoch: u64,
+}
+
+#[cw_serde]
+pub struct GaugesResponse {
+    pub gauges: Vec<AssetInfo>,
+}
+
+#[cw_serde]
+pub struct GaugeWeight {
+    pub asset_info: AssetInfo,
+    pub power: Uint128,
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub struct WeightsResponse {
+    pub epoch: u64,
+    // epoch whose votes give the weights, none before the first vote
+    pub vote_epoch: Option<u64>,
+    pub total_power: Uint128,
+    pub weights: Vec<GaugeWeight>,
+}
+
+#[cw_serde]
+pub struct PoolWeightResponse {
+    pub asset_info: AssetInfo,
+    pub epoch: u64,
+    pub weight: Decimal,
+}
+
+#[cw_serde]
+pub struct VoteResponse {
+    pub voter: Addr,
+    pub epoch: u64,
+    pub power: Uint128,
+    pub votes: Vec<GaugeVote>,
+}
@@ -1,7 +1,9 @@
+pub mod admin;
 pub mod asset;
 pub mod converter;
 pub mod error;
 pub mod factory;
+pub mod gauge;
 pub mod ibc;
 pub mod limit_order;
 pub mod math;
@@ -1,3 +1,4 @@
+use crate::admin::PendingAdminResponse;
 use crate::asset::{Asset, AssetInfo};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 use cosmwasm_std::{Addr, CanonicalAddr, Decimal, Uint128};
@@ -9,6 +10,8 @@ pub struct ContractInfo {
     pub version: String,
     // admin can update the parameter, may be multisig
     pub admin: CanonicalAddr,
+    // resolves the amm pair of an order book
+    pub factory_addr: Option<CanonicalAddr>,
 }
 
 #[cw_serde]
@@ -32,26 +35,75 @@ impl Default for OrderDirection {
         OrderDirection::Buy
     }
 }
+
+#[cw_serde]
+#[derive(Copy, Default)]
+pub enum OrderBookStatus {
+    #[default]
+    Active,
+    Paused,    // no new orders can be submitted, existing orders can still be matched
+    Halted,    // no new orders and no matching, bidders can only cancel their orders
+    Delisting, // open orders are being refunded, the order book is removed once empty
+}
+
+#[cw_serde]
+#[derive(Copy)]
+pub enum SelfTradePrevention {
+    CancelNewest,  // the newer order is cancelled, the older one keeps resting
+    CancelOldest,  // the older order is cancelled, the newer one keeps matching
+    DecrementBoth, // both orders are decreased by the overlapping amount and refunded
+}
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub name: Option<String>,
     pub version: Option<String>,
     pub admin: Option<Addr>,
+    pub factory_addr: Option<Addr>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
 
+    // proposes the new admin, who takes over with AcceptAdmin
     UpdateAdmin {
         admin: Addr,
     },
 
+    AcceptAdmin {},
+
     UpdateOrderBook {
         offer_info: AssetInfo,
         ask_info: AssetInfo,
         precision: Option<Decimal>,
         min_offer_amount: Uint128,
+        // price must be a multiple of tick size, and ask amount a multiple of lot size
+        tick_size: Option<Decimal>,
+        lot_size: Option<Uint128>,
+        // orders of the same bidder are not matched with each other, none to allow self trade
+        self_trade_prevention: Option<SelfTradePrevention>,
+    },
+
+    UpdateOrderBookStatus {
+        offer_info: AssetInfo,
+        ask_info: AssetInfo,
+        status: OrderBookStatus,
+    },
+
+    /// Refund open orders to their bidders with pagination, then remove the order book
+    DelistOrderBook {
+        offer_info: AssetInfo,
+        ask_info: AssetInfo,
+        limit: Option<u32>,
+    },
+
+    /// Re-bucket existing orders to the current tick size and lot size, with pagination
+    RebucketOrderBook {
+        offer_info: AssetInfo,
+        ask_info: AssetInfo,
+        start_after: Option<u64>,
+        limit: Option<u32>,
     },
 
     ///////////////////////
@@ -61,6 +113,9 @@ pub enum ExecuteMsg {
         direction: OrderDirection, // default is buy, with sell then it is reversed
         offer_asset: Asset,
         ask_asset: Asset,
+        /// the order is filled from the book or the factory pair of the same assets, whichever is better
+        #[serde(default)]
+        amm_fallback: bool,
     },
     CancelOrder {
         order_id: u64,
@@ -78,9 +133,13 @@ pub enum ExecuteMsg {
 
 #[cw_serde]
 pub enum Cw20HookMsg {
+    /// the sent token must be the paid asset, offer asset for buy and ask asset for sell
     SubmitOrder {
-        ask_asset: Asset,
         direction: OrderDirection,
+        offer_asset: Asset,
+        ask_asset: Asset,
+        #[serde(default)]
+        amm_fallback: bool,
     },
 
     /// Arbitrager execute order to get profit
@@ -103,6 +162,8 @@ pub enum OrderFilter {
 pub enum QueryMsg {
     #[returns(ContractInfoResponse)]
     ContractInfo {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
     #[returns(OrderBookResponse)]
     OrderBook {
         offer_info: AssetInfo,
@@ -157,6 +218,7 @@ pub struct ContractInfoResponse {
 
     // admin can update the parameter, may be multisig
     pub admin: Addr,
+    pub factory_addr: Option<Addr>,
 }
 
 #[cw_serde]
@@ -176,6 +238,10 @@ pub struct OrderBookResponse {
     pub ask_info: AssetInfo,
     pub min_offer_amount: Uint128,
     pub precision: Option<Decimal>,
+    pub tick_size: Option<Decimal>,
+    pub lot_size: Option<Uint128>,
+    pub status: OrderBookStatus,
+    pub self_trade_prevention: Option<SelfTradePrevention>,
 }
 
 #[cw_serde]
@@ -206,4 +272,6 @@ pub struct LastOrderIdResponse {
 
 /// We currently take no arguments for migrations
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    pub factory_addr: Option<Addr>,
+}
@@ -1,6 +1,9 @@
 use cosmwasm_schema::serde::de::DeserializeOwned;
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
+use crate::admin::{PendingAdminResponse, TimelockResponse};
+use crate::asset::AssetInfo;
+
 #[allow(unused_imports)]
 use cosmwasm_std::{
     to_binary, Addr, Api, CanonicalAddr, Coin, CosmosMsg, Decimal, QuerierWrapper, StdResult,
@@ -19,9 +22,21 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    // proposes the new admin, who takes over with AcceptAdmin
     UpdateAdmin {
         admin: Addr,
     },
+    AcceptAdmin {},
+    // tax changes wait for the delay once it is set, the delay change too
+    UpdateTimelock {
+        delay: u64,
+    },
+    ExecuteTimelocked {
+        id: u64,
+    },
+    CancelTimelocked {
+        id: u64,
+    },
     UpdateExchangeRate {
         denom: String,
         exchange_rate: Decimal,
@@ -37,6 +52,65 @@ pub enum ExecuteMsg {
     UpdateTaxRate {
         rate: Decimal,
     },
+    AddFeeder {
+        feeder: Addr,
+    },
+    RemoveFeeder {
+        feeder: Addr,
+    },
+    // max_age zero means the rates never expire
+    UpdateFeederConfig {
+        min_feeders: Option<u32>,
+        round_duration: Option<u64>,
+        max_deviation: Option<Decimal>,
+        max_age: Option<u64>,
+    },
+    // submit the prices of the current rounds, only whitelisted feeders
+    FeedExchangeRates {
+        exchange_rates: Vec<ExchangeRateFeed>,
+    },
+    // RateMax: 1%, none falls back to the global tax rate
+    UpdateDenomTaxRate {
+        denom: String,
+        rate: Option<Decimal>,
+    },
+    // transfers to exempt addresses are not taxed
+    AddTaxExempt {
+        address: Addr,
+    },
+    RemoveTaxExempt {
+        address: Addr,
+    },
+    // zero means no limit
+    UpdateHistoryConfig {
+        max_entries: Option<u32>,
+        retention: Option<u64>,
+    },
+    // assets without fed rate are priced from their pair, none removes the source
+    UpdatePriceSource {
+        asset_info: AssetInfo,
+        source: Option<PriceSource>,
+    },
+    // record the pool prices used by the twap, feeders only, once per block
+    ObservePrices {
+        asset_infos: Vec<AssetInfo>,
+    },
+}
+
+/// PriceSource prices an asset from an oraiswap pair, the other asset of the pair is priced by the oracle
+#[cw_serde]
+pub struct PriceSource {
+    pub pair_addr: Addr,
+    // the average price over the window, at least 300 seconds
+    pub twap_window: u64,
+    // fed rates further than this ratio from the pool price are rejected
+    pub max_deviation: Option<Decimal>,
+}
+
+#[cw_serde]
+pub struct ExchangeRateFeed {
+    pub denom: String,
+    pub exchange_rate: Decimal,
 }
 
 /// QueryMsg is defines available query datas
@@ -68,6 +142,12 @@ pub enum OracleTreasuryQuery {
     TaxRate {},
     #[returns(TaxCapResponse)]
     TaxCap { denom: String },
+    // tax applied to a transfer of denom to address
+    #[returns(TaxRateForResponse)]
+    TaxRateFor {
+        denom: String,
+        address: Option<Addr>,
+    },
 }
 
 #[cw_serde]
@@ -83,6 +163,26 @@ pub enum OracleExchangeQuery {
         base_denom: Option<String>,
         quote_denoms: Vec<String>,
     },
+    // does not fail on stale rates, flags them
+    #[returns(ExchangeRateInfoResponse)]
+    ExchangeRateInfo { denom: String },
+    #[returns(RoundResponse)]
+    Round { denom: String },
+    // rate of quote_denom to Orai in effect at the given time
+    #[returns(ExchangeRateAtResponse)]
+    ExchangeRateAt { quote_denom: String, time: u64 },
+    #[returns(ExchangeRateHistoryResponse)]
+    ExchangeRateHistory {
+        quote_denom: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // quote amount for one base, triangulated through the Orai rates
+    #[returns(CrossRateResponse)]
+    CrossRate { base: AssetInfo, quote: AssetInfo },
+    // pool price of the asset with its fed rate for cross-checking
+    #[returns(PoolPriceResponse)]
+    PoolPrice { asset_info: AssetInfo },
 }
 
 #[cw_serde]
@@ -92,6 +192,17 @@ pub enum OracleContractQuery {
     ContractInfo {},
     #[returns(Coin)]
     RewardPool { denom: String },
+    #[returns(FeedersResponse)]
+    Feeders {},
+    #[returns(HistoryConfig)]
+    HistoryConfig {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
+    #[returns(TimelockResponse)]
+    Timelock {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 /// TaxRateResponse is data format returned from TreasuryRequest::TaxRate query
@@ -106,6 +217,15 @@ pub struct TaxCapResponse {
     pub cap: Uint128,
 }
 
+/// TaxRateForResponse is data format returned from TreasuryRequest::TaxRateFor query
+#[cw_serde]
+pub struct TaxRateForResponse {
+    pub denom: String,
+    pub rate: Decimal,
+    pub cap: Uint128,
+    pub exempt: bool,
+}
+
 /// ExchangeRateItem is data format returned from OracleRequest::ExchangeRates query
 #[cw_serde]
 pub struct ExchangeRateItem {
@@ -127,6 +247,116 @@ pub struct ExchangeRateResponse {
     pub item: ExchangeRateItem,
 }
 
+/// ExchangeRateInfoResponse is data format returned from OracleRequest::ExchangeRateInfo query
+#[cw_serde]
+pub struct ExchangeRateInfoResponse {
+    pub denom: String,
+    pub exchange_rate: Decimal,
+    pub last_updated: Option<u64>,
+    pub stale: bool,
+}
+
+/// RoundResponse is data format returned from OracleRequest::Round query
+#[cw_serde]
+pub struct RoundResponse {
+    pub denom: String,
+    pub round_id: u64,
+    pub start_time: u64,
+    pub submissions: Vec<(Addr, Decimal)>,
+}
+
+/// FeederConfig is the aggregation parameters of the feeder rounds
+#[cw_serde]
+pub struct FeederConfig {
+    // submissions needed before the median updates the rate
+    pub min_feeders: u32,
+    pub round_duration: u64,
+    // submissions further than this ratio from the median are rejected
+    pub max_deviation: Decimal,
+    // rates older than this are stale, zero means no expiry
+    pub max_age: u64,
+}
+
+impl Default for FeederConfig {
+    fn default() -> Self {
+        FeederConfig {
+            min_feeders: 1,
+            round_duration: 60,
+            max_deviation: Decimal::percent(10),
+            max_age: 0,
+        }
+    }
+}
+
+/// FeedersResponse is data format returned from WasmRequest::Feeders query
+#[cw_serde]
+pub struct FeedersResponse {
+    pub feeders: Vec<Addr>,
+    pub config: FeederConfig,
+}
+
+/// ExchangeRateAtResponse is data format returned from OracleRequest::ExchangeRateAt query
+#[cw_serde]
+pub struct ExchangeRateAtResponse {
+    pub denom: String,
+    pub time: u64,
+    pub exchange_rate: Decimal,
+    // time of the update that set the rate
+    pub updated_at: u64,
+}
+
+#[cw_serde]
+pub struct ExchangeRateHistoryItem {
+    pub time: u64,
+    pub exchange_rate: Decimal,
+}
+
+/// ExchangeRateHistoryResponse is data format returned from OracleRequest::ExchangeRateHistory query
+#[cw_serde]
+pub struct ExchangeRateHistoryResponse {
+    pub denom: String,
+    pub history: Vec<ExchangeRateHistoryItem>,
+}
+
+/// CrossRateResponse is data format returned from OracleRequest::CrossRate query
+#[cw_serde]
+pub struct CrossRateResponse {
+    pub base: AssetInfo,
+    pub quote: AssetInfo,
+    pub exchange_rate: Decimal,
+    // assets crossed from base to quote, both included
+    pub path: Vec<AssetInfo>,
+}
+
+/// PoolPriceResponse is data format returned from OracleRequest::PoolPrice query
+#[cw_serde]
+pub struct PoolPriceResponse {
+    pub asset_info: AssetInfo,
+    pub source: PriceSource,
+    pub pool_rate: Decimal,
+    pub exchange_rate: Option<Decimal>,
+    // distance of the fed rate to the pool rate, relative to the pool rate
+    pub deviation: Option<Decimal>,
+}
+
+/// HistoryConfig is the retention of the exchange rate history per denom
+#[cw_serde]
+pub struct HistoryConfig {
+    // zero means no limit on the number of entries
+    pub max_entries: u32,
+    // entries older than this are pruned, zero means no expiry
+    pub retention: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            max_entries: 100,
+            retention: 0,
+        }
+    }
+}
+
 /// ContractInfo is data format stored
 #[cw_serde]
 pub struct ContractInfo {
@@ -221,6 +451,20 @@ impl OracleContract {
         self.query(querier, request)
     }
 
+    pub fn query_tax_rate_for<T: Into<String>>(
+        &self,
+        querier: &QuerierWrapper,
+        denom: T,
+        address: Option<Addr>,
+    ) -> StdResult<TaxRateForResponse> {
+        let request = QueryMsg::Treasury(OracleTreasuryQuery::TaxRateFor {
+            denom: denom.into(),
+            address,
+        });
+
+        self.query(querier, request)
+    }
+
     // this is for CEX
     pub fn query_exchange_rate<T: Into<String>>(
         &self,
@@ -236,6 +480,17 @@ impl OracleContract {
         self.query(querier, request)
     }
 
+    pub fn query_cross_rate(
+        &self,
+        querier: &QuerierWrapper,
+        base: AssetInfo,
+        quote: AssetInfo,
+    ) -> StdResult<CrossRateResponse> {
+        let request = QueryMsg::Exchange(OracleExchangeQuery::CrossRate { base, quote });
+
+        self.query(querier, request)
+    }
+
     pub fn query_exchange_rates<T: Into<String>>(
         &self,
         querier: &QuerierWrapper,
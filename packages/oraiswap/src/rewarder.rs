@@ -1,8 +1,10 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, StdError, StdResult, Uint128};
+use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::admin::{PendingAdminResponse, TimelockResponse};
+use crate::asset::{Asset, AssetInfo};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -12,23 +14,77 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+
     ///////////////////
     /// Owner Operations
     ///////////////////
+    // proposes the new owner, who takes over with AcceptAdmin
     UpdateConfig {
         owner: Option<Addr>,
         staking_contract: Option<Addr>,
         distribution_interval: Option<u64>,
+        // paid to whoever triggers distribute all, zero amount to disable
+        keeper_bounty: Option<Asset>,
+    },
+    AcceptAdmin {},
+
+    // emission changes wait for the delay once it is set, the delay change too
+    UpdateTimelock {
+        delay: u64,
+    },
+    ExecuteTimelocked {
+        id: u64,
+    },
+    CancelTimelocked {
+        id: u64,
+    },
+
+    RegisterPool {
+        asset_info: AssetInfo,
+    },
+    DeregisterPool {
+        asset_info: AssetInfo,
     },
 
+    // set the emission schedule of a pool, none to use the staking rewards per sec
+    UpdateSchedule {
+        asset_info: AssetInfo,
+        schedule: Option<EmissionSchedule>,
+    },
+
+    // split the total emission between pools by the gauge weights, none to disable
+    UpdateGauge {
+        gauge_contract: Option<Addr>,
+        emission: Option<EmissionSchedule>,
+    },
+
+    // fund the reward budget with the native tokens sent, forwarded to the staking contract
+    Fund {},
+
     // distribute for a list of pools
     Distribute {
         asset_infos: Vec<AssetInfo>,
     },
+
+    // distribute for a page of the registered pools, paying the keeper bounty share of the pools distributed
+    DistributeAll {
+        start_after: Option<AssetInfo>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub enum Cw20HookMsg {
+    // fund the reward budget with the cw20 tokens sent
+    Fund {},
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    // reward assets already funded to the staking contract, their budgets start from these amounts
+    pub budgets: Vec<Asset>,
+}
 
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -39,6 +95,30 @@ pub enum QueryMsg {
     DistributionInfo { asset_info: AssetInfo },
     #[returns(RewardAmountPerSecondResponse)]
     RewardAmountPerSec { asset_info: AssetInfo },
+    #[returns(BudgetResponse)]
+    Budget {},
+    #[returns(GaugeResponse)]
+    Gauge {},
+    #[returns(PoolsResponse)]
+    Pools {
+        start_after: Option<AssetInfo>,
+        limit: Option<u32>,
+    },
+    #[returns(ScheduleResponse)]
+    Schedule { asset_info: AssetInfo },
+    #[returns(ProjectedEmissionResponse)]
+    ProjectedEmission {
+        asset_info: AssetInfo,
+        start_time: u64,
+        end_time: u64,
+    },
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
+    #[returns(TimelockResponse)]
+    Timelock {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
@@ -47,6 +127,7 @@ pub struct ConfigResponse {
     pub owner: Addr,
     pub staking_contract: Addr,
     pub distribution_interval: u64,
+    pub keeper_bounty: Option<Asset>,
 }
 
 // We define a custom struct for each query response
@@ -60,3 +141,202 @@ pub struct DistributionInfoResponse {
 pub struct RewardAmountPerSecondResponse {
     pub reward_amount: Uint128,
 }
+
+#[cw_serde]
+pub struct BudgetResponseItem {
+    pub info: AssetInfo,
+    pub funded: Uint128,
+    pub distributed: Uint128,
+    pub remaining: Uint128,
+}
+
+#[cw_serde]
+pub struct BudgetResponse {
+    pub budgets: Vec<BudgetResponseItem>,
+}
+
+#[cw_serde]
+pub struct GaugeResponse {
+    pub gauge_contract: Option<Addr>,
+    pub emission: Option<EmissionSchedule>,
+}
+
+#[cw_serde]
+pub struct PoolsResponse {
+    pub pools: Vec<AssetInfo>,
+}
+
+#[cw_serde]
+pub struct ScheduleResponse {
+    pub asset_info: AssetInfo,
+    pub schedule: Option<EmissionSchedule>,
+}
+
+#[cw_serde]
+pub struct ProjectedEmissionResponse {
+    pub asset_info: AssetInfo,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub amount: Uint128,
+}
+
+/// reward rate per second moving linearly from start_rate to end_rate
+#[cw_serde]
+pub struct ScheduleSegment {
+    pub start_time: u64,
+    pub end_time: u64,
+    pub start_rate: Uint128,
+    pub end_rate: Uint128,
+}
+
+#[cw_serde]
+pub enum EmissionSchedule {
+    /// sorted segments, nothing is emitted outside of them
+    Linear { segments: Vec<ScheduleSegment> },
+    /// the rate is halved at the end of every epoch, until cap is emitted
+    Halving {
+        start_time: u64,
+        epoch_duration: u64,
+        initial_rate: Uint128,
+        cap: Uint128,
+    },
+}
+
+impl EmissionSchedule {
+    pub fn validate(&self) -> StdResult<()> {
+        match self {
+            EmissionSchedule::Linear { segments } => {
+                if segments.is_empty() {
+                    return Err(StdError::generic_err("Schedule has no segment"));
+                }
+                let mut prev_end_time = 0u64;
+                for segment in segments {
+                    if segment.start_time >= segment.end_time {
+                        return Err(StdError::generic_err("Invalid segment time range"));
+                    }
+                    if segment.start_time < prev_end_time {
+                        return Err(StdError::generic_err(
+                            "Segments must be sorted and not overlap",
+                        ));
+                    }
+                    prev_end_time = segment.end_time;
+                }
+            }
+            EmissionSchedule::Halving { epoch_duration, .. } => {
+                if *epoch_duration == 0 {
+                    return Err(StdError::generic_err("Epoch duration must be positive"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// total amount emitted from the beginning of the schedule until time
+    pub fn emitted_until(&self, time: u64) -> StdResult<Uint128> {
+        match self {
+            EmissionSchedule::Linear { segments } => {
+                let mut emitted = Uint128::zero();
+                for segment in segments.iter().filter(|s| s.start_time < time) {
+                    let elapsed = time.min(segment.end_time) - segment.start_time;
+                    emitted += segment.emitted(elapsed as u128)?;
+                }
+                Ok(emitted)
+            }
+            EmissionSchedule::Halving {
+                start_time,
+                epoch_duration,
+                initial_rate,
+                cap,
+            } => {
+                if time <= *start_time {
+                    return Ok(Uint128::zero());
+                }
+                let elapsed = time - start_time;
+                let epochs = elapsed / epoch_duration;
+                let mut emitted = Uint128::zero();
+                for epoch in 0..epochs.min(128) {
+                    emitted += Uint128::from(initial_rate.u128() >> epoch)
+                        .checked_mul((*epoch_duration).into())?;
+                    if emitted >= *cap {
+                        return Ok(*cap);
+                    }
+                }
+                if epochs < 128 {
+                    emitted += Uint128::from(initial_rate.u128() >> epochs)
+                        .checked_mul((elapsed % epoch_duration).into())?;
+                }
+                Ok(emitted.min(*cap))
+            }
+        }
+    }
+
+    /// amount emitted between start_time and end_time
+    pub fn emission(&self, start_time: u64, end_time: u64) -> StdResult<Uint128> {
+        if start_time > end_time {
+            return Err(StdError::generic_err("Invalid time range"));
+        }
+        Ok(self
+            .emitted_until(end_time)?
+            .checked_sub(self.emitted_until(start_time)?)?)
+    }
+
+    /// reward rate per second at time
+    pub fn rate_at(&self, time: u64) -> StdResult<Uint128> {
+        match self {
+            EmissionSchedule::Linear { segments } => segments
+                .iter()
+                .find(|s| s.start_time <= time && time < s.end_time)
+                .map_or(Ok(Uint128::zero()), |segment| {
+                    Ok(segment.rate_after((time - segment.start_time) as u128))
+                }),
+            EmissionSchedule::Halving {
+                start_time,
+                epoch_duration,
+                initial_rate,
+                cap,
+            } => {
+                if time < *start_time || self.emitted_until(time)? >= *cap {
+                    return Ok(Uint128::zero());
+                }
+                let epochs = (time - start_time) / epoch_duration;
+                Ok(if epochs < 128 {
+                    Uint128::from(initial_rate.u128() >> epochs)
+                } else {
+                    Uint128::zero()
+                })
+            }
+        }
+    }
+}
+
+impl ScheduleSegment {
+    fn duration(&self) -> u128 {
+        (self.end_time - self.start_time) as u128
+    }
+
+    fn rate_after(&self, elapsed: u128) -> Uint128 {
+        if self.end_rate >= self.start_rate {
+            self.start_rate
+                + (self.end_rate - self.start_rate).multiply_ratio(elapsed, self.duration())
+        } else {
+            self.start_rate
+                - (self.start_rate - self.end_rate).multiply_ratio(elapsed, self.duration())
+        }
+    }
+
+    /// exact integral of the rate, rounded down
+    fn emitted(&self, elapsed: u128) -> StdResult<Uint128> {
+        let duration = self.duration();
+        Ok(if self.end_rate >= self.start_rate {
+            // start_rate * t + diff * t^2 / 2d
+            self.start_rate.checked_mul(elapsed.into())?
+                + (self.end_rate - self.start_rate).multiply_ratio(elapsed * elapsed, 2 * duration)
+        } else {
+            // end_rate * t + diff * t * (2d - t) / 2d
+            self.end_rate.checked_mul(elapsed.into())?
+                + (self.start_rate - self.end_rate)
+                    .multiply_ratio(elapsed * (2 * duration - elapsed), 2 * duration)
+        })
+    }
+}
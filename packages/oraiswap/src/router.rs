@@ -75,6 +75,8 @@ pub enum QueryMsg {
     SimulateSwapOperations {
         offer_amount: Uint128,
         operations: Vec<SwapOperation>,
+        /// receiver of the final return, taxed the same as on execution
+        to: Option<Addr>,
     },
 }
 
@@ -1,5 +1,6 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
+use crate::admin::{PendingAdminResponse, TimelockResponse};
 use crate::asset::{Asset, AssetInfo};
 use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
@@ -22,14 +23,32 @@ pub enum ExecuteMsg {
     ////////////////////////
     /// Owner operations ///
     ////////////////////////
+    // proposes the new owner, who takes over with AcceptAdmin
     UpdateConfig {
         rewarder: Option<Addr>,
         owner: Option<Addr>,
+        router: Option<Addr>,
+    },
+    AcceptAdmin {},
+    // reward rate changes wait for the delay once it is set, the delay change too
+    UpdateTimelock {
+        delay: u64,
+    },
+    ExecuteTimelocked {
+        id: u64,
+    },
+    CancelTimelocked {
+        id: u64,
     },
     RegisterAsset {
         asset_info: AssetInfo, // can be ow20 token or native token
         staking_token: Addr,
     },
+    // unbonded amount of the pool can be claimed after the unbonding period in seconds
+    UpdateUnbondingPeriod {
+        asset_info: AssetInfo,
+        unbonding_period: u64,
+    },
     DeprecateStakingToken {
         asset_info: AssetInfo,
         new_staking_token: Addr,
@@ -44,14 +63,74 @@ pub enum ExecuteMsg {
     DepositReward {
         rewards: Vec<Asset>,
     },
+    // allow the stakers to emergency unbond, forfeiting their rewards
+    SetEmergency {
+        emergency: bool,
+    },
+    // allow a campaign reward asset with the minimum reward of a campaign, none disallows it
+    UpdateCampaignRewardAsset {
+        reward_info: AssetInfo,
+        minimum_reward: Option<Uint128>,
+    },
+    // set the lock durations with their reward boost, and the penalty for unlocking early
+    UpdateLockTiers {
+        tiers: Vec<LockTier>,
+        early_unlock_penalty: Decimal,
+    },
 
     ////////////////////////
     /// User operations ///
     ////////////////////////
+    /// Stream the sent native reward to the pool stakers between start and end time
+    CreateCampaign {
+        asset_info: AssetInfo,
+        reward: Asset,
+        start_time: u64,
+        end_time: u64,
+    },
+    /// Refund the reward streamed while nothing was bonded to the funder, after the campaign end
+    RefundCampaign {
+        campaign_id: u64,
+    },
     Unbond {
         asset_info: AssetInfo,
         amount: Uint128,
     },
+    /// Lock bonded amount for one of the lock tier durations to boost its reward weight
+    Lock {
+        asset_info: AssetInfo,
+        amount: Uint128,
+        duration: u64,
+    },
+    /// Release a lock, before the unlock time the penalty is deducted from the bond amount
+    Unlock {
+        asset_info: AssetInfo,
+        lock_id: u64,
+    },
+    /// Release the expired locks of any staker, removing their boost from the pool
+    ReleaseExpiredLocks {
+        staker_addr: Addr,
+        asset_info: AssetInfo,
+    },
+    /// Release the unbonded amount of all matured claims
+    ClaimUnbonded {},
+    /// Return the bond amount right away without computing rewards, only when emergency is set.
+    /// The unbonding period is skipped, locks not expired yet pay the early unlock penalty and
+    /// the settled pending reward is forfeited to the pool
+    EmergencyUnbond {
+        asset_info: AssetInfo,
+    },
+    /// Send the rewards of the sender to the recipient, the sender itself resets it
+    SetRewardRecipient {
+        recipient: Addr,
+    },
+    /// Move unlocked bond amount to another staker without unbonding,
+    /// rewards accrued until now stay with the sender
+    TransferPosition {
+        asset_info: AssetInfo,
+        amount: Uint128,
+        to: Addr,
+    },
     /// Withdraw pending rewards
     Withdraw {
         // If the asset token is not given, then all rewards are withdrawn
@@ -75,6 +154,27 @@ pub enum ExecuteMsg {
         staker_addr: Addr,
         prev_staking_token_amount: Uint128,
     },
+    /// Swap the pending rewards of the pool into its two assets through the router,
+    /// then provide liquidity and stake the LP tokens
+    Compound {
+        asset_info: AssetInfo,
+        // also bounds the swap balancing the two assets against the pool price,
+        // required when that swap is needed
+        slippage_tolerance: Option<Decimal>,
+        // minimum base denom amount received by swapping each reward asset out of the pool,
+        // keyed by the reward asset, the pool must hold the base denom
+        reward_minimum_receives: Vec<Asset>,
+    },
+    /// Hook to balance the compounded assets, then provide them as liquidity.
+    /// The amounts held are the given ones plus the balance changes since prev_balances
+    CompoundHook {
+        asset_info: AssetInfo,
+        staker_addr: Addr,
+        assets: [Asset; 2],
+        prev_balances: [Uint128; 2],
+        slippage_tolerance: Option<Decimal>,
+        balanced: bool,
+    },
     UpdateListStakers {
         asset_info: AssetInfo,
         stakers: Vec<Addr>,
@@ -84,7 +184,15 @@ pub enum ExecuteMsg {
 #[cw_serde]
 pub enum Cw20HookMsg {
     // this call from LP token contract
-    Bond { asset_info: AssetInfo },
+    Bond {
+        asset_info: AssetInfo,
+    },
+    // this call from the campaign reward token contract
+    CreateCampaign {
+        asset_info: AssetInfo,
+        start_time: u64,
+        end_time: u64,
+    },
 }
 
 /// We currently take no arguments for migrations
@@ -108,6 +216,13 @@ pub struct AmountInfo {
 pub enum QueryMsg {
     #[returns(ConfigResponse)]
     Config {},
+    #[returns(PendingAdminResponse)]
+    PendingAdmin {},
+    #[returns(TimelockResponse)]
+    Timelock {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     #[returns(PoolInfoResponse)]
     PoolInfo { asset_info: AssetInfo },
     #[returns(RewardsPerSecResponse)]
@@ -126,6 +241,49 @@ pub enum QueryMsg {
         // so can convert or throw error
         order: Option<i32>,
     },
+    #[returns(CampaignResponse)]
+    Campaign { campaign_id: u64 },
+    // campaigns of the pool that have not ended
+    #[returns(CampaignsResponse)]
+    Campaigns { asset_info: AssetInfo },
+    // reward assets allowed in campaigns with their minimum reward
+    #[returns(CampaignRewardAssetsResponse)]
+    CampaignRewardAssets {},
+    #[returns(CampaignRewardsResponse)]
+    CampaignRewards {
+        staker_addr: Addr,
+        asset_info: AssetInfo,
+    },
+    #[returns(ClaimsResponse)]
+    Claims { staker_addr: Addr },
+    #[returns(RewardRecipientResponse)]
+    RewardRecipient { staker_addr: Addr },
+    #[returns(PoolAprResponse)]
+    PoolApr { asset_info: AssetInfo },
+    /// bond amount at the start of the height
+    #[returns(BondAtResponse)]
+    BondAt {
+        staker_addr: Addr,
+        asset_info: AssetInfo,
+        height: u64,
+    },
+    #[returns(TotalBondAtResponse)]
+    TotalBondAt { asset_info: AssetInfo, height: u64 },
+    #[returns(LockTiersResponse)]
+    LockTiers {},
+    #[returns(LocksResponse)]
+    Locks {
+        staker_addr: Addr,
+        asset_info: AssetInfo,
+    },
+}
+
+#[cw_serde]
+pub struct LockTier {
+    /// lock duration in seconds
+    pub duration: u64,
+    /// reward weight multiplier of the locked amount, at least one
+    pub boost: Decimal,
 }
 
 // We define a custom struct for each query response
@@ -136,6 +294,8 @@ pub struct ConfigResponse {
     pub oracle_addr: Addr,
     pub factory_addr: Addr,
     pub base_denom: String,
+    pub router_addr: Option<Addr>,
+    pub emergency: bool,
 }
 
 #[cw_serde]
@@ -153,6 +313,7 @@ pub struct PoolInfoResponse {
     pub pending_reward: Uint128,
     pub migration_index_snapshot: Option<Decimal>,
     pub migration_deprecated_staking_token: Option<Addr>,
+    pub unbonding_period: u64,
 }
 
 // We define a custom struct for each query response
@@ -172,3 +333,111 @@ pub struct RewardInfoResponseItem {
     // with the new lp token
     pub should_migrate: Option<bool>,
 }
+
+#[cw_serde]
+pub struct LockTiersResponse {
+    pub tiers: Vec<LockTier>,
+    pub early_unlock_penalty: Decimal,
+}
+
+#[cw_serde]
+pub struct LocksResponse {
+    pub staker_addr: Addr,
+    pub asset_info: AssetInfo,
+    pub bond_amount: Uint128,
+    // bond amount plus the boost of the locks, used as the reward share
+    pub effective_bond_amount: Uint128,
+    pub locks: Vec<LockResponse>,
+}
+
+#[cw_serde]
+pub struct LockResponse {
+    pub lock_id: u64,
+    pub amount: Uint128,
+    pub boost_amount: Uint128,
+    pub unlock_time: u64,
+}
+
+#[cw_serde]
+pub struct BondAtResponse {
+    pub staker_addr: Addr,
+    pub asset_info: AssetInfo,
+    pub height: u64,
+    pub bond_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct TotalBondAtResponse {
+    pub asset_info: AssetInfo,
+    pub height: u64,
+    pub total_bond_amount: Uint128,
+}
+
+/// values are in the base denom, rewards per second are annualized
+#[cw_serde]
+pub struct PoolAprResponse {
+    pub asset_info: AssetInfo,
+    pub base_denom: String,
+    pub total_bond_amount: Uint128,
+    pub tvl: Uint128,
+    pub annual_reward_value: Uint128,
+    pub apr: Decimal,
+    pub rewards: Vec<RewardAprItem>,
+}
+
+#[cw_serde]
+pub struct RewardAprItem {
+    pub info: AssetInfo,
+    pub reward_per_sec: Uint128,
+    pub price: Decimal,
+    pub annual_reward_value: Uint128,
+    pub apr: Decimal,
+}
+
+#[cw_serde]
+pub struct RewardRecipientResponse {
+    pub staker_addr: Addr,
+    pub recipient: Addr,
+}
+
+#[cw_serde]
+pub struct ClaimsResponse {
+    pub claims: Vec<Claim>,
+}
+
+#[cw_serde]
+pub struct Claim {
+    pub asset_info: AssetInfo,
+    pub staking_token: Addr,
+    pub amount: Uint128,
+    /// block time in seconds the amount can be claimed
+    pub release_at: u64,
+}
+
+#[cw_serde]
+pub struct CampaignResponse {
+    pub campaign_id: u64,
+    pub asset_info: AssetInfo,
+    pub funder: Addr,
+    pub reward: Asset,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub unused_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CampaignsResponse {
+    pub campaigns: Vec<CampaignResponse>,
+}
+
+#[cw_serde]
+pub struct CampaignRewardAssetsResponse {
+    pub reward_assets: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct CampaignRewardsResponse {
+    pub staker_addr: Addr,
+    pub asset_info: AssetInfo,
+    pub pending_rewards: Vec<Asset>,
+}